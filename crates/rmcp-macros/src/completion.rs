@@ -0,0 +1,137 @@
+use darling::{FromMeta, ast::NestedMeta};
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::{ImplItemFn, ReturnType};
+
+#[derive(FromMeta, Default, Debug)]
+#[darling(default)]
+pub struct CompletionAttribute {
+    /// The name of the prompt whose argument is completed
+    pub prompt: Option<String>,
+    /// The URI template of the resource template whose variable is completed
+    pub resource: Option<String>,
+    /// The name of the argument or variable
+    pub argument: Option<String>,
+}
+
+pub fn completion(attr: TokenStream, input: TokenStream) -> syn::Result<TokenStream> {
+    let attribute = if attr.is_empty() {
+        Default::default()
+    } else {
+        let attr_args = NestedMeta::parse_meta_list(attr)?;
+        CompletionAttribute::from_list(&attr_args)?
+    };
+    let mut fn_item = syn::parse2::<ImplItemFn>(input.clone())?;
+    let fn_ident = &fn_item.sig.ident;
+
+    let Some(argument) = attribute.argument else {
+        return Err(syn::Error::new_spanned(
+            fn_ident,
+            "#[completion] requires an `argument`",
+        ));
+    };
+    let attr_expr = match (attribute.prompt, attribute.resource) {
+        (Some(prompt), None) => quote! {
+            rmcp::handler::server::router::completion::CompletionAttr::prompt(#prompt, #argument)
+        },
+        (None, Some(resource)) => quote! {
+            rmcp::handler::server::router::completion::CompletionAttr::resource(#resource, #argument)
+        },
+        _ => {
+            return Err(syn::Error::new_spanned(
+                fn_ident,
+                "#[completion] requires exactly one of `prompt` and `resource`",
+            ));
+        }
+    };
+
+    let completion_attr_fn_ident = format_ident!("{}_completion_attr", fn_ident);
+    let completion_attr_fn = syn::parse2::<ImplItemFn>(quote! {
+        pub fn #completion_attr_fn_ident() -> rmcp::handler::server::router::completion::CompletionAttr {
+            #attr_expr
+        }
+    })?;
+
+    // Modify the input function for async support (same as tool macro)
+    if fn_item.sig.asyncness.is_some() {
+        // 1. remove asyncness from sig
+        // 2. make return type: `futures::future::BoxFuture<'_, #ReturnType>`
+        // 3. make body: { Box::pin(async move { #body }) }
+        let new_output = syn::parse2::<ReturnType>({
+            let mut lt = quote! { 'static };
+            if let Some(receiver) = fn_item.sig.receiver() {
+                if let Some((_, receiver_lt)) = receiver.reference.as_ref() {
+                    if let Some(receiver_lt) = receiver_lt {
+                        lt = quote! { #receiver_lt };
+                    } else {
+                        lt = quote! { '_ };
+                    }
+                }
+            }
+            match &fn_item.sig.output {
+                syn::ReturnType::Default => {
+                    quote! { -> ::std::pin::Pin<Box<dyn ::std::future::Future<Output = ()> + Send + #lt>> }
+                }
+                syn::ReturnType::Type(_, ty) => {
+                    quote! { -> ::std::pin::Pin<Box<dyn ::std::future::Future<Output = #ty> + Send + #lt>> }
+                }
+            }
+        })?;
+        let prev_block = &fn_item.block;
+        let new_block = syn::parse2::<syn::Block>(quote! {
+           { Box::pin(async move #prev_block ) }
+        })?;
+        fn_item.sig.asyncness = None;
+        fn_item.sig.output = new_output;
+        fn_item.block = new_block;
+    }
+
+    Ok(quote! {
+        #completion_attr_fn
+        #fn_item
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_completion_macro() -> syn::Result<()> {
+        let attr = quote! { prompt = "sql_query", argument = "table" };
+        let input = quote! {
+            async fn complete_table(&self, CompletionValue(value): CompletionValue) -> Vec<String> {
+                vec![]
+            }
+        };
+        let result_str = completion(attr, input)?.to_string();
+        assert!(result_str.contains("complete_table_completion_attr"));
+        assert!(result_str.contains("CompletionAttr :: prompt (\"sql_query\" , \"table\")"));
+        assert!(result_str.contains("Box :: pin"));
+
+        let attr = quote! { resource = "users://{id}", argument = "id" };
+        let input = quote! {
+            fn complete_id(&self) -> Vec<String> {
+                vec![]
+            }
+        };
+        let result_str = completion(attr, input)?.to_string();
+        assert!(result_str.contains("CompletionAttr :: resource"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_completion_requires_target() {
+        let input = quote! {
+            fn complete_table(&self) -> Vec<String> {
+                vec![]
+            }
+        };
+        let attr = quote! { argument = "table" };
+        assert!(completion(attr, input.clone()).is_err());
+        let attr = quote! { prompt = "sql_query", resource = "users://{id}", argument = "id" };
+        assert!(completion(attr, input.clone()).is_err());
+        let attr = quote! { prompt = "sql_query" };
+        assert!(completion(attr, input).is_err());
+    }
+}
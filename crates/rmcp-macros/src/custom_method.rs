@@ -0,0 +1,96 @@
+use darling::FromDeriveInput;
+use proc_macro2::TokenStream;
+use quote::{ToTokens, quote};
+use syn::{DeriveInput, Expr, Lit, Meta, Type};
+
+#[derive(FromDeriveInput, Debug)]
+#[darling(attributes(custom_method))]
+struct CustomMethodAttribute {
+    method: String,
+    #[darling(default, with = parse_type, map = Some)]
+    result: Option<Type>,
+    #[darling(default)]
+    capability: Option<String>,
+}
+
+/// Accept `result = SearchResult` as well as `result = "Vec<SearchResult>"`, which is not a valid
+/// expression.
+fn parse_type(meta: &Meta) -> darling::Result<Type> {
+    let Meta::NameValue(name_value) = meta else {
+        return Err(darling::Error::unexpected_type("list").with_span(meta));
+    };
+    let result = match &name_value.value {
+        Expr::Lit(expr) => match &expr.lit {
+            Lit::Str(lit) => lit.parse(),
+            lit => return Err(darling::Error::unexpected_lit_type(lit)),
+        },
+        expr => syn::parse2(expr.to_token_stream()),
+    };
+    result.map_err(darling::Error::from)
+}
+
+pub fn custom_method(input: TokenStream) -> syn::Result<TokenStream> {
+    let input = syn::parse2::<DeriveInput>(input)?;
+    let CustomMethodAttribute {
+        method,
+        result,
+        capability,
+    } = CustomMethodAttribute::from_derive_input(&input)?;
+    if method.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &input.ident,
+            "custom method name must not be empty",
+        ));
+    }
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let result = result.map_or_else(|| quote! { rmcp::model::EmptyObject }, |ty| quote! { #ty });
+    let capability = capability.map(|capability| {
+        quote! { const CAPABILITY: &'static str = #capability; }
+    });
+    Ok(quote! {
+        impl #impl_generics rmcp::model::CustomMethod for #ident #ty_generics #where_clause {
+            const METHOD: &'static str = #method;
+            #capability
+            type Params = Self;
+            type Result = #result;
+        }
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_custom_method_derive() -> syn::Result<()> {
+        let input = quote! {
+            #[custom_method(method = "acme/search", result = SearchResult)]
+            struct Search {
+                query: String,
+            }
+        };
+        let output = custom_method(input)?.to_string();
+        assert!(output.contains("\"acme/search\""));
+        assert!(output.contains("type Result = SearchResult"));
+        assert!(!output.contains("CAPABILITY"));
+
+        let input = quote! {
+            #[custom_method(method = "acme/list", result = "Vec<Hit>", capability = "acme")]
+            struct List;
+        };
+        let output = custom_method(input)?.to_string();
+        assert!(output.contains("type Result = Vec < Hit >"));
+        assert!(output.contains("const CAPABILITY : & 'static str = \"acme\""));
+        Ok(())
+    }
+
+    #[test]
+    fn test_custom_method_requires_method() {
+        let input = quote! {
+            #[custom_method(result = SearchResult)]
+            struct Search;
+        };
+        assert!(custom_method(input).is_err());
+    }
+}
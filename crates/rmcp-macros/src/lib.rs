@@ -4,12 +4,19 @@
 use proc_macro::TokenStream;
 
 mod common;
+mod completion;
+mod custom_method;
+mod lint;
 mod prompt;
 mod prompt_handler;
 mod prompt_router;
+mod resource;
+mod resource_handler;
+mod resource_router;
 mod task_handler;
 mod tool;
 mod tool_handler;
+mod tool_parameters;
 mod tool_router;
 /// # tool
 ///
@@ -25,6 +32,9 @@ mod tool_router;
 /// | `description`     | `String`                   | A description of the tool. The document of this function will be used. |
 /// | `input_schema`    | `Expr`                     | A JSON Schema object defining the expected parameters for the tool. If not provide, if will use the json schema of its argument with type `Parameters<T>` |
 /// | `annotations`     | `ToolAnnotationsAttribute` | Additional tool information. Defaults to `None`. |
+/// | `scopes`          | `[&str]`                   | Scopes the caller must be granted, see `rmcp::handler::server::authorization`. Applied by `#[tool_router]`. |
+/// | `timeout`         | `String`                   | How long a call may run, such as `"500ms"`, `"30s"` or `"5m"`. Applied by `#[tool_router]`. |
+/// | `infer_annotations` | `bool`                   | Fill the annotation hints which aren't set from the verb starting the name, e.g. `get_*` is read-only and `delete_*` destructive. |
 ///
 /// ## Example
 ///
@@ -54,6 +64,15 @@ pub fn tool(attr: TokenStream, input: TokenStream) -> TokenStream {
 /// | :-        | :-            | :-    |
 /// | `router`  | `Ident`       | The name of the router function to be generated. Defaults to `tool_router`. |
 /// | `vis`     | `Visibility`  | The visibility of the generated router function. Defaults to empty. |
+/// | `lints`   | `String`      | `"allow"`, `"warn"` or `"deny"` the problems of the tools. Defaults to `"allow"`. |
+///
+/// ## Lints
+///
+/// With `lints = "warn"` or `"deny"`, the tools are checked for an invalid name, a missing
+/// description, and a name suggesting a read-only or destructive tool without the matching
+/// annotation hint. Warnings are reported as deprecations, so `#[allow(deprecated)]` on a tool
+/// silences its own. The fields of the parameters aren't visible here, see
+/// [`macro@tool_parameters`].
 ///
 /// ## Example
 ///
@@ -112,6 +131,35 @@ pub fn tool_router(attr: TokenStream, input: TokenStream) -> TokenStream {
         .into()
 }
 
+/// # tool_parameters
+///
+/// This macro checks at compile time that every field of the parameters of a tool has a
+/// description, from a doc comment or `#[schemars(description = "...")]`. Skipped and
+/// flattened fields are not checked.
+///
+/// ## Usage
+///
+/// | field     | type          | usage |
+/// | :-        | :-            | :-    |
+/// | `lints`   | `String`      | `"allow"`, `"warn"` or `"deny"` the fields without a description. Defaults to `"warn"`. |
+///
+/// ## Example
+///
+/// ```rust,ignore
+/// #[tool_parameters(lints = "deny")]
+/// #[derive(serde::Deserialize, schemars::JsonSchema)]
+/// pub struct ReadFileParams {
+///     /// The path of the file, relative to the workspace
+///     pub path: String,
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn tool_parameters(attr: TokenStream, input: TokenStream) -> TokenStream {
+    tool_parameters::tool_parameters(attr.into(), input.into())
+        .unwrap_or_else(|err| err.to_compile_error())
+        .into()
+}
+
 /// # tool_handler
 ///
 /// This macro will generate the handler for `tool_call` and `list_tools` methods in the implementation block, by using an existing `ToolRouter` instance.
@@ -201,7 +249,9 @@ pub fn prompt(attr: TokenStream, input: TokenStream) -> TokenStream {
 ///
 /// This macro generates a prompt router based on functions marked with `#[rmcp::prompt]` in an implementation block.
 ///
-/// It creates a function that returns a `PromptRouter` instance.
+/// It creates a function that returns a `PromptRouter` instance. Functions marked with
+/// `#[rmcp::completion]` are added as completions, and the enum-typed fields of the
+/// `Parameters<T>` of a prompt complete to their variants.
 ///
 /// ## Usage
 ///
@@ -238,6 +288,7 @@ pub fn prompt_router(attr: TokenStream, input: TokenStream) -> TokenStream {
 /// # prompt_handler
 ///
 /// This macro generates handler methods for `get_prompt` and `list_prompts` in the implementation block, using an existing `PromptRouter` instance.
+/// A `complete` method answering from the completions of the router is generated too, unless the block already defines one.
 ///
 /// ## Usage
 ///
@@ -267,6 +318,142 @@ pub fn prompt_handler(attr: TokenStream, input: TokenStream) -> TokenStream {
         .into()
 }
 
+/// # completion
+///
+/// This macro is used to mark a function as the completion handler of a prompt argument, or of a
+/// resource template variable. Functions in a `#[prompt_router]` block are added to its router.
+///
+/// This will generate a function that returns the attribute of this completion, with type
+/// `rmcp::handler::server::router::completion::CompletionAttr`. The function can extract the
+/// value typed so far with `CompletionValue`, and the arguments already resolved with
+/// `CompletionContext`. It returns a `Vec<String>` or a `CompletionInfo`, truncated to 100
+/// values by the router.
+///
+/// ## Usage
+///
+/// | field      | type     | usage |
+/// | :-         | :-       | :-    |
+/// | `prompt`   | `String` | The name of the prompt whose argument is completed. |
+/// | `resource` | `String` | The URI template of the resource template whose variable is completed. |
+/// | `argument` | `String` | The name of the argument or variable. Required. |
+///
+/// Exactly one of `prompt` and `resource` must be given.
+///
+/// ## Example
+///
+/// ```rust,ignore
+/// #[completion(prompt = "sql_query", argument = "columns")]
+/// async fn columns(
+///     &self,
+///     CompletionValue(value): CompletionValue,
+///     context: CompletionContext,
+/// ) -> Vec<String> {
+///     let table = context.get_argument("table");
+///     // Suggest the columns of `table` starting with `value`
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn completion(attr: TokenStream, input: TokenStream) -> TokenStream {
+    completion::completion(attr.into(), input.into())
+        .unwrap_or_else(|err| err.to_compile_error())
+        .into()
+}
+
+/// # resource
+///
+/// This macro is used to mark a function as a resource handler.
+///
+/// This will generate a function that returns the attribute of this resource, with type
+/// `rmcp::handler::server::router::resource::ResourceAttr`. A `uri` containing `{` is a URI
+/// template, whose variables can be extracted with `Parameters<T>`.
+///
+/// ## Usage
+///
+/// | field         | type     | usage |
+/// | :-            | :-       | :-    |
+/// | `uri`         | `String` | The URI or URI template of the resource. Required. |
+/// | `name`        | `String` | The name of the resource. If not provided, it defaults to the function name. |
+/// | `title`       | `String` | A human readable title of the resource. |
+/// | `description` | `String` | A description of the resource. The document of this function will be used if not provided. |
+/// | `mime_type`   | `String` | The MIME type of the resource. |
+/// | `size`        | `u32`    | The size of the resource in bytes. Not available for templates. |
+/// | `icons`       | `Expr`   | Optional list of icons for the resource. |
+/// | `meta`        | `Expr`   | Optional metadata for the resource. Not available for templates. |
+///
+/// ## Example
+///
+/// ```rust,ignore
+/// #[resource(uri = "users://{id}/profile", mime_type = "application/json")]
+/// async fn user_profile(&self, Parameters(user): Parameters<UserId>) -> Result<String, ErrorData> {
+///     // Read the profile of `user.id`
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn resource(attr: TokenStream, input: TokenStream) -> TokenStream {
+    resource::resource(attr.into(), input.into())
+        .unwrap_or_else(|err| err.to_compile_error())
+        .into()
+}
+
+/// # resource_router
+///
+/// This macro generates a resource router based on functions marked with `#[rmcp::resource]` in an implementation block.
+///
+/// ## Usage
+///
+/// | field     | type          | usage |
+/// | :-        | :-            | :-    |
+/// | `router`  | `Ident`       | The name of the router function to be generated. Defaults to `resource_router`. |
+/// | `vis`     | `Visibility`  | The visibility of the generated router function. Defaults to empty. |
+///
+/// ## Example
+///
+/// ```rust,ignore
+/// #[resource_router]
+/// impl MyServer {
+///     #[resource(uri = "config://app")]
+///     async fn config(&self) -> String {
+///         // Read the configuration
+///     }
+///
+///     pub fn new() -> Self {
+///         Self {
+///             resource_router: Self::resource_router(),
+///         }
+///     }
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn resource_router(attr: TokenStream, input: TokenStream) -> TokenStream {
+    resource_router::resource_router(attr.into(), input.into())
+        .unwrap_or_else(|err| err.to_compile_error())
+        .into()
+}
+
+/// # resource_handler
+///
+/// This macro generates handler methods for `read_resource`, `list_resources` and `list_resource_templates` in the implementation block, using an existing `ResourceRouter` instance.
+///
+/// ## Usage
+///
+/// | field     | type   | usage |
+/// | :-        | :-     | :-    |
+/// | `router`  | `Expr` | The expression to access the `ResourceRouter` instance. Defaults to `self.resource_router`. |
+///
+/// ## Example
+/// ```rust,ignore
+/// #[resource_handler]
+/// impl ServerHandler for MyServer {
+///     // ...implement other handler methods
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn resource_handler(attr: TokenStream, input: TokenStream) -> TokenStream {
+    resource_handler::resource_handler(attr.into(), input.into())
+        .unwrap_or_else(|err| err.to_compile_error())
+        .into()
+}
+
 /// # task_handler
 ///
 /// Generates basic task-handling methods (`enqueue_task` and `list_tasks`) for a server handler
@@ -280,3 +467,34 @@ pub fn task_handler(attr: TokenStream, input: TokenStream) -> TokenStream {
         .unwrap_or_else(|err| err.to_compile_error())
         .into()
 }
+
+/// # CustomMethod
+///
+/// Derive `rmcp::model::CustomMethod` on the params type of a custom method, so it can be sent
+/// with `Peer::send_custom` or `Peer::notify_custom` and routed with `CustomRouter`.
+///
+/// ## Usage
+///
+/// | field        | type     | usage |
+/// | :-           | :-       | :-    |
+/// | `method`     | `String` | The JSON-RPC method name. Required. |
+/// | `result`     | `Type`   | The result type of the method. Defaults to `rmcp::model::EmptyObject`. |
+/// | `capability` | `String` | The `experimental` capability advertising the method. Defaults to the method name. |
+///
+/// ## Example
+///
+/// ```rust,ignore
+/// #[derive(Serialize, Deserialize, CustomMethod)]
+/// #[custom_method(method = "acme/search", result = SearchResult, capability = "acme")]
+/// pub struct Search {
+///     pub query: String,
+/// }
+///
+/// let result: SearchResult = client.send_custom::<Search>(Search { query: "rmcp".into() }).await?;
+/// ```
+#[proc_macro_derive(CustomMethod, attributes(custom_method))]
+pub fn custom_method(input: TokenStream) -> TokenStream {
+    custom_method::custom_method(input.into())
+        .unwrap_or_else(|err| err.to_compile_error())
+        .into()
+}
@@ -0,0 +1,227 @@
+//! Compile-time checks of the tools of a `#[tool_router]` and of `#[tool_parameters]` structs
+//!
+//! Stable proc macros can't emit warnings, so each one is the use of a deprecated constant,
+//! which `#[allow(deprecated)]` silences.
+
+use darling::FromMeta;
+use proc_macro2::{Span, TokenStream};
+use quote::{format_ident, quote_spanned};
+
+use crate::tool::ToolAnnotationsAttribute;
+
+/// What `lints = "..."` does with the problems found.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LintLevel {
+    #[default]
+    Allow,
+    Warn,
+    Deny,
+}
+
+impl FromMeta for LintLevel {
+    fn from_string(value: &str) -> darling::Result<Self> {
+        match value {
+            "allow" => Ok(Self::Allow),
+            "warn" => Ok(Self::Warn),
+            "deny" => Ok(Self::Deny),
+            _ => Err(darling::Error::unknown_value(value)),
+        }
+    }
+}
+
+/// What a tool does, guessed from the first word of its name.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verb {
+    ReadOnly,
+    Additive,
+    Idempotent,
+    Destructive,
+}
+
+const READ_ONLY: &[&str] = &[
+    "get", "list", "read", "search", "find", "fetch", "show", "describe", "query", "view", "count",
+    "lookup", "inspect",
+];
+const ADDITIVE: &[&str] = &["create", "add", "insert", "append", "upload", "register"];
+const IDEMPOTENT: &[&str] = &["set", "put", "update", "enable", "disable"];
+const DESTRUCTIVE: &[&str] = &[
+    "delete",
+    "remove",
+    "drop",
+    "destroy",
+    "purge",
+    "erase",
+    "kill",
+    "truncate",
+    "wipe",
+    "reset",
+    "clear",
+    "revoke",
+    "uninstall",
+];
+
+impl Verb {
+    pub fn of(name: &str) -> Option<Self> {
+        let word = first_word(name);
+        [
+            (READ_ONLY, Self::ReadOnly),
+            (ADDITIVE, Self::Additive),
+            (IDEMPOTENT, Self::Idempotent),
+            (DESTRUCTIVE, Self::Destructive),
+        ]
+        .into_iter()
+        .find_map(|(words, verb)| words.contains(&word.as_str()).then_some(verb))
+    }
+
+    /// Fill the hints of `annotations` this verb implies, keeping the ones already set.
+    pub fn infer(self, annotations: &mut ToolAnnotationsAttribute) {
+        let (read_only, destructive, idempotent) = match self {
+            Self::ReadOnly => (true, None, None),
+            Self::Additive => (false, Some(false), None),
+            Self::Idempotent => (false, None, Some(true)),
+            Self::Destructive => (false, Some(true), None),
+        };
+        annotations.read_only_hint.get_or_insert(read_only);
+        if let Some(destructive) = destructive {
+            annotations.destructive_hint.get_or_insert(destructive);
+        }
+        if let Some(idempotent) = idempotent {
+            annotations.idempotent_hint.get_or_insert(idempotent);
+        }
+    }
+}
+
+/// The first word of a snake, kebab, dotted or camel case name, in lower case.
+fn first_word(name: &str) -> String {
+    let mut word = String::new();
+    for (index, c) in name.char_indices() {
+        if matches!(c, '_' | '-' | '.') || (index > 0 && c.is_ascii_uppercase()) {
+            if !word.is_empty() {
+                break;
+            }
+            if !c.is_ascii_uppercase() {
+                continue;
+            }
+        }
+        word.push(c.to_ascii_lowercase());
+    }
+    word
+}
+
+/// Why `name` is rejected by the tool name validation of rmcp, if it is.
+pub fn invalid_name(name: &str) -> Option<String> {
+    if name.is_empty() {
+        return Some("tool name cannot be empty".into());
+    }
+    if name.len() > 128 {
+        return Some(format!(
+            "tool name `{name}` exceeds the maximum length of 128 characters"
+        ));
+    }
+    let mut invalid = name
+        .chars()
+        .filter(|c| !(c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.')))
+        .collect::<Vec<_>>();
+    invalid.dedup();
+    (!invalid.is_empty()).then(|| {
+        format!(
+            "tool name `{name}` contains invalid characters {invalid:?}, only A-Z, a-z, 0-9, `_`, `-` and `.` are allowed"
+        )
+    })
+}
+
+/// The problems of the tool `name`, whose annotations are the explicit ones, plus the inferred
+/// ones with `infer_annotations`.
+pub fn check_tool(
+    name: &str,
+    has_description: bool,
+    annotations: Option<&ToolAnnotationsAttribute>,
+) -> Vec<String> {
+    let mut problems = Vec::from_iter(invalid_name(name));
+    if !has_description {
+        problems.push(format!(
+            "tool `{name}` has no description, add a doc comment or `description = \"...\"`"
+        ));
+    }
+    let hint_missing =
+        |hint: fn(&ToolAnnotationsAttribute) -> Option<bool>| annotations.and_then(hint).is_none();
+    match Verb::of(name) {
+        Some(Verb::ReadOnly) if hint_missing(|a| a.read_only_hint) => problems.push(format!(
+            "tool `{name}` looks read-only, set `annotations(read_only_hint = ...)` or `infer_annotations`"
+        )),
+        Some(Verb::Destructive) if hint_missing(|a| a.destructive_hint) => {
+            problems.push(format!(
+                "tool `{name}` looks destructive, set `annotations(destructive_hint = ...)` or `infer_annotations`"
+            ))
+        }
+        _ => {}
+    }
+    problems
+}
+
+/// Report `problems` at `span` as `level` requires.
+pub fn report(level: LintLevel, span: Span, problems: &[String]) -> syn::Result<TokenStream> {
+    match level {
+        LintLevel::Allow => Ok(TokenStream::new()),
+        LintLevel::Warn => Ok(problems
+            .iter()
+            .map(|problem| {
+                let lint = format_ident!("rmcp_tool_lint", span = span);
+                quote_spanned! {span=>
+                    const _: () = {
+                        #[deprecated(note = #problem)]
+                        #[allow(non_upper_case_globals)]
+                        const #lint: () = ();
+                        #lint
+                    };
+                }
+            })
+            .collect()),
+        LintLevel::Deny => match problems
+            .iter()
+            .map(|problem| syn::Error::new(span, problem))
+            .reduce(|mut error, next| {
+                error.combine(next);
+                error
+            }) {
+            Some(error) => Err(error),
+            None => Ok(TokenStream::new()),
+        },
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_verb() {
+        assert_eq!(Verb::of("get_weather"), Some(Verb::ReadOnly));
+        assert_eq!(Verb::of("listFiles"), Some(Verb::ReadOnly));
+        assert_eq!(Verb::of("delete-file"), Some(Verb::Destructive));
+        assert_eq!(Verb::of("_add_item"), Some(Verb::Additive));
+        assert_eq!(Verb::of("SetLevel"), Some(Verb::Idempotent));
+        assert_eq!(Verb::of("getter"), None);
+        assert_eq!(Verb::of("status"), None);
+    }
+
+    #[test]
+    fn test_invalid_name() {
+        assert!(invalid_name("git.status-v2_x").is_none());
+        assert!(invalid_name("").is_some());
+        assert!(invalid_name("say hello").is_some());
+        assert!(invalid_name(&"a".repeat(129)).is_some());
+    }
+
+    #[test]
+    fn test_check_tool() {
+        assert_eq!(check_tool("status", true, None), Vec::<String>::new());
+        assert_eq!(check_tool("delete_file", false, None).len(), 2);
+        let annotations = ToolAnnotationsAttribute {
+            read_only_hint: Some(true),
+            ..Default::default()
+        };
+        assert!(check_tool("get_file", true, Some(&annotations)).is_empty());
+        assert_eq!(check_tool("get_file", true, None).len(), 1);
+    }
+}
@@ -51,21 +51,38 @@ pub fn prompt_handler(attr: TokenStream, input: TokenStream) -> syn::Result<Toke
     let list_prompts_impl: ImplItem = parse_quote! {
         async fn list_prompts(
             &self,
-            _request: Option<PaginatedRequestParams>,
-            _context: RequestContext<RoleServer>,
+            request: Option<PaginatedRequestParams>,
+            context: RequestContext<RoleServer>,
         ) -> Result<ListPromptsResult, rmcp::ErrorData> {
-            let prompts = #router_expr.list_all();
+            let page = #router_expr.list_page_for(request.as_ref(), &context)?;
             Ok(ListPromptsResult {
-                prompts,
+                prompts: page.items,
                 meta: #meta,
-                next_cursor: None,
+                next_cursor: page.next_cursor,
             })
         }
     };
 
+    // Add complete implementation
+    let complete_impl: ImplItem = parse_quote! {
+        async fn complete(
+            &self,
+            request: rmcp::model::CompleteRequestParams,
+            context: rmcp::service::RequestContext<rmcp::RoleServer>,
+        ) -> Result<rmcp::model::CompleteResult, rmcp::ErrorData> {
+            let completion_context =
+                rmcp::handler::server::completion::CompletionRequestContext::new(
+                    self, request, context,
+                );
+            #router_expr.complete(completion_context).await
+        }
+    };
+
     // Check if methods already exist and replace them if they do
     let mut has_get_prompt = false;
     let mut has_list_prompts = false;
+    // an existing `complete` is kept, as it may complete more than the prompts
+    let mut has_complete = false;
 
     for item in &mut impl_block.items {
         if let ImplItem::Fn(fn_item) = item {
@@ -78,6 +95,7 @@ pub fn prompt_handler(attr: TokenStream, input: TokenStream) -> syn::Result<Toke
                     *item = list_prompts_impl.clone();
                     has_list_prompts = true;
                 }
+                "complete" => has_complete = true,
                 _ => {}
             }
         }
@@ -90,6 +108,9 @@ pub fn prompt_handler(attr: TokenStream, input: TokenStream) -> syn::Result<Toke
     if !has_list_prompts {
         impl_block.items.push(list_prompts_impl);
     }
+    if !has_complete {
+        impl_block.items.push(complete_impl);
+    }
 
     Ok(quote! {
         #impl_block
@@ -116,6 +137,30 @@ mod test {
         assert!(result_str.contains("PromptContext") && result_str.contains("new"));
         assert!(result_str.contains("async fn list_prompts"));
         assert!(result_str.contains("ListPromptsResult"));
+        assert!(result_str.contains("async fn complete"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_prompt_handler_keeps_complete() -> syn::Result<()> {
+        let input = quote! {
+            impl ServerHandler for MyPromptHandler {
+                async fn complete(
+                    &self,
+                    request: CompleteRequestParams,
+                    context: RequestContext<RoleServer>,
+                ) -> Result<CompleteResult, ErrorData> {
+                    Ok(CompleteResult::default())
+                }
+            }
+        };
+
+        let result = prompt_handler(TokenStream::new(), input)?;
+        let result_str = result.to_string();
+
+        assert_eq!(result_str.matches("async fn complete").count(), 1);
+        assert!(!result_str.contains("CompletionRequestContext"));
 
         Ok(())
     }
@@ -141,7 +186,7 @@ mod test {
         assert!(
             result_str.contains("self")
                 && result_str.contains("get_prompt_router")
-                && result_str.contains("list_all")
+                && result_str.contains("list_page")
         );
 
         Ok(())
@@ -1,7 +1,9 @@
-use darling::FromMeta;
+use darling::{FromMeta, ast::NestedMeta};
 use proc_macro2::TokenStream;
 use quote::{format_ident, quote};
-use syn::{ImplItem, ItemImpl, Visibility, parse_quote};
+use syn::{ImplItem, ItemImpl, Meta, Visibility, parse_quote};
+
+use crate::{common::find_parameters_type_impl, prompt::PromptAttribute};
 
 #[derive(FromMeta, Debug, Default)]
 #[darling(default)]
@@ -14,7 +16,7 @@ pub fn prompt_router(attr: TokenStream, input: TokenStream) -> syn::Result<Token
     let attribute = if attr.is_empty() {
         Default::default()
     } else {
-        let attr_args = darling::ast::NestedMeta::parse_meta_list(attr)?;
+        let attr_args = NestedMeta::parse_meta_list(attr)?;
         PromptRouterAttribute::from_list(&attr_args)?
     };
 
@@ -31,33 +33,54 @@ pub fn prompt_router(attr: TokenStream, input: TokenStream) -> syn::Result<Token
 
     for item in &mut impl_block.items {
         if let ImplItem::Fn(fn_item) = item {
-            let has_prompt_attr = fn_item.attrs.iter().any(|attr| {
+            let prompt_attr = fn_item.attrs.iter().find(|attr| {
                 attr.path()
                     .segments
                     .last()
                     .map(|seg| seg.ident == "prompt")
                     .unwrap_or(false)
             });
+            let has_completion_attr = fn_item.attrs.iter().any(|attr| {
+                attr.path()
+                    .segments
+                    .last()
+                    .map(|seg| seg.ident == "completion")
+                    .unwrap_or(false)
+            });
 
-            if has_prompt_attr {
+            if let Some(prompt_attr) = prompt_attr {
                 let fn_ident = &fn_item.sig.ident;
                 let attr_fn_ident = format_ident!("{}_prompt_attr", fn_ident);
 
-                // Check what parameters the function takes
-                let mut param_names = Vec::new();
-                let mut param_types = Vec::new();
+                // Use the exact same pattern as tool_router
+                prompt_route_fn_calls.push(quote! {
+                    .with_route((Self::#attr_fn_ident(), Self::#fn_ident))
+                });
 
-                for input in &fn_item.sig.inputs {
-                    if let syn::FnArg::Typed(pat_type) = input {
-                        // Extract parameter pattern and type
-                        param_types.push(&*pat_type.ty);
-                        param_names.push(&*pat_type.pat);
+                // Enum-typed parameters complete to their variants, unless the arguments
+                // are not generated from the parameters
+                let has_arguments = match &prompt_attr.meta {
+                    Meta::List(list) => NestedMeta::parse_meta_list(list.tokens.clone())
+                        .ok()
+                        .and_then(|args| PromptAttribute::from_list(&args).ok())
+                        .is_none_or(|attribute| attribute.arguments.is_some()),
+                    _ => false,
+                };
+                if !has_arguments {
+                    if let Some(params_ty) = find_parameters_type_impl(fn_item) {
+                        prompt_route_fn_calls.push(quote! {
+                            .with_argument_values(
+                                Self::#attr_fn_ident().name,
+                                rmcp::handler::server::prompt::cached_argument_values_from_schema::<#params_ty>(),
+                            )
+                        });
                     }
                 }
-
-                // Use the exact same pattern as tool_router
+            } else if has_completion_attr {
+                let fn_ident = &fn_item.sig.ident;
+                let attr_fn_ident = format_ident!("{}_completion_attr", fn_ident);
                 prompt_route_fn_calls.push(quote! {
-                    .with_route((Self::#attr_fn_ident(), Self::#fn_ident))
+                    .with_completion((Self::#attr_fn_ident(), Self::#fn_ident))
                 });
             }
         }
@@ -105,6 +128,38 @@ mod test {
         assert!(result_str.contains("PromptRouter :: new"));
         assert!(result_str.contains("greeting_prompt_prompt_attr"));
         assert!(result_str.contains("code_review_prompt_prompt_attr"));
+        assert!(
+            result_str.contains(
+                "cached_argument_values_from_schema :: < Parameters < CodeReviewArgs > >"
+            )
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_prompt_router_completions() -> syn::Result<()> {
+        let input = quote! {
+            impl MyPromptHandler {
+                #[prompt(arguments = None)]
+                async fn sql_query(&self, Parameters(args): Parameters<SqlQueryArgs>) -> Result<Vec<PromptMessage>, Error> {
+                    Ok(vec![])
+                }
+
+                #[completion(prompt = "sql_query", argument = "table")]
+                async fn tables(&self, CompletionValue(value): CompletionValue) -> Vec<String> {
+                    vec![]
+                }
+            }
+        };
+
+        let result_str = prompt_router(TokenStream::new(), input)?.to_string();
+
+        assert!(
+            result_str
+                .contains("with_completion ((Self :: tables_completion_attr () , Self :: tables))")
+        );
+        assert!(!result_str.contains("cached_argument_values_from_schema"));
 
         Ok(())
     }
@@ -0,0 +1,254 @@
+use darling::{FromMeta, ast::NestedMeta};
+use proc_macro2::{Span, TokenStream};
+use quote::{format_ident, quote};
+use syn::{Expr, Ident, ImplItemFn, ReturnType};
+
+use crate::common::extract_doc_line;
+
+#[derive(FromMeta, Default, Debug)]
+#[darling(default)]
+pub struct ResourceAttribute {
+    /// The URI of the resource, or a URI template if it contains `{`
+    pub uri: Option<String>,
+    /// The name of the resource
+    pub name: Option<String>,
+    /// Human readable title of resource
+    pub title: Option<String>,
+    /// Optional description of the resource
+    pub description: Option<String>,
+    /// MIME type of the resource
+    pub mime_type: Option<String>,
+    /// Size of the resource in bytes, not available for templates
+    pub size: Option<u32>,
+    /// Optional icons for the resource
+    pub icons: Option<Expr>,
+    /// Optional metadata for the resource, not available for templates
+    pub meta: Option<Expr>,
+}
+
+pub struct ResolvedResourceAttribute {
+    pub uri: String,
+    pub name: String,
+    pub title: Option<String>,
+    pub description: Option<Expr>,
+    pub mime_type: Option<String>,
+    pub size: Option<u32>,
+    pub icons: Option<Expr>,
+    pub meta: Option<Expr>,
+}
+
+impl ResolvedResourceAttribute {
+    pub fn into_fn(self, fn_ident: Ident) -> syn::Result<ImplItemFn> {
+        let Self {
+            uri,
+            name,
+            title,
+            description,
+            mime_type,
+            size,
+            icons,
+            meta,
+        } = self;
+        let optional_str = |value: Option<String>| match value {
+            Some(value) => quote! { Some(#value.into()) },
+            None => quote! { None },
+        };
+        let title = optional_str(title);
+        let mime_type = optional_str(mime_type);
+        let description = if let Some(description) = description {
+            quote! { Some(#description.into()) }
+        } else {
+            quote! { None }
+        };
+        let icons = if let Some(icons) = icons {
+            quote! { Some(#icons) }
+        } else {
+            quote! { None }
+        };
+        let raw = if uri.contains('{') {
+            if size.is_some() || meta.is_some() {
+                return Err(syn::Error::new(
+                    Span::call_site(),
+                    "`size` and `meta` are not available for resource templates",
+                ));
+            }
+            quote! {
+                rmcp::model::RawResourceTemplate {
+                    uri_template: #uri.into(),
+                    name: #name.into(),
+                    title: #title,
+                    description: #description,
+                    mime_type: #mime_type,
+                    icons: #icons,
+                }
+            }
+        } else {
+            let size = if let Some(size) = size {
+                quote! { Some(#size) }
+            } else {
+                quote! { None }
+            };
+            let meta = if let Some(meta) = meta {
+                quote! { Some(#meta) }
+            } else {
+                quote! { None }
+            };
+            quote! {
+                rmcp::model::RawResource {
+                    uri: #uri.into(),
+                    name: #name.into(),
+                    title: #title,
+                    description: #description,
+                    mime_type: #mime_type,
+                    size: #size,
+                    icons: #icons,
+                    meta: #meta,
+                }
+            }
+        };
+        let tokens = quote! {
+            pub fn #fn_ident() -> rmcp::handler::server::router::resource::ResourceAttr {
+                rmcp::model::AnnotateAble::no_annotation(#raw).into()
+            }
+        };
+        syn::parse2::<ImplItemFn>(tokens)
+    }
+}
+
+pub fn resource(attr: TokenStream, input: TokenStream) -> syn::Result<TokenStream> {
+    let attribute = if attr.is_empty() {
+        Default::default()
+    } else {
+        let attr_args = NestedMeta::parse_meta_list(attr)?;
+        ResourceAttribute::from_list(&attr_args)?
+    };
+    let mut fn_item = syn::parse2::<ImplItemFn>(input.clone())?;
+    let fn_ident = &fn_item.sig.ident;
+
+    let Some(uri) = attribute.uri else {
+        return Err(syn::Error::new_spanned(
+            fn_ident,
+            "#[resource] requires a `uri`",
+        ));
+    };
+
+    let resource_attr_fn_ident = format_ident!("{}_resource_attr", fn_ident);
+
+    let name = attribute.name.unwrap_or_else(|| fn_ident.to_string());
+    let description = if let Some(s) = attribute.description {
+        Some(Expr::Lit(syn::ExprLit {
+            attrs: Vec::new(),
+            lit: syn::Lit::Str(syn::LitStr::new(&s, Span::call_site())),
+        }))
+    } else {
+        fn_item.attrs.iter().try_fold(None, extract_doc_line)?
+    };
+
+    let resolved_resource_attr = ResolvedResourceAttribute {
+        uri,
+        name,
+        title: attribute.title,
+        description,
+        mime_type: attribute.mime_type,
+        size: attribute.size,
+        icons: attribute.icons,
+        meta: attribute.meta,
+    };
+    let resource_attr_fn = resolved_resource_attr.into_fn(resource_attr_fn_ident)?;
+
+    // Modify the input function for async support (same as tool macro)
+    if fn_item.sig.asyncness.is_some() {
+        // 1. remove asyncness from sig
+        // 2. make return type: `futures::future::BoxFuture<'_, #ReturnType>`
+        // 3. make body: { Box::pin(async move { #body }) }
+        let new_output = syn::parse2::<ReturnType>({
+            let mut lt = quote! { 'static };
+            if let Some(receiver) = fn_item.sig.receiver() {
+                if let Some((_, receiver_lt)) = receiver.reference.as_ref() {
+                    if let Some(receiver_lt) = receiver_lt {
+                        lt = quote! { #receiver_lt };
+                    } else {
+                        lt = quote! { '_ };
+                    }
+                }
+            }
+            match &fn_item.sig.output {
+                syn::ReturnType::Default => {
+                    quote! { -> ::std::pin::Pin<Box<dyn ::std::future::Future<Output = ()> + Send + #lt>> }
+                }
+                syn::ReturnType::Type(_, ty) => {
+                    quote! { -> ::std::pin::Pin<Box<dyn ::std::future::Future<Output = #ty> + Send + #lt>> }
+                }
+            }
+        })?;
+        let prev_block = &fn_item.block;
+        let new_block = syn::parse2::<syn::Block>(quote! {
+           { Box::pin(async move #prev_block ) }
+        })?;
+        fn_item.sig.asyncness = None;
+        fn_item.sig.output = new_output;
+        fn_item.block = new_block;
+    }
+
+    Ok(quote! {
+        #resource_attr_fn
+        #fn_item
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_resource_macro() -> syn::Result<()> {
+        let attr = quote! {
+            uri = "config://app",
+            mime_type = "application/json"
+        };
+        let input = quote! {
+            /// The application configuration
+            async fn config(&self) -> String {
+                "{}".to_string()
+            }
+        };
+        let result_str = resource(attr, input)?.to_string();
+        assert!(result_str.contains("config_resource_attr"));
+        assert!(result_str.contains("RawResource {"));
+        assert!(result_str.contains("The application configuration"));
+        assert!(result_str.contains("Box :: pin"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_resource_template_macro() -> syn::Result<()> {
+        let attr = quote! { uri = "users://{id}/profile" };
+        let input = quote! {
+            fn profile(&self, Parameters(user): Parameters<User>) -> String {
+                user.id.to_string()
+            }
+        };
+        let result_str = resource(attr, input)?.to_string();
+        assert!(result_str.contains("RawResourceTemplate"));
+        assert!(result_str.contains("uri_template"));
+
+        let attr = quote! { uri = "users://{id}/profile", size = 10 };
+        let input = quote! {
+            fn profile(&self) -> String {
+                String::new()
+            }
+        };
+        assert!(resource(attr, input).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_resource_requires_uri() {
+        let input = quote! {
+            fn profile(&self) -> String {
+                String::new()
+            }
+        };
+        assert!(resource(TokenStream::new(), input).is_err());
+    }
+}
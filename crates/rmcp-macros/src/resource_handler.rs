@@ -0,0 +1,133 @@
+use darling::FromMeta;
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{Expr, ImplItem, ItemImpl, parse_quote};
+
+#[derive(FromMeta, Debug, Default)]
+#[darling(default)]
+pub struct ResourceHandlerAttribute {
+    pub router: Option<Expr>,
+}
+
+pub fn resource_handler(attr: TokenStream, input: TokenStream) -> syn::Result<TokenStream> {
+    let attribute = if attr.is_empty() {
+        Default::default()
+    } else {
+        let attr_args = darling::ast::NestedMeta::parse_meta_list(attr)?;
+        ResourceHandlerAttribute::from_list(&attr_args)?
+    };
+
+    let mut impl_block = syn::parse2::<ItemImpl>(input)?;
+
+    let router_expr = attribute
+        .router
+        .unwrap_or_else(|| syn::parse2(quote! { self.resource_router }).unwrap());
+
+    let read_resource_impl: ImplItem = parse_quote! {
+        async fn read_resource(
+            &self,
+            request: rmcp::model::ReadResourceRequestParams,
+            context: rmcp::service::RequestContext<rmcp::RoleServer>,
+        ) -> Result<rmcp::model::ReadResourceResult, rmcp::ErrorData> {
+            let resource_context = rmcp::handler::server::resource::ResourceContext::new(
+                self,
+                request.uri,
+                context,
+            );
+            #router_expr.read_resource(resource_context).await
+        }
+    };
+
+    let list_resources_impl: ImplItem = parse_quote! {
+        async fn list_resources(
+            &self,
+            request: Option<rmcp::model::PaginatedRequestParams>,
+            _context: rmcp::service::RequestContext<rmcp::RoleServer>,
+        ) -> Result<rmcp::model::ListResourcesResult, rmcp::ErrorData> {
+            let page = #router_expr.list_resources_page(request.as_ref())?;
+            Ok(rmcp::model::ListResourcesResult {
+                resources: page.items,
+                meta: None,
+                next_cursor: page.next_cursor,
+            })
+        }
+    };
+
+    let list_resource_templates_impl: ImplItem = parse_quote! {
+        async fn list_resource_templates(
+            &self,
+            request: Option<rmcp::model::PaginatedRequestParams>,
+            _context: rmcp::service::RequestContext<rmcp::RoleServer>,
+        ) -> Result<rmcp::model::ListResourceTemplatesResult, rmcp::ErrorData> {
+            let page = #router_expr.list_resource_templates_page(request.as_ref())?;
+            Ok(rmcp::model::ListResourceTemplatesResult {
+                resource_templates: page.items,
+                meta: None,
+                next_cursor: page.next_cursor,
+            })
+        }
+    };
+
+    // Replace the methods if they already exist, add them otherwise
+    let mut generated = [
+        ("read_resource", Some(read_resource_impl)),
+        ("list_resources", Some(list_resources_impl)),
+        (
+            "list_resource_templates",
+            Some(list_resource_templates_impl),
+        ),
+    ];
+    for item in &mut impl_block.items {
+        if let ImplItem::Fn(fn_item) = item {
+            let ident = fn_item.sig.ident.to_string();
+            if let Some((_, method)) = generated.iter_mut().find(|(name, _)| *name == ident) {
+                if let Some(method) = method.take() {
+                    *item = method;
+                }
+            }
+        }
+    }
+    impl_block
+        .items
+        .extend(generated.into_iter().filter_map(|(_, method)| method));
+
+    Ok(quote! {
+        #impl_block
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_resource_handler_macro() -> syn::Result<()> {
+        let input = quote! {
+            impl ServerHandler for MyServer {
+                async fn read_resource(&self) {}
+            }
+        };
+
+        let result_str = resource_handler(TokenStream::new(), input)?.to_string();
+        assert_eq!(result_str.matches("async fn read_resource").count(), 1);
+        assert!(result_str.contains("ResourceContext :: new"));
+        assert!(result_str.contains("async fn list_resources"));
+        assert!(result_str.contains("async fn list_resource_templates"));
+        assert!(result_str.contains("self . resource_router . read_resource"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_resource_handler_with_custom_router() -> syn::Result<()> {
+        let attr = quote! { router = self.router.resource_router };
+        let input = quote! {
+            impl ServerHandler for MyServer {}
+        };
+
+        let result_str = resource_handler(attr, input)?.to_string();
+        assert!(result_str.contains("self . router . resource_router . list_resources"));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,98 @@
+use darling::FromMeta;
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::{ImplItem, ItemImpl, Visibility, parse_quote};
+
+#[derive(FromMeta, Debug, Default)]
+#[darling(default)]
+pub struct ResourceRouterAttribute {
+    pub router: Option<String>,
+    pub vis: Option<Visibility>,
+}
+
+pub fn resource_router(attr: TokenStream, input: TokenStream) -> syn::Result<TokenStream> {
+    let attribute = if attr.is_empty() {
+        Default::default()
+    } else {
+        let attr_args = darling::ast::NestedMeta::parse_meta_list(attr)?;
+        ResourceRouterAttribute::from_list(&attr_args)?
+    };
+
+    let mut impl_block = syn::parse2::<ItemImpl>(input)?;
+    let self_ty = &impl_block.self_ty;
+
+    let router_fn_ident = attribute
+        .router
+        .map(|s| format_ident!("{}", s))
+        .unwrap_or_else(|| format_ident!("resource_router"));
+    let vis = attribute.vis.unwrap_or(Visibility::Inherited);
+
+    let mut resource_route_fn_calls = Vec::new();
+
+    for item in &impl_block.items {
+        if let ImplItem::Fn(fn_item) = item {
+            let has_resource_attr = fn_item.attrs.iter().any(|attr| {
+                attr.path()
+                    .segments
+                    .last()
+                    .map(|seg| seg.ident == "resource")
+                    .unwrap_or(false)
+            });
+
+            if has_resource_attr {
+                let fn_ident = &fn_item.sig.ident;
+                let attr_fn_ident = format_ident!("{}_resource_attr", fn_ident);
+                resource_route_fn_calls.push(quote! {
+                    .with_route((Self::#attr_fn_ident(), Self::#fn_ident))
+                });
+            }
+        }
+    }
+
+    let router_fn: ImplItem = parse_quote! {
+        #vis fn #router_fn_ident() -> rmcp::handler::server::router::resource::ResourceRouter<#self_ty> {
+            rmcp::handler::server::router::resource::ResourceRouter::new()
+                #(#resource_route_fn_calls)*
+        }
+    };
+
+    impl_block.items.push(router_fn);
+
+    Ok(quote! {
+        #impl_block
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_resource_router_macro() -> syn::Result<()> {
+        let attr = quote! { router = "resources", vis = "pub" };
+        let input = quote! {
+            impl MyServer {
+                #[resource(uri = "config://app")]
+                async fn config(&self) -> String {
+                    String::new()
+                }
+
+                #[resource(uri = "users://{id}")]
+                fn user(&self, Parameters(user): Parameters<User>) -> String {
+                    String::new()
+                }
+
+                fn helper(&self) {}
+            }
+        };
+
+        let result_str = resource_router(attr, input)?.to_string();
+        assert!(result_str.contains("pub fn resources"));
+        assert!(result_str.contains("ResourceRouter :: new"));
+        assert!(result_str.contains("config_resource_attr"));
+        assert!(result_str.contains("user_resource_attr"));
+        assert!(!result_str.contains("helper_resource_attr"));
+
+        Ok(())
+    }
+}
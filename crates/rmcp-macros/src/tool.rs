@@ -3,7 +3,10 @@ use proc_macro2::{Span, TokenStream};
 use quote::{ToTokens, format_ident, quote};
 use syn::{Expr, Ident, ImplItemFn, LitStr, ReturnType, parse_quote};
 
-use crate::common::{extract_doc_line, none_expr};
+use crate::{
+    common::{extract_doc_line, none_expr},
+    lint::Verb,
+};
 
 /// Check if a type is Json<T> and extract the inner type T
 fn extract_json_inner_type(ty: &syn::Type) -> Option<&syn::Type> {
@@ -93,6 +96,54 @@ pub struct ToolAttribute {
     pub icons: Option<Expr>,
     /// Optional metadata for the tool
     pub meta: Option<Expr>,
+    /// Scopes the caller must be granted, applied by `#[tool_router]`
+    pub scopes: Option<Vec<LitStr>>,
+    /// How long a call may run, such as `"500ms"` or `"30s"`, applied by `#[tool_router]`
+    pub timeout: Option<LitStr>,
+    /// Fill the annotation hints which aren't set from the verb the name starts with
+    pub infer_annotations: bool,
+}
+
+impl ToolAttribute {
+    /// The explicit annotations, completed by the inferred ones with `infer_annotations`.
+    pub fn resolved_annotations(&self, name: &str) -> Option<ToolAnnotationsAttribute> {
+        let verb = Verb::of(name).filter(|_| self.infer_annotations);
+        match (self.annotations.clone(), verb) {
+            (annotations, None) => annotations,
+            (annotations, Some(verb)) => {
+                let mut annotations = annotations.unwrap_or_default();
+                verb.infer(&mut annotations);
+                Some(annotations)
+            }
+        }
+    }
+}
+
+/// The milliseconds of a duration with a unit among `ms`, `s`, `m` and `h`.
+pub fn parse_timeout(timeout: &LitStr) -> syn::Result<u64> {
+    let value = timeout.value();
+    let value = value.trim();
+    let split = value
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(value.len());
+    let (amount, unit) = value.split_at(split);
+    let factor = match unit.trim() {
+        "ms" => 1,
+        "s" => 1_000,
+        "m" => 60_000,
+        "h" => 3_600_000,
+        _ => {
+            return Err(syn::Error::new_spanned(
+                timeout,
+                "expected a duration such as \"500ms\", \"30s\", \"5m\" or \"1h\"",
+            ));
+        }
+    };
+    amount
+        .parse::<u64>()
+        .ok()
+        .and_then(|amount| amount.checked_mul(factor))
+        .ok_or_else(|| syn::Error::new_spanned(timeout, "invalid duration"))
 }
 
 pub struct ResolvedToolAttribute {
@@ -164,7 +215,7 @@ impl ResolvedToolAttribute {
     }
 }
 
-#[derive(FromMeta, Debug, Default)]
+#[derive(FromMeta, Debug, Default, Clone)]
 #[darling(default)]
 pub struct ToolAnnotationsAttribute {
     /// A human-readable title for the tool.
@@ -208,10 +259,18 @@ pub fn tool(attr: TokenStream, input: TokenStream) -> syn::Result<TokenStream> {
         let attr_args = NestedMeta::parse_meta_list(attr)?;
         ToolAttribute::from_list(&attr_args)?
     };
+    if let Some(timeout) = &attribute.timeout {
+        parse_timeout(timeout)?;
+    }
     let mut fn_item = syn::parse2::<ImplItemFn>(input.clone())?;
     let fn_ident = &fn_item.sig.ident;
 
     let tool_attr_fn_ident = format_ident!("{}_tool_attr", fn_ident);
+    let name = attribute
+        .name
+        .clone()
+        .unwrap_or_else(|| fn_ident.to_string());
+    let annotations = attribute.resolved_annotations(&name);
     let input_schema_expr = if let Some(input_schema) = attribute.input_schema {
         input_schema
     } else {
@@ -233,7 +292,7 @@ pub fn tool(attr: TokenStream, input: TokenStream) -> syn::Result<TokenStream> {
             })?
         }
     };
-    let annotations_expr = if let Some(annotations) = attribute.annotations {
+    let annotations_expr = if let Some(annotations) = annotations {
         let ToolAnnotationsAttribute {
             title,
             read_only_hint,
@@ -281,7 +340,7 @@ pub fn tool(attr: TokenStream, input: TokenStream) -> syn::Result<TokenStream> {
         fn_item.attrs.iter().try_fold(None, extract_doc_line)?
     };
     let resolved_tool_attr = ResolvedToolAttribute {
-        name: attribute.name.unwrap_or_else(|| fn_ident.to_string()),
+        name,
         description: description_expr,
         input_schema: input_schema_expr,
         output_schema: output_schema_expr,
@@ -349,6 +408,58 @@ mod test {
         Ok(())
     }
 
+    #[test]
+    fn test_scopes_attr() -> syn::Result<()> {
+        let attr = quote! { scopes = ["repo:read", "repo:write"] };
+        let attr_args = NestedMeta::parse_meta_list(attr)?;
+        let attribute = ToolAttribute::from_list(&attr_args)?;
+        let scopes = attribute
+            .scopes
+            .unwrap_or_default()
+            .iter()
+            .map(LitStr::value)
+            .collect::<Vec<_>>();
+        assert_eq!(scopes, ["repo:read", "repo:write"]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_timeout() {
+        let parse = |value: &str| parse_timeout(&LitStr::new(value, Span::call_site())).ok();
+        assert_eq!(parse("500ms"), Some(500));
+        assert_eq!(parse("30s"), Some(30_000));
+        assert_eq!(parse("2m"), Some(120_000));
+        assert_eq!(parse("1h"), Some(3_600_000));
+        assert_eq!(parse("30"), None);
+        assert_eq!(parse("s"), None);
+        assert!(tool(quote! { timeout = "soon" }, quote! { fn f(&self) {} }).is_err());
+    }
+
+    #[test]
+    fn test_infer_annotations() -> syn::Result<()> {
+        let input = quote! {
+            fn delete_file(&self) {}
+        };
+        let result = tool(
+            quote! { infer_annotations, annotations(title = "Delete") },
+            input.clone(),
+        )?
+        .to_string();
+        assert!(
+            result.contains("read_only_hint : Some (false . into ())"),
+            "{result}"
+        );
+        assert!(
+            result.contains("destructive_hint : Some (true . into ())"),
+            "{result}"
+        );
+        assert!(result.contains("Some (\"Delete\" . into ())"), "{result}");
+
+        let result = tool(quote! {}, input)?.to_string();
+        assert!(result.contains("annotations : None"), "{result}");
+        Ok(())
+    }
+
     #[test]
     fn test_doc_comment_description() -> syn::Result<()> {
         let attr = quote! {}; // No explicit description
@@ -46,13 +46,14 @@ pub fn tool_handler(attr: TokenStream, input: TokenStream) -> syn::Result<TokenS
     let tool_list_fn = quote! {
         async fn list_tools(
             &self,
-            _request: Option<rmcp::model::PaginatedRequestParams>,
-            _context: rmcp::service::RequestContext<rmcp::RoleServer>,
+            request: Option<rmcp::model::PaginatedRequestParams>,
+            context: rmcp::service::RequestContext<rmcp::RoleServer>,
         ) -> Result<rmcp::model::ListToolsResult, rmcp::ErrorData> {
+            let page = #router.list_page_for(request.as_ref(), &context)?;
             Ok(rmcp::model::ListToolsResult{
-                tools: #router.list_all(),
+                tools: page.items,
                 meta: #result_meta,
-                next_cursor: None,
+                next_cursor: page.next_cursor,
             })
         }
     };
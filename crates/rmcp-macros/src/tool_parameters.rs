@@ -0,0 +1,128 @@
+//! ```ignore
+//! #[rmcp::tool_parameters]
+//! #[derive(serde::Deserialize, schemars::JsonSchema)]
+//! struct Params {
+//!     /// The path of the file
+//!     path: String,
+//! }
+//! ```
+
+use darling::{FromMeta, ast::NestedMeta};
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{Attribute, Field, ItemStruct, spanned::Spanned};
+
+use crate::lint::{LintLevel, report};
+
+#[derive(FromMeta)]
+#[darling(default)]
+pub struct ToolParametersAttribute {
+    pub lints: LintLevel,
+}
+
+impl Default for ToolParametersAttribute {
+    fn default() -> Self {
+        Self {
+            lints: LintLevel::Warn,
+        }
+    }
+}
+
+/// Whether a `#[serde(...)]` or `#[schemars(...)]` attribute contains `word`.
+fn has_word(attrs: &[Attribute], word: &str) -> bool {
+    attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("serde") || attr.path().is_ident("schemars"))
+        .any(|attr| {
+            let mut found = false;
+            let _ = attr.parse_nested_meta(|meta| {
+                found |= meta.path.is_ident(word);
+                // skip the value of `key = value` and `key(...)`
+                if meta.input.peek(syn::Token![=]) {
+                    meta.value()?.parse::<syn::Expr>()?;
+                } else if meta.input.peek(syn::token::Paren) {
+                    let _content;
+                    syn::parenthesized!(_content in meta.input);
+                }
+                Ok(())
+            });
+            found
+        })
+}
+
+/// Whether the field is described in the input schema of a tool.
+fn is_documented(field: &Field) -> bool {
+    field.attrs.iter().any(|attr| attr.path().is_ident("doc"))
+        || has_word(&field.attrs, "description")
+        || has_word(&field.attrs, "skip")
+        || has_word(&field.attrs, "flatten")
+}
+
+pub fn tool_parameters(attr: TokenStream, input: TokenStream) -> syn::Result<TokenStream> {
+    let attr_args = NestedMeta::parse_meta_list(attr)?;
+    let ToolParametersAttribute { lints } = ToolParametersAttribute::from_list(&attr_args)?;
+    let item = syn::parse2::<ItemStruct>(input)?;
+    let mut warnings = TokenStream::new();
+    let mut errors: Option<syn::Error> = None;
+    for field in &item.fields {
+        let Some(ident) = &field.ident else {
+            continue;
+        };
+        if is_documented(field) {
+            continue;
+        }
+        let problem = format!(
+            "parameter `{ident}` of `{}` has no description, add a doc comment",
+            item.ident
+        );
+        match report(lints, field.span(), &[problem]) {
+            Ok(warning) => warnings.extend(warning),
+            Err(error) => match &mut errors {
+                Some(errors) => errors.combine(error),
+                None => errors = Some(error),
+            },
+        }
+    }
+    if let Some(errors) = errors {
+        return Err(errors);
+    }
+    Ok(quote! {
+        #item
+        #warnings
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_tool_parameters() -> syn::Result<()> {
+        let input = quote! {
+            struct Params {
+                /// The path of the file
+                path: String,
+                #[schemars(description = "The encoding")]
+                encoding: String,
+                #[serde(default, skip)]
+                cache: bool,
+                #[serde(flatten)]
+                options: Options,
+                #[schemars(range(min = 1), description = "The depth")]
+                depth: u32,
+                mode: u32,
+                recursive: bool,
+            }
+        };
+        let result = tool_parameters(quote! {}, input.clone())?.to_string();
+        assert_eq!(result.matches("deprecated").count(), 2, "{result}");
+        assert!(result.contains("parameter `mode` of `Params` has no description"));
+
+        let error = tool_parameters(quote! { lints = "deny" }, input.clone()).unwrap_err();
+        assert_eq!(error.into_iter().count(), 2);
+
+        let result = tool_parameters(quote! { lints = "allow" }, input)?.to_string();
+        assert!(!result.contains("deprecated"));
+        Ok(())
+    }
+}
@@ -9,13 +9,19 @@
 use darling::{FromMeta, ast::NestedMeta};
 use proc_macro2::TokenStream;
 use quote::{ToTokens, format_ident, quote};
-use syn::{Ident, ImplItem, ItemImpl, Visibility};
+use syn::{Ident, ImplItem, ItemImpl, Meta, Visibility};
+
+use crate::{
+    lint::{LintLevel, check_tool, report},
+    tool::{ToolAttribute, parse_timeout},
+};
 
 #[derive(FromMeta)]
 #[darling(default)]
 pub struct ToolRouterAttribute {
     pub router: Ident,
     pub vis: Option<Visibility>,
+    pub lints: LintLevel,
 }
 
 impl Default for ToolRouterAttribute {
@@ -23,41 +29,93 @@ impl Default for ToolRouterAttribute {
         Self {
             router: format_ident!("tool_router"),
             vis: None,
+            lints: LintLevel::default(),
         }
     }
 }
 
 pub fn tool_router(attr: TokenStream, input: TokenStream) -> syn::Result<TokenStream> {
     let attr_args = NestedMeta::parse_meta_list(attr)?;
-    let ToolRouterAttribute { router, vis } = ToolRouterAttribute::from_list(&attr_args)?;
+    let ToolRouterAttribute { router, vis, lints } = ToolRouterAttribute::from_list(&attr_args)?;
     let mut item_impl = syn::parse2::<ItemImpl>(input.clone())?;
+    let mut routers = vec![];
+    let mut errors: Option<syn::Error> = None;
     // find all function marked with `#[rmcp::tool]`
-    let tool_attr_fns: Vec<_> = item_impl
-        .items
-        .iter()
-        .filter_map(|item| {
-            if let syn::ImplItem::Fn(fn_item) = item {
+    for item in &mut item_impl.items {
+        let syn::ImplItem::Fn(fn_item) = item else {
+            continue;
+        };
+        let Some(attr) = fn_item.attrs.iter().find(|attr| {
+            attr.path()
+                .segments
+                .last()
+                .is_some_and(|seg| seg.ident == "tool")
+        }) else {
+            continue;
+        };
+        let handler = fn_item.sig.ident.clone();
+        let tool_attr_fn_ident = format_ident!("{handler}_tool_attr");
+        // `#[tool]` reports invalid attributes itself
+        let attribute = match &attr.meta {
+            Meta::List(list) => NestedMeta::parse_meta_list(list.tokens.clone())
+                .ok()
+                .and_then(|args| ToolAttribute::from_list(&args).ok()),
+            _ => None,
+        }
+        .unwrap_or_default();
+        let name = attribute
+            .name
+            .clone()
+            .unwrap_or_else(|| handler.to_string());
+        let has_description = attribute.description.is_some()
+            || fn_item.attrs.iter().any(|attr| attr.path().is_ident("doc"));
+        let problems = check_tool(
+            &name,
+            has_description,
+            attribute.resolved_annotations(&name).as_ref(),
+        );
+        match report(lints, handler.span(), &problems) {
+            // in the body, so that `#[allow(deprecated)]` on the tool silences them
+            Ok(warnings) => {
+                let warnings = syn::parse2::<syn::File>(warnings)?.items;
                 fn_item
-                    .attrs
-                    .iter()
-                    .any(|attr| {
-                        attr.path()
-                            .segments
-                            .last()
-                            .is_some_and(|seg| seg.ident == "tool")
-                    })
-                    .then_some(&fn_item.sig.ident)
-            } else {
-                None
+                    .block
+                    .stmts
+                    .splice(0..0, warnings.into_iter().map(syn::Stmt::Item));
             }
-        })
-        .collect();
-    let mut routers = vec![];
-    for handler in tool_attr_fns {
-        let tool_attr_fn_ident = format_ident!("{handler}_tool_attr");
-        routers.push(quote! {
-            .with_route((Self::#tool_attr_fn_ident(), Self::#handler))
-        })
+            Err(error) => match &mut errors {
+                Some(errors) => errors.combine(error),
+                None => errors = Some(error),
+            },
+        }
+        let mut options = vec![];
+        if let Some(scopes) = attribute.scopes {
+            options.push(quote! { .with_scopes([#(#scopes),*]) });
+        }
+        if let Some(millis) = attribute
+            .timeout
+            .and_then(|timeout| parse_timeout(&timeout).ok())
+        {
+            options.push(quote! { .with_timeout(std::time::Duration::from_millis(#millis)) });
+        }
+        if options.is_empty() {
+            routers.push(quote! {
+                .with_route((Self::#tool_attr_fn_ident(), Self::#handler))
+            })
+        } else {
+            routers.push(quote! {
+                .with_route(
+                    rmcp::handler::server::router::tool::ToolRoute::new(
+                        Self::#tool_attr_fn_ident(),
+                        Self::#handler,
+                    )
+                    #(#options)*
+                )
+            })
+        }
+    }
+    if let Some(errors) = errors {
+        return Err(errors);
     }
     let router_fn = syn::parse2::<ImplItem>(quote! {
         #vis fn #router() -> rmcp::handler::server::router::tool::ToolRouter<Self> {
@@ -72,6 +130,76 @@ pub fn tool_router(attr: TokenStream, input: TokenStream) -> syn::Result<TokenSt
 #[cfg(test)]
 mod test {
     use super::*;
+
+    #[test]
+    fn test_router_with_scopes() -> syn::Result<()> {
+        let input = quote! {
+            impl Repo {
+                #[tool(scopes = ["repo:write"], timeout = "2s")]
+                async fn push(&self) -> String {
+                    String::new()
+                }
+
+                #[tool]
+                async fn status(&self) -> String {
+                    String::new()
+                }
+            }
+        };
+        let result = tool_router(quote! {}, input)?.to_string();
+        assert!(
+            result.contains(r#"with_scopes (["repo:write"])"#),
+            "{result}"
+        );
+        assert!(result.contains("with_route ((Self :: status_tool_attr ()"));
+        Ok(())
+    }
+    #[test]
+    fn test_router_lints() -> syn::Result<()> {
+        let input = quote! {
+            impl Files {
+                #[tool]
+                async fn delete_file(&self) -> String {
+                    String::new()
+                }
+
+                /// Read a file
+                #[tool(annotations(read_only_hint = true))]
+                async fn read_file(&self) -> String {
+                    String::new()
+                }
+
+                /// Remove a directory
+                #[tool(infer_annotations)]
+                async fn remove_dir(&self) -> String {
+                    String::new()
+                }
+            }
+        };
+        // opt-in
+        let result = tool_router(quote! {}, input.clone())?.to_string();
+        assert!(!result.contains("deprecated"));
+
+        let result = tool_router(quote! { lints = "warn" }, input.clone())?.to_string();
+        assert_eq!(result.matches("deprecated").count(), 2, "{result}");
+        assert!(result.contains("tool `delete_file` has no description"));
+        assert!(result.contains("tool `delete_file` looks destructive"));
+
+        let error = tool_router(quote! { lints = "deny" }, input).unwrap_err();
+        assert_eq!(error.into_iter().count(), 2);
+
+        let invalid = quote! {
+            impl Files {
+                /// Say hello
+                #[tool(name = "say hello")]
+                fn hello(&self) {}
+            }
+        };
+        let error = tool_router(quote! { lints = "deny" }, invalid).unwrap_err();
+        assert!(error.to_string().contains("invalid characters"), "{error}");
+        Ok(())
+    }
+
     #[test]
     fn test_router_attr() -> Result<(), Box<dyn std::error::Error>> {
         let attr = quote! {
@@ -79,7 +207,7 @@ mod test {
             vis = "pub(crate)"
         };
         let attr_args = NestedMeta::parse_meta_list(attr)?;
-        let ToolRouterAttribute { router, vis } = ToolRouterAttribute::from_list(&attr_args)?;
+        let ToolRouterAttribute { router, vis, .. } = ToolRouterAttribute::from_list(&attr_args)?;
         println!("router: {}", router);
         if let Some(vis) = vis {
             println!("visibility: {}", vis.to_token_stream());
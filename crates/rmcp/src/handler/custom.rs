@@ -0,0 +1,327 @@
+//! Routing custom requests and notifications to typed handlers.
+//!
+//! ```rust,ignore
+//! #[derive(Serialize, Deserialize, CustomMethod)]
+//! #[custom_method(method = "acme/search", result = SearchResult)]
+//! struct Search {
+//!     query: String,
+//! }
+//!
+//! let custom_router = CustomRouter::<MyServer, RoleServer>::new()
+//!     .with_request::<Search, _>(|server, search, _context| {
+//!         Box::pin(async move { server.search(&search.query).await })
+//!     });
+//!
+//! impl ServerHandler for MyServer {
+//!     async fn on_custom_request(
+//!         &self,
+//!         request: CustomRequest,
+//!         context: RequestContext<RoleServer>,
+//!     ) -> Result<CustomResult, ErrorData> {
+//!         self.custom_router.handle_request(self, request, context).await
+//!     }
+//! }
+//! ```
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::Arc,
+};
+
+use futures::future::BoxFuture;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+use crate::{
+    error::ErrorData as McpError,
+    model::{
+        CustomMethod, CustomNotification, CustomRequest, CustomResult, ErrorCode,
+        ExperimentalCapabilities, JsonObject,
+    },
+    service::{NotificationContext, RequestContext, ServiceRole},
+};
+
+pub type DynCustomRequestHandler<S, R> = dyn for<'a> Fn(
+        &'a S,
+        Option<Value>,
+        RequestContext<R>,
+    ) -> BoxFuture<'a, Result<CustomResult, McpError>>
+    + Send
+    + Sync;
+
+pub type DynCustomNotificationHandler<S, R> =
+    dyn for<'a> Fn(&'a S, Option<Value>, NotificationContext<R>) -> BoxFuture<'a, ()> + Send + Sync;
+
+struct CustomRoute<H: ?Sized> {
+    capability: &'static str,
+    handler: Arc<H>,
+}
+
+impl<H: ?Sized> Clone for CustomRoute<H> {
+    fn clone(&self) -> Self {
+        Self {
+            capability: self.capability,
+            handler: self.handler.clone(),
+        }
+    }
+}
+
+/// Routes custom methods declared with [`CustomMethod`] to typed handlers of a service `S`
+/// playing the role `R`.
+///
+/// A request of a method without a handler gets a `-32601` (method not found) error, and a
+/// request with params that do not deserialize gets a `-32602` (invalid params) error.
+pub struct CustomRouter<S, R: ServiceRole> {
+    requests: HashMap<&'static str, CustomRoute<DynCustomRequestHandler<S, R>>>,
+    notifications: HashMap<&'static str, CustomRoute<DynCustomNotificationHandler<S, R>>>,
+}
+
+impl<S, R: ServiceRole> Default for CustomRouter<S, R> {
+    fn default() -> Self {
+        Self {
+            requests: HashMap::new(),
+            notifications: HashMap::new(),
+        }
+    }
+}
+
+impl<S, R: ServiceRole> Clone for CustomRouter<S, R> {
+    fn clone(&self) -> Self {
+        Self {
+            requests: self.requests.clone(),
+            notifications: self.notifications.clone(),
+        }
+    }
+}
+
+impl<S, R: ServiceRole> std::fmt::Debug for CustomRouter<S, R> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CustomRouter")
+            .field("requests", &self.requests.keys().collect::<Vec<_>>())
+            .field(
+                "notifications",
+                &self.notifications.keys().collect::<Vec<_>>(),
+            )
+            .finish()
+    }
+}
+
+fn parse_params<P: DeserializeOwned>(params: Option<Value>) -> Result<P, serde_json::Error> {
+    serde_json::from_value(params.unwrap_or_else(|| Value::Object(JsonObject::new())))
+}
+
+impl<S: Send + Sync + 'static, R: ServiceRole> CustomRouter<S, R> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Handle the requests of `M`, replacing any previous handler.
+    pub fn with_request<M, H>(mut self, handler: H) -> Self
+    where
+        M: CustomMethod,
+        H: for<'a> Fn(
+                &'a S,
+                M::Params,
+                RequestContext<R>,
+            ) -> BoxFuture<'a, Result<M::Result, McpError>>
+            + Send
+            + Sync
+            + 'static,
+    {
+        let handler: Arc<DynCustomRequestHandler<S, R>> =
+            Arc::new(move |service, params, context| {
+                let params = match parse_params::<M::Params>(params) {
+                    Ok(params) => params,
+                    Err(error) => {
+                        return Box::pin(std::future::ready(Err(McpError::invalid_params(
+                            format!("invalid params of `{}`: {error}", M::METHOD),
+                            None,
+                        )))) as BoxFuture<'_, _>;
+                    }
+                };
+                let result = handler(service, params, context);
+                Box::pin(async move {
+                    let result = result.await?;
+                    serde_json::to_value(result)
+                        .map(CustomResult)
+                        .map_err(|error| McpError::internal_error(error.to_string(), None))
+                })
+            });
+        self.requests.insert(
+            M::METHOD,
+            CustomRoute {
+                capability: M::CAPABILITY,
+                handler,
+            },
+        );
+        self
+    }
+
+    /// Handle the notifications of `M`, replacing any previous handler.
+    pub fn with_notification<M, H>(mut self, handler: H) -> Self
+    where
+        M: CustomMethod,
+        H: for<'a> Fn(&'a S, M::Params, NotificationContext<R>) -> BoxFuture<'a, ()>
+            + Send
+            + Sync
+            + 'static,
+    {
+        let handler: Arc<DynCustomNotificationHandler<S, R>> = Arc::new(
+            move |service, params, context| match parse_params::<M::Params>(params) {
+                Ok(params) => handler(service, params, context),
+                Err(error) => {
+                    tracing::warn!(method = M::METHOD, %error, "drop notification with invalid params");
+                    Box::pin(std::future::ready(()))
+                }
+            },
+        );
+        self.notifications.insert(
+            M::METHOD,
+            CustomRoute {
+                capability: M::CAPABILITY,
+                handler,
+            },
+        );
+        self
+    }
+
+    /// Add the handlers of `other`, replacing the handlers of the same methods.
+    pub fn merge(mut self, other: Self) -> Self {
+        self.requests.extend(other.requests);
+        self.notifications.extend(other.notifications);
+        self
+    }
+
+    pub fn has_request(&self, method: &str) -> bool {
+        self.requests.contains_key(method)
+    }
+
+    pub fn has_notification(&self, method: &str) -> bool {
+        self.notifications.contains_key(method)
+    }
+
+    pub async fn handle_request(
+        &self,
+        service: &S,
+        request: CustomRequest,
+        context: RequestContext<R>,
+    ) -> Result<CustomResult, McpError> {
+        let Some(route) = self.requests.get(request.method.as_str()) else {
+            return Err(McpError::new(
+                ErrorCode::METHOD_NOT_FOUND,
+                request.method,
+                None,
+            ));
+        };
+        (route.handler)(service, request.params, context).await
+    }
+
+    /// Handle a notification, notifications without a handler are ignored.
+    pub async fn handle_notification(
+        &self,
+        service: &S,
+        notification: CustomNotification,
+        context: NotificationContext<R>,
+    ) {
+        match self.notifications.get(notification.method.as_str()) {
+            Some(route) => (route.handler)(service, notification.params, context).await,
+            None => tracing::debug!(method = notification.method, "no handler for notification"),
+        }
+    }
+
+    /// The `experimental` capabilities advertising the routed methods.
+    ///
+    /// Each [capability](CustomMethod::CAPABILITY) lists its requests under `methods` and its
+    /// notifications under `notifications`:
+    ///
+    /// ```json
+    /// { "acme": { "methods": ["acme/search"], "notifications": ["acme/indexed"] } }
+    /// ```
+    pub fn experimental_capabilities(&self) -> ExperimentalCapabilities {
+        let mut capabilities = BTreeMap::<String, BTreeMap<&str, Vec<&str>>>::new();
+        let routes = self
+            .requests
+            .iter()
+            .map(|(method, route)| ("methods", *method, route.capability))
+            .chain(
+                self.notifications
+                    .iter()
+                    .map(|(method, route)| ("notifications", *method, route.capability)),
+            );
+        for (kind, method, capability) in routes {
+            capabilities
+                .entry(capability.to_owned())
+                .or_default()
+                .entry(kind)
+                .or_default()
+                .push(method);
+        }
+        capabilities
+            .into_iter()
+            .map(|(capability, kinds)| {
+                let object = kinds
+                    .into_iter()
+                    .map(|(kind, mut methods)| {
+                        methods.sort_unstable();
+                        (kind.to_owned(), Value::from(methods))
+                    })
+                    .collect();
+                (capability, object)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+    use serde_json::json;
+
+    use super::*;
+    use crate::{model::EmptyObject, service::RoleServer};
+
+    #[derive(Serialize, Deserialize)]
+    struct Search {
+        query: String,
+    }
+
+    impl CustomMethod for Search {
+        const METHOD: &'static str = "acme/search";
+        const CAPABILITY: &'static str = "acme";
+        type Params = Self;
+        type Result = EmptyObject;
+    }
+
+    struct Indexed;
+
+    impl CustomMethod for Indexed {
+        const METHOD: &'static str = "acme/indexed";
+        const CAPABILITY: &'static str = "acme";
+        type Params = EmptyObject;
+        type Result = EmptyObject;
+    }
+
+    struct Ping;
+
+    impl CustomMethod for Ping {
+        const METHOD: &'static str = "other/ping";
+        type Params = EmptyObject;
+        type Result = EmptyObject;
+    }
+
+    #[test]
+    fn test_experimental_capabilities() {
+        let router = CustomRouter::<(), RoleServer>::new()
+            .with_request::<Search, _>(|_, _, _| Box::pin(async { Ok(EmptyObject {}) }))
+            .with_request::<Ping, _>(|_, _, _| Box::pin(async { Ok(EmptyObject {}) }))
+            .with_notification::<Indexed, _>(|_, _, _| Box::pin(async {}));
+        assert!(router.has_request("acme/search"));
+        assert!(!router.has_request("acme/indexed"));
+        assert_eq!(
+            serde_json::to_value(router.experimental_capabilities()).unwrap(),
+            json!({
+                "acme": { "methods": ["acme/search"], "notifications": ["acme/indexed"] },
+                "other/ping": { "methods": ["other/ping"] },
+            })
+        );
+    }
+}
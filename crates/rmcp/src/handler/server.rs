@@ -6,12 +6,25 @@ use crate::{
     service::{NotificationContext, RequestContext, RoleServer, Service, ServiceRole},
 };
 
+pub mod authorization;
 pub mod common;
+pub mod completion;
+#[cfg(feature = "resource-fs")]
+#[cfg_attr(docsrs, doc(cfg(feature = "resource-fs")))]
+pub mod filesystem;
+pub mod pagination;
+pub mod progress;
 pub mod prompt;
-mod resource;
+pub mod registry;
+pub mod resource;
 pub mod router;
+pub mod subscription;
 pub mod tool;
 pub mod tool_name_validation;
+#[cfg(feature = "schema-validation")]
+#[cfg_attr(docsrs, doc(cfg(feature = "schema-validation")))]
+pub mod validation;
+pub mod visibility;
 pub mod wrapper;
 
 impl<H: ServerHandler> Service<RoleServer> for H {
@@ -32,10 +45,19 @@ impl<H: ServerHandler> Service<RoleServer> for H {
                 .complete(request.params, context)
                 .await
                 .map(ServerResult::CompleteResult),
-            ClientRequest::SetLevelRequest(request) => self
-                .set_level(request.params, context)
-                .await
-                .map(ServerResult::empty),
+            ClientRequest::SetLevelRequest(request) => {
+                #[cfg(feature = "logging")]
+                let forwarded =
+                    crate::service::logging::set_level(&context.peer, request.params.level);
+                let result = self.set_level(request.params, context).await;
+                // the logging layer supports the method for handlers which don't
+                #[cfg(feature = "logging")]
+                let result = result.or_else(|error| match error.code {
+                    ErrorCode::METHOD_NOT_FOUND if forwarded => Ok(()),
+                    _ => Err(error),
+                });
+                result.map(ServerResult::empty)
+            }
             ClientRequest::GetPromptRequest(request) => self
                 .get_prompt(request.params, context)
                 .await
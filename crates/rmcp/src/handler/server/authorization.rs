@@ -0,0 +1,127 @@
+//! Restrict tools to callers with certain scopes or roles.
+//!
+//! A tool route with a [`ToolPolicy`] can only be called, and is only listed, when the
+//! [`Identity`] of the caller satisfies it. To other callers the tool doesn't exist: calling
+//! it fails with the same "tool not found" error as an unknown tool, which tells nothing of
+//! the policy. The identity is found in the extensions of the
+//! request, where a transport or middleware puts it after authenticating the client:
+//!
+//! ```rust,ignore
+//! // e.g. in an axum middleware in front of the streamable http service, once the token
+//! // is validated
+//! request
+//!     .extensions_mut()
+//!     .insert(Identity::new("alice").with_scopes(claims.scopes));
+//!
+//! #[tool_router]
+//! impl Repo {
+//!     #[tool(scopes = ["repo:write"])]
+//!     async fn push(&self) -> String { todo!() }
+//! }
+//! ```
+//!
+//! A router can also derive identities from other extensions, such as the claims of a token,
+//! with [`ToolRouter::with_identity_resolver`](super::router::tool::ToolRouter::with_identity_resolver).
+use std::{borrow::Cow, collections::BTreeSet, sync::Arc};
+
+use crate::model::Extensions;
+
+/// The authenticated caller of a request.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Identity {
+    pub subject: Option<String>,
+    /// The granted OAuth scopes or roles
+    pub scopes: BTreeSet<String>,
+}
+
+impl Identity {
+    pub fn new(subject: impl Into<String>) -> Self {
+        Self {
+            subject: Some(subject.into()),
+            scopes: BTreeSet::new(),
+        }
+    }
+
+    pub fn with_scopes(mut self, scopes: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.scopes.extend(scopes.into_iter().map(Into::into));
+        self
+    }
+
+    pub fn has_scope(&self, scope: &str) -> bool {
+        self.scopes.contains(scope)
+    }
+
+    /// The identity in `extensions`, or in the extensions of the HTTP request they were
+    /// received with.
+    pub fn from_extensions(extensions: &Extensions) -> Option<Self> {
+        if let Some(identity) = extensions.get::<Identity>() {
+            return Some(identity.clone());
+        }
+        #[cfg(feature = "server-side-http")]
+        if let Some(parts) = extensions.get::<http::request::Parts>() {
+            return parts.extensions.get::<Identity>().cloned();
+        }
+        None
+    }
+}
+
+/// Finds the identity of the caller in the extensions of a request.
+pub type IdentityResolver = Arc<dyn Fn(&Extensions) -> Option<Identity> + Send + Sync>;
+
+/// Who may call a tool.
+#[derive(Clone)]
+pub enum ToolPolicy {
+    /// Callers granted every one of these scopes
+    Scopes(Vec<Cow<'static, str>>),
+    /// Callers for which the function returns true
+    Custom(Arc<dyn Fn(&Identity) -> bool + Send + Sync>),
+}
+
+impl std::fmt::Debug for ToolPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ToolPolicy::Scopes(scopes) => f.debug_tuple("Scopes").field(scopes).finish(),
+            ToolPolicy::Custom(_) => f.debug_tuple("Custom").finish_non_exhaustive(),
+        }
+    }
+}
+
+impl ToolPolicy {
+    pub fn scopes(scopes: impl IntoIterator<Item = impl Into<Cow<'static, str>>>) -> Self {
+        ToolPolicy::Scopes(scopes.into_iter().map(Into::into).collect())
+    }
+
+    pub fn custom(allows: impl Fn(&Identity) -> bool + Send + Sync + 'static) -> Self {
+        ToolPolicy::Custom(Arc::new(allows))
+    }
+
+    /// Whether `identity` may call the tool. Anonymous callers never may.
+    pub fn allows(&self, identity: Option<&Identity>) -> bool {
+        let Some(identity) = identity else {
+            return false;
+        };
+        match self {
+            ToolPolicy::Scopes(scopes) => scopes.iter().all(|scope| identity.has_scope(scope)),
+            ToolPolicy::Custom(allows) => allows(identity),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_policy() {
+        let policy = ToolPolicy::scopes(["repo:read", "repo:write"]);
+        let reader = Identity::new("reader").with_scopes(["repo:read"]);
+        let writer = Identity::new("writer").with_scopes(["repo:read", "repo:write"]);
+        assert!(!policy.allows(None));
+        assert!(!policy.allows(Some(&reader)));
+        assert!(policy.allows(Some(&writer)));
+
+        let policy = ToolPolicy::custom(|identity| identity.subject.as_deref() == Some("reader"));
+        assert!(policy.allows(Some(&reader)));
+        assert!(!policy.allows(Some(&writer)));
+    }
+}
@@ -0,0 +1,276 @@
+//! Completion handling infrastructure for MCP servers
+//!
+//! This module provides the core types and traits for implementing completion handlers
+//! in MCP servers. A handler suggests values for one argument of a prompt, or one variable
+//! of a resource template, and can read the arguments already resolved by the client from
+//! the [`CompletionContext`](crate::model::CompletionContext).
+
+use std::marker::PhantomData;
+
+use futures::future::{BoxFuture, FutureExt};
+
+use super::common::{AsRequestContext, FromContextPart};
+pub use super::common::{Extension, RequestId};
+use crate::{
+    RoleServer,
+    model::{ArgumentInfo, CompleteRequestParams, CompletionInfo, Reference},
+    service::RequestContext,
+};
+
+/// Context for completion requests
+pub struct CompletionRequestContext<'a, S> {
+    pub server: &'a S,
+    pub request: CompleteRequestParams,
+    pub context: RequestContext<RoleServer>,
+}
+
+impl<'a, S> CompletionRequestContext<'a, S> {
+    pub fn new(
+        server: &'a S,
+        request: CompleteRequestParams,
+        context: RequestContext<RoleServer>,
+    ) -> Self {
+        Self {
+            server,
+            request,
+            context,
+        }
+    }
+}
+
+impl<S> AsRequestContext for CompletionRequestContext<'_, S> {
+    fn as_request_context(&self) -> &RequestContext<RoleServer> {
+        &self.context
+    }
+
+    fn as_request_context_mut(&mut self) -> &mut RequestContext<RoleServer> {
+        &mut self.context
+    }
+}
+
+/// Trait for handling completions
+pub trait CompleteHandler<S, A> {
+    fn handle(
+        self,
+        context: CompletionRequestContext<'_, S>,
+    ) -> BoxFuture<'_, Result<CompletionInfo, crate::ErrorData>>;
+}
+
+/// Type alias for dynamic completion handlers
+pub type DynCompleteHandler<S> = dyn for<'a> Fn(
+        CompletionRequestContext<'a, S>,
+    ) -> BoxFuture<'a, Result<CompletionInfo, crate::ErrorData>>
+    + Send
+    + Sync;
+
+/// Adapter type for sync methods
+pub struct SyncCompleteMethodAdapter<P, R>(PhantomData<fn(P) -> R>);
+
+/// Trait for types that can be converted into CompletionInfo
+pub trait IntoCompletionInfo {
+    fn into_completion_info(self) -> Result<CompletionInfo, crate::ErrorData>;
+}
+
+impl IntoCompletionInfo for CompletionInfo {
+    fn into_completion_info(self) -> Result<CompletionInfo, crate::ErrorData> {
+        Ok(self)
+    }
+}
+
+impl IntoCompletionInfo for Vec<String> {
+    fn into_completion_info(self) -> Result<CompletionInfo, crate::ErrorData> {
+        Ok(CompletionInfo {
+            total: u32::try_from(self.len()).ok(),
+            has_more: Some(false),
+            values: self,
+        })
+    }
+}
+
+impl IntoCompletionInfo for Vec<&'static str> {
+    fn into_completion_info(self) -> Result<CompletionInfo, crate::ErrorData> {
+        self.into_iter()
+            .map(String::from)
+            .collect::<Vec<_>>()
+            .into_completion_info()
+    }
+}
+
+impl<T: IntoCompletionInfo> IntoCompletionInfo for Result<T, crate::ErrorData> {
+    fn into_completion_info(self) -> Result<CompletionInfo, crate::ErrorData> {
+        self.and_then(|v| v.into_completion_info())
+    }
+}
+
+/// Keep the first [`CompletionInfo::MAX_VALUES`] values, recording that there are more.
+pub fn truncate_completion(mut completion: CompletionInfo) -> CompletionInfo {
+    let count = completion.values.len();
+    if count > CompletionInfo::MAX_VALUES {
+        completion.values.truncate(CompletionInfo::MAX_VALUES);
+        let count = u32::try_from(count).unwrap_or(u32::MAX);
+        completion.total = Some(completion.total.map_or(count, |total| total.max(count)));
+        completion.has_more = Some(true);
+    }
+    completion
+}
+
+/// The values starting with the current value of the argument, ignoring case.
+pub fn complete_from_values(values: &[String], current: &str) -> CompletionInfo {
+    let current = current.to_lowercase();
+    let values = values
+        .iter()
+        .filter(|value| value.to_lowercase().starts_with(&current))
+        .cloned()
+        .collect::<Vec<_>>();
+    CompletionInfo {
+        total: u32::try_from(values.len()).ok(),
+        has_more: Some(false),
+        values,
+    }
+}
+
+// Completion-specific extractor for the value typed so far
+pub struct CompletionValue(pub String);
+
+impl<S> FromContextPart<CompletionRequestContext<'_, S>> for CompletionValue {
+    fn from_context_part(
+        context: &mut CompletionRequestContext<S>,
+    ) -> Result<Self, crate::ErrorData> {
+        Ok(Self(context.request.argument.value.clone()))
+    }
+}
+
+impl<S> FromContextPart<CompletionRequestContext<'_, S>> for ArgumentInfo {
+    fn from_context_part(
+        context: &mut CompletionRequestContext<S>,
+    ) -> Result<Self, crate::ErrorData> {
+        Ok(context.request.argument.clone())
+    }
+}
+
+impl<S> FromContextPart<CompletionRequestContext<'_, S>> for Reference {
+    fn from_context_part(
+        context: &mut CompletionRequestContext<S>,
+    ) -> Result<Self, crate::ErrorData> {
+        Ok(context.request.r#ref.clone())
+    }
+}
+
+// The arguments already resolved by the client, empty if it sent none
+impl<S> FromContextPart<CompletionRequestContext<'_, S>> for crate::model::CompletionContext {
+    fn from_context_part(
+        context: &mut CompletionRequestContext<S>,
+    ) -> Result<Self, crate::ErrorData> {
+        Ok(context.request.context.clone().unwrap_or_default())
+    }
+}
+
+// Macro to generate CompleteHandler implementations for various parameter combinations
+macro_rules! impl_complete_handler_for {
+    ($($T: ident)*) => {
+        impl_complete_handler_for!([] [$($T)*]);
+    };
+    // finished
+    ([$($Tn: ident)*] []) => {
+        impl_complete_handler_for!(@impl $($Tn)*);
+    };
+    ([$($Tn: ident)*] [$Tn_1: ident $($Rest: ident)*]) => {
+        impl_complete_handler_for!(@impl $($Tn)*);
+        impl_complete_handler_for!([$($Tn)* $Tn_1] [$($Rest)*]);
+    };
+    (@impl $($Tn: ident)*) => {
+        // Implementation for async methods (transformed by #[completion] macro)
+        impl<$($Tn,)* S, F, R> CompleteHandler<S, ($($Tn,)*)> for F
+        where
+            $(
+                $Tn: for<'a> FromContextPart<CompletionRequestContext<'a, S>> + Send,
+            )*
+            F: FnOnce(&S, $($Tn,)*) -> BoxFuture<'_, R> + Send,
+            R: IntoCompletionInfo + Send + 'static,
+            S: Send + Sync + 'static,
+        {
+            #[allow(unused_variables, non_snake_case, unused_mut)]
+            fn handle(
+                self,
+                mut context: CompletionRequestContext<'_, S>,
+            ) -> BoxFuture<'_, Result<CompletionInfo, crate::ErrorData>>
+            {
+                $(
+                    let result = $Tn::from_context_part(&mut context);
+                    let $Tn = match result {
+                        Ok(value) => value,
+                        Err(e) => return std::future::ready(Err(e)).boxed(),
+                    };
+                )*
+                let service = context.server;
+                let fut = self(service, $($Tn,)*);
+                async move {
+                    let result = fut.await;
+                    result.into_completion_info()
+                }.boxed()
+            }
+        }
+
+        // Implementation for sync methods
+        impl<$($Tn,)* S, F, R> CompleteHandler<S, SyncCompleteMethodAdapter<($($Tn,)*), R>> for F
+        where
+            $(
+                $Tn: for<'a> FromContextPart<CompletionRequestContext<'a, S>> + Send,
+            )*
+            F: FnOnce(&S, $($Tn,)*) -> R + Send,
+            R: IntoCompletionInfo + Send,
+            S: Send + Sync,
+        {
+            #[allow(unused_variables, non_snake_case, unused_mut)]
+            fn handle(
+                self,
+                mut context: CompletionRequestContext<'_, S>,
+            ) -> BoxFuture<'_, Result<CompletionInfo, crate::ErrorData>>
+            {
+                $(
+                    let result = $Tn::from_context_part(&mut context);
+                    let $Tn = match result {
+                        Ok(value) => value,
+                        Err(e) => return std::future::ready(Err(e)).boxed(),
+                    };
+                )*
+                let service = context.server;
+                let result = self(service, $($Tn,)*);
+                std::future::ready(result.into_completion_info()).boxed()
+            }
+        }
+    };
+}
+
+// Invoke the macro to generate implementations for up to 16 parameters
+impl_complete_handler_for!(T0 T1 T2 T3 T4 T5 T6 T7 T8 T9 T10 T11 T12 T13 T14 T15);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_truncate_completion() {
+        let values = (0..150).map(|i| i.to_string()).collect::<Vec<_>>();
+        let completion = truncate_completion(values.into_completion_info().unwrap());
+        assert_eq!(completion.values.len(), CompletionInfo::MAX_VALUES);
+        assert_eq!(completion.total, Some(150));
+        assert_eq!(completion.has_more, Some(true));
+        assert!(completion.validate().is_ok());
+
+        let completion = truncate_completion(vec!["a", "b"].into_completion_info().unwrap());
+        assert_eq!(completion.values, vec!["a", "b"]);
+        assert_eq!(completion.has_more, Some(false));
+    }
+
+    #[test]
+    fn test_complete_from_values() {
+        let values = vec!["Rust".to_owned(), "Ruby".to_owned(), "Python".to_owned()];
+        assert_eq!(
+            complete_from_values(&values, "ru").values,
+            vec!["Rust", "Ruby"]
+        );
+        assert_eq!(complete_from_values(&values, "").values.len(), 3);
+        assert!(complete_from_values(&values, "go").values.is_empty());
+    }
+}
@@ -0,0 +1,764 @@
+//! Serve a directory as `file://` resources.
+//!
+//! ```rust,ignore
+//! struct Server {
+//!     files: FileSystemResources,
+//! }
+//!
+//! impl ServerHandler for Server {
+//!     async fn list_resources(
+//!         &self,
+//!         request: Option<PaginatedRequestParams>,
+//!         context: RequestContext<RoleServer>,
+//!     ) -> Result<ListResourcesResult, ErrorData> {
+//!         self.files.list_resources(request, &context.peer).await
+//!     }
+//!
+//!     async fn read_resource(
+//!         &self,
+//!         request: ReadResourceRequestParams,
+//!         _context: RequestContext<RoleServer>,
+//!     ) -> Result<ReadResourceResult, ErrorData> {
+//!         self.files.read_resource(&request.uri).await
+//!     }
+//! }
+//! ```
+use std::{
+    collections::HashMap,
+    path::{Component, Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::{Duration, SystemTime},
+};
+
+use base64::engine::{Engine, general_purpose::STANDARD as BASE64_STANDARD};
+use tokio_util::sync::{CancellationToken, DropGuard};
+
+use super::{pagination::Paginator, subscription::ResourceSubscriptions};
+use crate::{
+    ErrorData, RoleServer,
+    model::{
+        AnnotateAble, ListResourcesResult, PaginatedRequestParams, RawResource, ReadResourceResult,
+        Resource, ResourceContents,
+    },
+    service::Peer,
+};
+
+const IGNORE_FILES: [&str; 2] = [".gitignore", ".ignore"];
+
+#[derive(Debug, thiserror::Error)]
+pub enum FileSystemResourceError {
+    #[error("io error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("`{0}` is not a file uri")]
+    InvalidUri(String),
+    #[error("`{0}` is outside of the root directory")]
+    OutsideRoot(String),
+    #[error("`{0}` is ignored")]
+    Ignored(String),
+    #[error("`{0}` is not a file")]
+    NotAFile(String),
+    #[error("`{uri}` has {size} bytes, more than the limit of {limit} bytes")]
+    TooLarge { uri: String, size: u64, limit: u64 },
+}
+
+impl From<FileSystemResourceError> for ErrorData {
+    fn from(error: FileSystemResourceError) -> Self {
+        let message = error.to_string();
+        match error {
+            // don't tell apart what exists outside of the served files
+            FileSystemResourceError::InvalidUri(uri)
+            | FileSystemResourceError::OutsideRoot(uri)
+            | FileSystemResourceError::Ignored(uri)
+            | FileSystemResourceError::NotAFile(uri) => ErrorData::resource_not_found(
+                format!("resource '{uri}' not found"),
+                Some(serde_json::json!({ "uri": uri })),
+            ),
+            FileSystemResourceError::Io(error) if error.kind() == std::io::ErrorKind::NotFound => {
+                ErrorData::resource_not_found(message, None)
+            }
+            FileSystemResourceError::Io(_) => ErrorData::internal_error(message, None),
+            FileSystemResourceError::TooLarge { .. } => ErrorData::invalid_params(message, None),
+        }
+    }
+}
+
+/// Serves the files of a directory as `file://` resources.
+///
+/// Files can only be read if they are listed: paths resolving outside of the root, through
+/// `..` or symlinks, ignored files and, unless enabled, hidden files are not served. Ignore rules
+/// are read from the `.gitignore` and `.ignore` files of each directory.
+///
+/// Cloning shares the provider.
+#[derive(Debug, Clone)]
+pub struct FileSystemResources {
+    inner: Arc<Inner>,
+}
+
+#[derive(Debug)]
+struct Inner {
+    root: PathBuf,
+    paginator: Paginator,
+    max_text_size: u64,
+    max_size: u64,
+    include_hidden: bool,
+    ignore_files: bool,
+    ignore: Vec<IgnoreRule>,
+    /// Peers which listed the resources, notified when the list changes
+    listeners: Mutex<Vec<Peer<RoleServer>>>,
+}
+
+#[derive(Debug)]
+pub struct FileSystemResourcesBuilder {
+    root: PathBuf,
+    page_size: usize,
+    max_text_size: u64,
+    max_size: u64,
+    include_hidden: bool,
+    ignore_files: bool,
+    ignore: Vec<String>,
+}
+
+impl FileSystemResourcesBuilder {
+    /// The number of resources of a page of `resources/list`, 100 by default.
+    pub fn page_size(mut self, page_size: usize) -> Self {
+        self.page_size = page_size.max(1);
+        self
+    }
+
+    /// UTF-8 files up to this size are returned as text, others as base64 blobs. 1 MiB by
+    /// default.
+    pub fn max_text_size(mut self, max_text_size: u64) -> Self {
+        self.max_text_size = max_text_size;
+        self
+    }
+
+    /// Larger files cannot be read. 10 MiB by default.
+    pub fn max_size(mut self, max_size: u64) -> Self {
+        self.max_size = max_size;
+        self
+    }
+
+    /// Serve files and directories whose name starts with `.`.
+    pub fn include_hidden(mut self, include_hidden: bool) -> Self {
+        self.include_hidden = include_hidden;
+        self
+    }
+
+    /// Honor `.gitignore` and `.ignore` files, enabled by default.
+    pub fn ignore_files(mut self, ignore_files: bool) -> Self {
+        self.ignore_files = ignore_files;
+        self
+    }
+
+    /// Ignore paths matching a gitignore pattern, relative to the root.
+    pub fn ignore(mut self, pattern: impl Into<String>) -> Self {
+        self.ignore.push(pattern.into());
+        self
+    }
+
+    pub fn build(self) -> Result<FileSystemResources, FileSystemResourceError> {
+        let root = self.root.canonicalize()?;
+        if !root.is_dir() {
+            return Err(
+                std::io::Error::other(format!("{} is not a directory", root.display())).into(),
+            );
+        }
+        let ignore = self
+            .ignore
+            .iter()
+            .filter_map(|pattern| IgnoreRule::parse(pattern, ""))
+            .collect();
+        Ok(FileSystemResources {
+            inner: Arc::new(Inner {
+                root,
+                paginator: Paginator::new(self.page_size),
+                max_text_size: self.max_text_size,
+                max_size: self.max_size,
+                include_hidden: self.include_hidden,
+                ignore_files: self.ignore_files,
+                ignore,
+                listeners: Mutex::new(Vec::new()),
+            }),
+        })
+    }
+}
+
+/// A file found by walking the root directory.
+#[derive(Debug, Clone, PartialEq)]
+struct Entry {
+    /// `/` separated path relative to the root
+    relative: String,
+    path: PathBuf,
+    size: u64,
+    modified: Option<SystemTime>,
+}
+
+impl FileSystemResources {
+    pub fn builder(root: impl Into<PathBuf>) -> FileSystemResourcesBuilder {
+        FileSystemResourcesBuilder {
+            root: root.into(),
+            page_size: 100,
+            max_text_size: 1024 * 1024,
+            max_size: 10 * 1024 * 1024,
+            include_hidden: false,
+            ignore_files: true,
+            ignore: Vec::new(),
+        }
+    }
+
+    pub fn new(root: impl Into<PathBuf>) -> Result<Self, FileSystemResourceError> {
+        Self::builder(root).build()
+    }
+
+    /// The canonical root directory.
+    pub fn root(&self) -> &Path {
+        &self.inner.root
+    }
+
+    /// The `file://` URI of a path under the root.
+    pub fn uri_of(&self, path: impl AsRef<Path>) -> Option<String> {
+        let path = path.as_ref();
+        let path = if path.is_absolute() {
+            path.to_path_buf()
+        } else {
+            self.inner.root.join(path)
+        };
+        url::Url::from_file_path(path).ok().map(String::from)
+    }
+
+    /// A page of the served files, recording `peer` to notify it when the list changes.
+    pub async fn list_resources(
+        &self,
+        request: Option<PaginatedRequestParams>,
+        peer: &Peer<RoleServer>,
+    ) -> Result<ListResourcesResult, ErrorData> {
+        {
+            let mut listeners = self
+                .inner
+                .listeners
+                .lock()
+                .expect("listeners lock poisoned");
+            listeners.retain(|listener| !listener.is_transport_closed());
+            if !listeners.iter().any(|listener| listener.is_same_peer(peer)) {
+                listeners.push(peer.clone());
+            }
+        }
+        let page = self.inner.paginator.paginate(
+            self.entries().await?,
+            |entry| &entry.relative,
+            request.as_ref(),
+        )?;
+        Ok(ListResourcesResult {
+            resources: page
+                .items
+                .iter()
+                .filter_map(|entry| self.resource(entry))
+                .collect(),
+            next_cursor: page.next_cursor,
+            meta: None,
+        })
+    }
+
+    fn resource(&self, entry: &Entry) -> Option<Resource> {
+        let uri = self.uri_of(&entry.path)?;
+        let name = entry
+            .relative
+            .rsplit('/')
+            .next()
+            .unwrap_or(&entry.relative)
+            .to_owned();
+        Some(
+            RawResource {
+                uri,
+                name,
+                title: Some(entry.relative.clone()),
+                description: None,
+                mime_type: Some(mime_type(&entry.path).to_owned()),
+                size: u32::try_from(entry.size).ok(),
+                icons: None,
+                meta: None,
+            }
+            .no_annotation(),
+        )
+    }
+
+    /// Every served file, sorted by path.
+    async fn entries(&self) -> Result<Vec<Entry>, FileSystemResourceError> {
+        let inner = self.inner.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut entries = Vec::new();
+            inner.walk(&inner.root, "", inner.ignore.clone(), &mut entries)?;
+            entries.sort_by(|a, b| a.relative.cmp(&b.relative));
+            Ok(entries)
+        })
+        .await
+        .map_err(std::io::Error::other)?
+    }
+
+    /// The path of the file served as `uri`.
+    pub fn resolve(&self, uri: &str) -> Result<PathBuf, FileSystemResourceError> {
+        self.inner.resolve(uri).map(|(path, _)| path)
+    }
+
+    pub async fn read_resource(&self, uri: &str) -> Result<ReadResourceResult, ErrorData> {
+        let inner = self.inner.clone();
+        let uri = uri.to_owned();
+        let contents = tokio::task::spawn_blocking(move || inner.read(&uri))
+            .await
+            .map_err(|error| ErrorData::internal_error(error.to_string(), None))??;
+        Ok(ReadResourceResult {
+            contents: vec![contents],
+        })
+    }
+
+    /// Poll the root directory for changes every `interval`, until the returned watcher is
+    /// dropped.
+    ///
+    /// Changed and removed files are notified to their subscribers with `resources/updated`, and
+    /// the peers which listed the resources are notified with `resources/list_changed` when
+    /// files are added or removed.
+    pub fn watch(
+        &self,
+        interval: Duration,
+        subscriptions: ResourceSubscriptions,
+    ) -> FileSystemWatcher {
+        let cancellation = CancellationToken::new();
+        let token = cancellation.clone();
+        let resources = self.clone();
+        tokio::spawn(async move {
+            let mut snapshot = match resources.snapshot().await {
+                Ok(snapshot) => snapshot,
+                Err(error) => {
+                    tracing::warn!(%error, "failed to watch resources");
+                    HashMap::new()
+                }
+            };
+            let mut ticker = tokio::time::interval(interval);
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            ticker.tick().await;
+            loop {
+                tokio::select! {
+                    _ = token.cancelled() => break,
+                    _ = ticker.tick() => {}
+                }
+                let current = match resources.snapshot().await {
+                    Ok(current) => current,
+                    Err(error) => {
+                        tracing::warn!(%error, "failed to watch resources");
+                        continue;
+                    }
+                };
+                let list_changed = current.len() != snapshot.len()
+                    || current.keys().any(|uri| !snapshot.contains_key(uri));
+                for (uri, state) in &snapshot {
+                    if current.get(uri) != Some(state) {
+                        subscriptions.notify_updated(uri.clone()).await;
+                    }
+                }
+                if list_changed {
+                    resources.notify_list_changed().await;
+                }
+                snapshot = current;
+            }
+        });
+        FileSystemWatcher {
+            _guard: cancellation.drop_guard(),
+        }
+    }
+
+    async fn snapshot(
+        &self,
+    ) -> Result<HashMap<String, (u64, Option<SystemTime>)>, FileSystemResourceError> {
+        Ok(self
+            .entries()
+            .await?
+            .into_iter()
+            .filter_map(|entry| Some((self.uri_of(&entry.path)?, (entry.size, entry.modified))))
+            .collect())
+    }
+
+    async fn notify_list_changed(&self) {
+        let listeners = {
+            let mut listeners = self
+                .inner
+                .listeners
+                .lock()
+                .expect("listeners lock poisoned");
+            listeners.retain(|listener| !listener.is_transport_closed());
+            listeners.clone()
+        };
+        for listener in listeners {
+            if let Err(error) = listener.notify_resource_list_changed().await {
+                tracing::warn!(%error, "failed to notify resource list change");
+            }
+        }
+    }
+}
+
+/// Stops watching for changes when dropped, see [`FileSystemResources::watch`].
+#[derive(Debug)]
+pub struct FileSystemWatcher {
+    _guard: DropGuard,
+}
+
+impl Inner {
+    fn is_hidden(&self, name: &str) -> bool {
+        !self.include_hidden && name.starts_with('.')
+    }
+
+    fn load_ignore_files(&self, directory: &Path, relative: &str, rules: &mut Vec<IgnoreRule>) {
+        if !self.ignore_files {
+            return;
+        }
+        for file in IGNORE_FILES {
+            if let Ok(content) = std::fs::read_to_string(directory.join(file)) {
+                rules.extend(
+                    content
+                        .lines()
+                        .filter_map(|line| IgnoreRule::parse(line, relative)),
+                );
+            }
+        }
+    }
+
+    fn walk(
+        &self,
+        directory: &Path,
+        relative: &str,
+        mut rules: Vec<IgnoreRule>,
+        entries: &mut Vec<Entry>,
+    ) -> Result<(), FileSystemResourceError> {
+        self.load_ignore_files(directory, relative, &mut rules);
+        let dir_entries = match std::fs::read_dir(directory) {
+            Ok(dir_entries) => dir_entries,
+            Err(error) if !relative.is_empty() => {
+                tracing::warn!(directory = %directory.display(), %error, "skip unreadable directory");
+                return Ok(());
+            }
+            Err(error) => return Err(error.into()),
+        };
+        for dir_entry in dir_entries {
+            let dir_entry = dir_entry?;
+            let Ok(name) = dir_entry.file_name().into_string() else {
+                continue;
+            };
+            if self.is_hidden(&name) {
+                continue;
+            }
+            let child_relative = if relative.is_empty() {
+                name
+            } else {
+                format!("{relative}/{name}")
+            };
+            let path = dir_entry.path();
+            let file_type = dir_entry.file_type()?;
+            if file_type.is_symlink() {
+                // symlinked files are served if they resolve under the root, directories are
+                // not followed to avoid cycles
+                let Ok(target) = path.canonicalize() else {
+                    continue;
+                };
+                let Ok(metadata) = target.metadata() else {
+                    continue;
+                };
+                if !metadata.is_file()
+                    || is_ignored(&rules, &child_relative, false)
+                    || !self.is_served_target(&target)
+                {
+                    continue;
+                }
+                entries.push(Entry {
+                    relative: child_relative,
+                    path,
+                    size: metadata.len(),
+                    modified: metadata.modified().ok(),
+                });
+            } else if file_type.is_dir() {
+                if !is_ignored(&rules, &child_relative, true) {
+                    self.walk(&path, &child_relative, rules.clone(), entries)?;
+                }
+            } else if file_type.is_file() && !is_ignored(&rules, &child_relative, false) {
+                let metadata = dir_entry.metadata()?;
+                entries.push(Entry {
+                    relative: child_relative,
+                    path,
+                    size: metadata.len(),
+                    modified: metadata.modified().ok(),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// The canonical path and metadata of the file served as `uri`.
+    fn resolve(&self, uri: &str) -> Result<(PathBuf, std::fs::Metadata), FileSystemResourceError> {
+        let invalid = || FileSystemResourceError::InvalidUri(uri.to_owned());
+        let url = url::Url::parse(uri).map_err(|_| invalid())?;
+        if url.scheme() != "file" {
+            return Err(invalid());
+        }
+        let path = url.to_file_path().map_err(|_| invalid())?;
+        let relative = path
+            .strip_prefix(&self.root)
+            .map_err(|_| FileSystemResourceError::OutsideRoot(uri.to_owned()))?;
+
+        if !self.is_served(relative) {
+            return Err(FileSystemResourceError::Ignored(uri.to_owned()));
+        }
+        let canonical = path.canonicalize()?;
+        // a symlinked file is served if its target is, directories are not followed
+        if canonical != path {
+            let parent_is_canonical = path
+                .parent()
+                .map(|parent| {
+                    parent
+                        .canonicalize()
+                        .is_ok_and(|canonical| canonical == parent)
+                })
+                .unwrap_or(false);
+            if !parent_is_canonical || !self.is_served_target(&canonical) {
+                return Err(FileSystemResourceError::OutsideRoot(uri.to_owned()));
+            }
+        }
+        let metadata = canonical.metadata()?;
+        if !metadata.is_file() {
+            return Err(FileSystemResourceError::NotAFile(uri.to_owned()));
+        }
+        Ok((canonical, metadata))
+    }
+
+    /// Whether the canonical path of a symlink target is a served file.
+    fn is_served_target(&self, target: &Path) -> bool {
+        target
+            .strip_prefix(&self.root)
+            .is_ok_and(|relative| self.is_served(relative))
+    }
+
+    /// Whether no component of a path relative to the root is hidden or ignored, without
+    /// resolving symlinks.
+    fn is_served(&self, relative: &Path) -> bool {
+        let mut rules = self.ignore.clone();
+        let mut directory = self.root.clone();
+        let mut relative_path = String::new();
+        let components = relative.components().collect::<Vec<_>>();
+        for (index, component) in components.iter().enumerate() {
+            let Component::Normal(name) = component else {
+                return false;
+            };
+            let Some(name) = name.to_str() else {
+                return false;
+            };
+            if self.is_hidden(name) {
+                return false;
+            }
+            self.load_ignore_files(&directory, &relative_path, &mut rules);
+            if !relative_path.is_empty() {
+                relative_path.push('/');
+            }
+            relative_path.push_str(name);
+            let is_dir = index + 1 < components.len();
+            if is_ignored(&rules, &relative_path, is_dir) {
+                return false;
+            }
+            directory.push(name);
+        }
+        !components.is_empty()
+    }
+
+    fn read(&self, uri: &str) -> Result<ResourceContents, FileSystemResourceError> {
+        let (path, metadata) = self.resolve(uri)?;
+        if metadata.len() > self.max_size {
+            return Err(FileSystemResourceError::TooLarge {
+                uri: uri.to_owned(),
+                size: metadata.len(),
+                limit: self.max_size,
+            });
+        }
+        let bytes = std::fs::read(&path)?;
+        let mime_type = mime_type(&path).to_owned();
+        let bytes = if bytes.len() as u64 <= self.max_text_size {
+            match String::from_utf8(bytes) {
+                Ok(text) => {
+                    return Ok(ResourceContents::TextResourceContents {
+                        uri: uri.to_owned(),
+                        mime_type: Some(mime_type),
+                        text,
+                        meta: None,
+                    });
+                }
+                Err(error) => error.into_bytes(),
+            }
+        } else {
+            bytes
+        };
+        Ok(ResourceContents::BlobResourceContents {
+            uri: uri.to_owned(),
+            mime_type: Some(mime_type),
+            blob: BASE64_STANDARD.encode(bytes),
+            meta: None,
+        })
+    }
+}
+
+fn mime_type(path: &Path) -> &'static str {
+    mime_guess::from_path(path)
+        .first_raw()
+        .unwrap_or("application/octet-stream")
+}
+
+/// A line of a gitignore file.
+#[derive(Debug, Clone, PartialEq)]
+struct IgnoreRule {
+    /// The directory of the ignore file, relative to the root
+    base: String,
+    pattern: Vec<char>,
+    negated: bool,
+    directory_only: bool,
+    /// Whether the pattern is matched against the whole path instead of the file name
+    anchored: bool,
+}
+
+impl IgnoreRule {
+    fn parse(line: &str, base: &str) -> Option<Self> {
+        let line = line.trim_end();
+        if line.is_empty() || line.starts_with('#') {
+            return None;
+        }
+        let (negated, line) = match line.strip_prefix('!') {
+            Some(line) => (true, line),
+            None => (false, line.strip_prefix('\\').unwrap_or(line)),
+        };
+        let (directory_only, line) = match line.strip_suffix('/') {
+            Some(line) => (true, line),
+            None => (false, line),
+        };
+        let anchored = line.contains('/');
+        let line = line.strip_prefix('/').unwrap_or(line);
+        if line.is_empty() {
+            return None;
+        }
+        Some(Self {
+            base: base.to_owned(),
+            pattern: line.chars().collect(),
+            negated,
+            directory_only,
+            anchored,
+        })
+    }
+
+    fn matches(&self, relative: &str, is_dir: bool) -> bool {
+        if self.directory_only && !is_dir {
+            return false;
+        }
+        let relative = if self.base.is_empty() {
+            relative
+        } else {
+            match relative
+                .strip_prefix(self.base.as_str())
+                .and_then(|rest| rest.strip_prefix('/'))
+            {
+                Some(rest) => rest,
+                None => return false,
+            }
+        };
+        let subject = if self.anchored {
+            relative
+        } else {
+            relative.rsplit('/').next().unwrap_or(relative)
+        };
+        glob_match(&self.pattern, &subject.chars().collect::<Vec<_>>())
+    }
+}
+
+/// Whether the path is ignored by the rules, the last matching rule winning.
+fn is_ignored(rules: &[IgnoreRule], relative: &str, is_dir: bool) -> bool {
+    rules
+        .iter()
+        .rev()
+        .find(|rule| rule.matches(relative, is_dir))
+        .is_some_and(|rule| !rule.negated)
+}
+
+fn glob_match(pattern: &[char], subject: &[char]) -> bool {
+    match pattern {
+        [] => subject.is_empty(),
+        ['*', '*', '/', rest @ ..] => {
+            // zero or more directories
+            glob_match(rest, subject)
+                || subject
+                    .iter()
+                    .enumerate()
+                    .any(|(index, c)| *c == '/' && glob_match(rest, &subject[index + 1..]))
+        }
+        ['*', '*'] => true,
+        ['*', rest @ ..] => {
+            let end = subject
+                .iter()
+                .position(|c| *c == '/')
+                .unwrap_or(subject.len());
+            (0..=end).any(|index| glob_match(rest, &subject[index..]))
+        }
+        ['?', rest @ ..] => {
+            matches!(subject.first(), Some(c) if *c != '/') && glob_match(rest, &subject[1..])
+        }
+        ['[', rest @ ..] => {
+            let Some(close) = rest.iter().skip(1).position(|c| *c == ']').map(|i| i + 1) else {
+                return subject.first() == Some(&'[') && glob_match(rest, &subject[1..]);
+            };
+            let Some(c) = subject.first().filter(|c| **c != '/') else {
+                return false;
+            };
+            let (negated, class) = match rest[..close].split_first() {
+                Some(('!' | '^', class)) => (true, class),
+                _ => (false, &rest[..close]),
+            };
+            let mut matched = false;
+            let mut index = 0;
+            while index < class.len() {
+                if index + 2 < class.len() && class[index + 1] == '-' {
+                    matched |= (class[index]..=class[index + 2]).contains(c);
+                    index += 3;
+                } else {
+                    matched |= class[index] == *c;
+                    index += 1;
+                }
+            }
+            matched != negated && glob_match(&rest[close + 1..], &subject[1..])
+        }
+        ['\\', c, rest @ ..] | [c, rest @ ..] => {
+            subject.first() == Some(c) && glob_match(rest, &subject[1..])
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ignored(rules: &[&str], relative: &str, is_dir: bool) -> bool {
+        let rules = rules
+            .iter()
+            .filter_map(|rule| IgnoreRule::parse(rule, ""))
+            .collect::<Vec<_>>();
+        is_ignored(&rules, relative, is_dir)
+    }
+
+    #[test]
+    fn test_ignore_rules() {
+        assert!(ignored(&["*.log"], "logs/today.log", false));
+        assert!(!ignored(&["*.log", "!keep.log"], "keep.log", false));
+        assert!(ignored(&["target/"], "target", true));
+        assert!(!ignored(&["target/"], "target", false));
+        assert!(ignored(&["/build"], "build", true));
+        assert!(!ignored(&["/build"], "src/build", true));
+        assert!(ignored(&["docs/**/*.tmp"], "docs/a/b/c.tmp", false));
+        assert!(ignored(&["docs/**/*.tmp"], "docs/c.tmp", false));
+        assert!(ignored(&["file[0-9].txt"], "file7.txt", false));
+        assert!(!ignored(&["file[!0-9].txt"], "file7.txt", false));
+        assert!(!ignored(&["# comment", ""], "comment", false));
+
+        let rules = [IgnoreRule::parse("*.md", "docs").unwrap()];
+        assert!(is_ignored(&rules, "docs/readme.md", false));
+        assert!(!is_ignored(&rules, "readme.md", false));
+    }
+}
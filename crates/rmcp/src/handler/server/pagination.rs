@@ -0,0 +1,144 @@
+//! Split the results of list requests into pages.
+//!
+//! Items are ordered by a key, such as the name of a tool or the URI of a resource, and a
+//! cursor holds the key of the last item of a page. The next page starts after that key, so
+//! adding or removing items between requests neither skips nor repeats the others.
+use crate::model::{Cursor, PaginatedRequestParams};
+
+/// A page of a list.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    /// The cursor of the next page, `None` for the last one
+    pub next_cursor: Option<Cursor>,
+}
+
+/// Splits lists into pages of at most `page_size` items.
+///
+/// The default paginator returns every item in a single page, see [`Paginator::unlimited`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Paginator {
+    page_size: usize,
+}
+
+impl Default for Paginator {
+    fn default() -> Self {
+        Self::unlimited()
+    }
+}
+
+impl Paginator {
+    /// A paginator of pages of `page_size` items, at least one.
+    pub fn new(page_size: usize) -> Self {
+        Self {
+            page_size: page_size.max(1),
+        }
+    }
+
+    /// A paginator returning every item in a single page.
+    pub fn unlimited() -> Self {
+        Self::new(usize::MAX)
+    }
+
+    pub fn page_size(&self) -> usize {
+        self.page_size
+    }
+
+    /// The page of `items` requested, ordered by `key`.
+    ///
+    /// A cursor which was not returned by a paginator is an invalid params error.
+    pub fn paginate<T>(
+        &self,
+        mut items: Vec<T>,
+        key: impl Fn(&T) -> &str,
+        request: Option<&PaginatedRequestParams>,
+    ) -> Result<Page<T>, crate::ErrorData> {
+        items.sort_by(|a, b| key(a).cmp(key(b)));
+        if let Some(cursor) = request.and_then(|request| request.cursor.as_deref()) {
+            let after = decode_cursor(cursor)?;
+            let start = items.partition_point(|item| key(item) <= after.as_str());
+            items.drain(..start);
+        }
+        let next_cursor = if items.len() > self.page_size {
+            items.truncate(self.page_size);
+            items.last().map(|item| encode_cursor(key(item)))
+        } else {
+            None
+        };
+        Ok(Page { items, next_cursor })
+    }
+}
+
+fn encode_cursor(key: &str) -> Cursor {
+    key.bytes().map(|byte| format!("{byte:02x}")).collect()
+}
+
+fn decode_cursor(cursor: &str) -> Result<String, crate::ErrorData> {
+    let invalid = || {
+        crate::ErrorData::invalid_params(
+            "invalid cursor",
+            Some(serde_json::json!({ "cursor": cursor })),
+        )
+    };
+    if cursor.len() % 2 != 0 {
+        return Err(invalid());
+    }
+    let bytes = (0..cursor.len())
+        .step_by(2)
+        .map(|i| {
+            cursor
+                .get(i..i + 2)
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+        })
+        .collect::<Option<Vec<_>>>()
+        .ok_or_else(invalid)?;
+    String::from_utf8(bytes).map_err(|_| invalid())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(cursor: Option<Cursor>) -> PaginatedRequestParams {
+        PaginatedRequestParams { meta: None, cursor }
+    }
+
+    #[test]
+    fn test_paginate() {
+        let paginator = Paginator::new(2);
+        let items = vec!["d", "a", "c", "b", "e"];
+        let page = paginator.paginate(items.clone(), |s| *s, None).unwrap();
+        assert_eq!(page.items, ["a", "b"]);
+
+        let page = paginator
+            .paginate(items.clone(), |s| *s, Some(&request(page.next_cursor)))
+            .unwrap();
+        assert_eq!(page.items, ["c", "d"]);
+
+        // "c" was removed and "cc" added in between
+        let items = vec!["d", "a", "cc", "b", "e"];
+        let page = paginator
+            .paginate(items, |s| *s, Some(&request(page.next_cursor)))
+            .unwrap();
+        assert_eq!(page.items, ["e"]);
+        assert_eq!(page.next_cursor, None);
+
+        let page = Paginator::default()
+            .paginate(vec!["b", "a"], |s| *s, None)
+            .unwrap();
+        assert_eq!(page.items, ["a", "b"]);
+        assert_eq!(page.next_cursor, None);
+    }
+
+    #[test]
+    fn test_invalid_cursor() {
+        let paginator = Paginator::new(2);
+        for cursor in ["abc", "zz", "ff"] {
+            let error = paginator
+                .paginate(vec!["a"], |s| *s, Some(&request(Some(cursor.into()))))
+                .unwrap_err();
+            assert_eq!(error.code, crate::model::ErrorCode::INVALID_PARAMS);
+        }
+        assert_eq!(decode_cursor(&encode_cursor("tool/é")).unwrap(), "tool/é");
+    }
+}
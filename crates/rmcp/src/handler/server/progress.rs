@@ -0,0 +1,232 @@
+//! Report the progress of a request to the client.
+//!
+//! [`Progress`] is an extractor for tool and prompt functions. It sends
+//! `notifications/progress` with the token of the request, and does nothing when the client
+//! didn't ask for progress:
+//!
+//! ```rust,ignore
+//! #[tool(description = "Process files")]
+//! async fn process(&self, progress: Progress) -> String {
+//!     for i in 0..100 {
+//!         progress.report(i as f64, Some(100.0), Some("processing"));
+//!         // ...
+//!     }
+//!     "done".into()
+//! }
+//! ```
+//!
+//! Updates are rate limited, so only the latest one is sent when several are reported within
+//! [`Progress::DEFAULT_INTERVAL`]. Updates which don't increase the progress are dropped.
+use std::{
+    sync::{Arc, Mutex, OnceLock},
+    time::Duration,
+};
+
+use tokio::sync::watch;
+use tokio_util::sync::CancellationToken;
+
+use super::common::{AsRequestContext, FromContextPart};
+use crate::{
+    Peer, RoleServer,
+    model::{ProgressNotificationParam, ProgressToken},
+};
+
+/// Reports the progress of a request, see the [module](self) documentation.
+///
+/// Cloning shares the progress.
+#[derive(Clone, Default)]
+pub struct Progress {
+    inner: Option<Arc<Inner>>,
+    scope: Option<Arc<Scope>>,
+}
+
+struct Inner {
+    peer: Peer<RoleServer>,
+    token: ProgressToken,
+    interval: Duration,
+    state: Mutex<State>,
+    updates: watch::Sender<Option<ProgressNotificationParam>>,
+    sender: OnceLock<()>,
+    /// Cancelled once every clone is dropped, to send the last update without waiting
+    dropped: CancellationToken,
+}
+
+impl Drop for Inner {
+    fn drop(&mut self) {
+        self.dropped.cancel();
+    }
+}
+
+#[derive(Default)]
+struct State {
+    progress: Option<f64>,
+    total: Option<f64>,
+}
+
+/// A part of the parent progress, from `start` to `end`.
+struct Scope {
+    parent: Progress,
+    start: f64,
+    end: f64,
+    total: Mutex<Option<f64>>,
+}
+
+impl std::fmt::Debug for Progress {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut debug = f.debug_struct("Progress");
+        if let Some(inner) = &self.inner {
+            debug.field("token", &inner.token);
+        }
+        if let Some(scope) = &self.scope {
+            debug.field("start", &scope.start).field("end", &scope.end);
+        }
+        debug.finish()
+    }
+}
+
+impl Progress {
+    pub const DEFAULT_INTERVAL: Duration = Duration::from_millis(100);
+
+    /// Report progress to `peer` with `token`.
+    pub fn new(peer: Peer<RoleServer>, token: ProgressToken) -> Self {
+        Self::with_interval(peer, token, Self::DEFAULT_INTERVAL)
+    }
+
+    /// Report progress to `peer` with `token`, sending at most one update per `interval`.
+    pub fn with_interval(peer: Peer<RoleServer>, token: ProgressToken, interval: Duration) -> Self {
+        Self {
+            inner: Some(Arc::new(Inner {
+                peer,
+                token,
+                interval,
+                state: Mutex::default(),
+                updates: watch::channel(None).0,
+                sender: OnceLock::new(),
+                dropped: CancellationToken::new(),
+            })),
+            scope: None,
+        }
+    }
+
+    /// A progress reporting nothing, as when the client sent no token.
+    pub fn disabled() -> Self {
+        Self::default()
+    }
+
+    /// Whether the client asked for progress.
+    pub fn is_enabled(&self) -> bool {
+        self.inner.is_some()
+    }
+
+    /// Report `current` out of `total`, which is kept from the previous report if `None`.
+    ///
+    /// The update is dropped unless `current` exceeds the progress already reported.
+    pub fn report(&self, current: f64, total: Option<f64>, message: Option<&str>) {
+        if let Some(scope) = &self.scope {
+            let mut scope_total = scope.total.lock().expect("progress lock poisoned");
+            if total.is_some() {
+                *scope_total = total;
+            }
+            let span = scope.end - scope.start;
+            let advanced = match *scope_total {
+                Some(total) if total > 0.0 => span * (current / total).min(1.0),
+                _ => current.min(span),
+            };
+            drop(scope_total);
+            scope.parent.report(scope.start + advanced, None, message);
+            return;
+        }
+        let Some(inner) = &self.inner else {
+            return;
+        };
+        let mut state = inner.state.lock().expect("progress lock poisoned");
+        if state.progress.is_some_and(|progress| current <= progress) {
+            tracing::debug!(
+                current,
+                progress = state.progress,
+                "drop non increasing progress"
+            );
+            return;
+        }
+        state.progress = Some(current);
+        if total.is_some() {
+            state.total = total;
+        }
+        inner.updates.send_replace(Some(ProgressNotificationParam {
+            progress_token: inner.token.clone(),
+            progress: current,
+            total: state.total,
+            message: message.map(Into::into),
+        }));
+        drop(state);
+        inner.sender.get_or_init(|| Self::spawn_sender(inner));
+    }
+
+    /// A progress for a step of this one: reporting all of its total moves this progress
+    /// from `start` to `end`.
+    ///
+    /// Without a total, its progress is added to `start`, up to `end`.
+    pub fn scope(&self, start: f64, end: f64) -> Progress {
+        Progress {
+            inner: self.inner.clone(),
+            scope: Some(Arc::new(Scope {
+                parent: self.clone(),
+                start,
+                end,
+                total: Mutex::new(None),
+            })),
+        }
+    }
+
+    /// Send the latest update once per interval, and the last one as soon as every clone of
+    /// the progress is dropped.
+    fn spawn_sender(inner: &Arc<Inner>) {
+        let mut updates = inner.updates.subscribe();
+        let peer = inner.peer.clone();
+        let interval = inner.interval;
+        let dropped = inner.dropped.clone();
+        tokio::spawn(async move {
+            while updates.changed().await.is_ok() {
+                let update = updates.borrow_and_update().clone();
+                if let Some(update) = update {
+                    if let Err(error) = peer.notify_progress(update).await {
+                        tracing::warn!(%error, "failed to notify progress");
+                        break;
+                    }
+                }
+                tokio::select! {
+                    _ = tokio::time::sleep(interval) => {}
+                    _ = dropped.cancelled() => {}
+                }
+            }
+        });
+    }
+}
+
+impl<C> FromContextPart<C> for Progress
+where
+    C: AsRequestContext,
+{
+    fn from_context_part(context: &mut C) -> Result<Self, crate::ErrorData> {
+        let context = context.as_request_context();
+        Ok(match context.meta.get_progress_token() {
+            Some(token) => Progress::new(context.peer.clone(), token),
+            None => Progress::disabled(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_disabled_progress_is_a_no_op() {
+        let progress = Progress::disabled();
+        assert!(!progress.is_enabled());
+        progress.report(1.0, Some(2.0), Some("half"));
+        let step = progress.scope(1.0, 2.0);
+        assert!(!step.is_enabled());
+        step.report(1.0, None, None);
+    }
+}
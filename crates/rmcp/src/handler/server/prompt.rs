@@ -365,3 +365,100 @@ pub fn cached_arguments_from_schema<T: schemars::JsonSchema + std::any::Any>()
         None
     }
 }
+
+/// Extract the values of the enum-typed properties of a type's JSON schema, to complete the
+/// prompt arguments they describe
+pub fn cached_argument_values_from_schema<T: schemars::JsonSchema + std::any::Any>()
+-> Vec<(String, Vec<String>)> {
+    let schema = super::common::schema_for_type::<T>();
+    let Some(properties) = schema.get("properties").and_then(|p| p.as_object()) else {
+        return Vec::new();
+    };
+    properties
+        .iter()
+        .filter_map(|(name, prop_schema)| {
+            enum_values(prop_schema, &schema).map(|values| (name.clone(), values))
+        })
+        .collect()
+}
+
+fn enum_values(schema: &serde_json::Value, root: &crate::model::JsonObject) -> Option<Vec<String>> {
+    if let Some(reference) = schema.get("$ref").and_then(|r| r.as_str()) {
+        let (defs, name) = reference.strip_prefix("#/")?.split_once('/')?;
+        return enum_values(root.get(defs)?.get(name)?, root);
+    }
+    if let Some(values) = schema.get("enum").and_then(|e| e.as_array()) {
+        let values = values
+            .iter()
+            .filter_map(|value| value.as_str().map(str::to_owned))
+            .collect::<Vec<_>>();
+        return (!values.is_empty()).then_some(values);
+    }
+    if let Some(value) = schema.get("const").and_then(|c| c.as_str()) {
+        return Some(vec![value.to_owned()]);
+    }
+    let variants = schema
+        .get("oneOf")
+        .or_else(|| schema.get("anyOf"))
+        .and_then(|v| v.as_array())?;
+    let mut values = Vec::new();
+    for variant in variants {
+        let is_null = variant.get("const").is_some_and(|c| c.is_null())
+            || variant.get("type").and_then(|t| t.as_str()) == Some("null");
+        if !is_null {
+            values.extend(enum_values(variant, root)?);
+        }
+    }
+    (!values.is_empty()).then_some(values)
+}
+
+#[cfg(test)]
+mod tests {
+    use schemars::JsonSchema;
+
+    use super::*;
+
+    #[test]
+    fn test_argument_values_from_schema() {
+        #[derive(JsonSchema)]
+        #[serde(rename_all = "lowercase")]
+        #[allow(dead_code)]
+        enum Language {
+            Rust,
+            Python,
+        }
+
+        #[derive(JsonSchema)]
+        #[allow(dead_code)]
+        enum Severity {
+            /// Must be fixed
+            Error,
+            /// Should be fixed
+            Warning,
+        }
+
+        #[derive(JsonSchema)]
+        #[allow(dead_code)]
+        struct ReviewArgs {
+            language: Language,
+            severity: Option<Severity>,
+            file: String,
+        }
+
+        let mut values = cached_argument_values_from_schema::<ReviewArgs>();
+        values.sort();
+        assert_eq!(
+            values,
+            vec![
+                (
+                    "language".to_owned(),
+                    vec!["rust".to_owned(), "python".to_owned()]
+                ),
+                (
+                    "severity".to_owned(),
+                    vec!["Error".to_owned(), "Warning".to_owned()]
+                ),
+            ]
+        );
+    }
+}
@@ -0,0 +1,353 @@
+//! A registry of tools and prompts that can change while sessions are live.
+use std::sync::{Arc, Mutex, RwLock, Weak};
+
+use super::{
+    prompt::PromptContext,
+    router::{
+        prompt::{IntoPromptRoute, PromptRouter},
+        tool::{IntoToolRoute, ToolRouter},
+    },
+    tool::ToolCallContext,
+};
+use crate::{
+    RoleServer,
+    model::{
+        CallToolResult, Extensions, GetPromptResult, Prompt, PromptsCapability, ServerCapabilities,
+        Tool, ToolsCapability,
+    },
+    service::{Peer, RequestContext},
+};
+
+struct Inner<S> {
+    tools: RwLock<ToolRouter<S>>,
+    prompts: RwLock<PromptRouter<S>>,
+    peers: Mutex<Vec<Peer<RoleServer>>>,
+}
+
+/// Tools and prompts shared by every session of a server, which can be updated at any time.
+///
+/// Each change sends `notifications/tools/list_changed` or `notifications/prompts/list_changed`
+/// to the connected peers. A peer is connected once initialized, and disconnected when its
+/// service stops. Cloning shares the registry.
+///
+/// A [`Router`](super::router::Router) serves the registry with
+/// [`with_registry`](super::router::Router::with_registry). Otherwise, a handler answers from it
+/// and connects the peers itself:
+///
+/// ```rust,ignore
+/// impl ServerHandler for MyServer {
+///     fn get_info(&self) -> ServerInfo {
+///         ServerInfo {
+///             capabilities: self.registry.enable_capability(ServerCapabilities::default()),
+///             ..Default::default()
+///         }
+///     }
+///
+///     async fn on_initialized(&self, context: NotificationContext<RoleServer>) {
+///         self.registry.connect(&context.peer);
+///     }
+///
+///     async fn call_tool(
+///         &self,
+///         request: CallToolRequestParams,
+///         context: RequestContext<RoleServer>,
+///     ) -> Result<CallToolResult, ErrorData> {
+///         self.registry
+///             .call_tool(ToolCallContext::new(self, request, context))
+///             .await
+///     }
+///     // list_tools, get_prompt and list_prompts alike
+/// }
+///
+/// // later, while clients are connected
+/// registry.add_tool((tool, handler)).await;
+/// ```
+pub struct DynamicRegistry<S> {
+    inner: Arc<Inner<S>>,
+}
+
+impl<S> Clone for DynamicRegistry<S> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<S> std::fmt::Debug for DynamicRegistry<S> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let tools = self.inner.tools.read().expect("registry lock poisoned");
+        let prompts = self.inner.prompts.read().expect("registry lock poisoned");
+        f.debug_struct("DynamicRegistry")
+            .field("tools", &tools.map.keys().collect::<Vec<_>>())
+            .field("prompts", &prompts.map.keys().collect::<Vec<_>>())
+            .finish_non_exhaustive()
+    }
+}
+
+impl<S> Default for DynamicRegistry<S> {
+    fn default() -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                tools: RwLock::new(ToolRouter::default()),
+                prompts: RwLock::new(PromptRouter::default()),
+                peers: Mutex::new(Vec::new()),
+            }),
+        }
+    }
+}
+
+impl<S> DynamicRegistry<S>
+where
+    S: Send + Sync + 'static,
+{
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start with the routes and settings of `tools`.
+    pub fn with_tools(self, tools: ToolRouter<S>) -> Self {
+        {
+            let mut registered = self.tools_mut();
+            *registered = ToolRouter {
+                map: std::mem::take(&mut registered.map),
+                ..tools.without_routes()
+            };
+            registered.merge(tools);
+        }
+        self
+    }
+
+    /// Start with the routes of `prompts`.
+    pub fn with_prompts(self, prompts: PromptRouter<S>) -> Self {
+        self.prompts_mut().merge(prompts);
+        self
+    }
+
+    /// Set `tools.listChanged` and `prompts.listChanged` in the capabilities.
+    pub fn enable_capability(&self, mut capabilities: ServerCapabilities) -> ServerCapabilities {
+        capabilities
+            .tools
+            .get_or_insert_with(ToolsCapability::default)
+            .list_changed = Some(true);
+        capabilities
+            .prompts
+            .get_or_insert_with(PromptsCapability::default)
+            .list_changed = Some(true);
+        capabilities
+    }
+
+    /// Notify `peer` of the changes from now on, until its service stops.
+    pub fn connect(&self, peer: &Peer<RoleServer>) {
+        let mut peers = self.inner.peers.lock().expect("registry lock poisoned");
+        if peers.iter().any(|connected| connected.is_same_peer(peer)) {
+            return;
+        }
+        peers.push(peer.clone());
+        Self::remove_when_closed(Arc::downgrade(&self.inner), peer.clone());
+    }
+
+    /// The number of connected peers.
+    pub fn peer_count(&self) -> usize {
+        self.inner
+            .peers
+            .lock()
+            .expect("registry lock poisoned")
+            .iter()
+            .filter(|peer| !peer.is_transport_closed())
+            .count()
+    }
+
+    pub async fn add_tool<R, A>(&self, route: R)
+    where
+        R: IntoToolRoute<S, A>,
+    {
+        let route = route.into_tool_route();
+        self.update_tools(|tools| tools.add_route(route)).await;
+    }
+
+    /// Remove a tool, returning whether it was registered.
+    pub async fn remove_tool(&self, name: &str) -> bool {
+        let removed = self.tools_mut().map.remove(name).is_some();
+        if removed {
+            self.notify_tool_list_changed().await;
+        }
+        removed
+    }
+
+    /// Change the tools, notifying the peers once.
+    pub async fn update_tools(&self, update: impl FnOnce(&mut ToolRouter<S>)) {
+        update(&mut self.tools_mut());
+        self.notify_tool_list_changed().await;
+    }
+
+    pub async fn add_prompt<R, A: 'static>(&self, route: R)
+    where
+        R: IntoPromptRoute<S, A>,
+    {
+        let route = route.into_prompt_route();
+        self.update_prompts(|prompts| prompts.add_route(route))
+            .await;
+    }
+
+    /// Remove a prompt, returning whether it was registered.
+    pub async fn remove_prompt(&self, name: &str) -> bool {
+        let removed = {
+            let mut prompts = self.prompts_mut();
+            let removed = prompts.has_route(name);
+            prompts.remove_route(name);
+            removed
+        };
+        if removed {
+            self.notify_prompt_list_changed().await;
+        }
+        removed
+    }
+
+    /// Change the prompts, notifying the peers once.
+    pub async fn update_prompts(&self, update: impl FnOnce(&mut PromptRouter<S>)) {
+        update(&mut self.prompts_mut());
+        self.notify_prompt_list_changed().await;
+    }
+
+    pub fn has_tool(&self, name: &str) -> bool {
+        self.tools().has_route(name)
+    }
+
+    pub fn list_tools(&self) -> Vec<Tool> {
+        self.tools().list_all()
+    }
+
+    /// The tools the caller of a request with `extensions` may call.
+    pub fn list_authorized_tools(&self, extensions: &Extensions) -> Vec<Tool> {
+        self.tools().list_authorized(extensions)
+    }
+
+    /// The tools the session of `context` sees, see [`ToolRouter::list_for`].
+    pub fn list_tools_for(&self, context: &RequestContext<RoleServer>) -> Vec<Tool> {
+        self.tools().list_for(context)
+    }
+
+    pub async fn call_tool(
+        &self,
+        context: ToolCallContext<'_, S>,
+    ) -> Result<CallToolResult, crate::ErrorData> {
+        // dispatch through a router holding this route only, so the lock isn't held
+        let router = {
+            let tools = self.tools();
+            let mut router = tools.without_routes();
+            router.map.extend(
+                tools
+                    .map
+                    .get_key_value(context.name())
+                    .map(|(name, route)| (name.clone(), route.clone())),
+            );
+            router
+        };
+        router.call(context).await
+    }
+
+    pub fn has_prompt(&self, name: &str) -> bool {
+        self.prompts().has_route(name)
+    }
+
+    pub fn list_prompts(&self) -> Vec<Prompt> {
+        self.prompts().list_all()
+    }
+
+    /// The prompts the session of `context` sees, see [`PromptRouter::list_for`].
+    pub fn list_prompts_for(&self, context: &RequestContext<RoleServer>) -> Vec<Prompt> {
+        self.prompts().list_for(context)
+    }
+
+    pub async fn get_prompt(
+        &self,
+        context: PromptContext<'_, S>,
+    ) -> Result<GetPromptResult, crate::ErrorData> {
+        let route = {
+            let prompts = self.prompts();
+            prompts
+                .map
+                .get(context.name.as_str())
+                .filter(|route| prompts.visibility.is_visible(&route.attr, &context.context))
+                .cloned()
+        };
+        let route = route.ok_or_else(|| {
+            crate::ErrorData::invalid_params(format!("prompt '{}' not found", context.name), None)
+        })?;
+        (route.get)(context).await
+    }
+
+    /// Send `notifications/tools/list_changed` to the connected peers, returning how many
+    /// were notified.
+    pub async fn notify_tool_list_changed(&self) -> usize {
+        let peers = self.connected_peers();
+        let results =
+            futures::future::join_all(peers.iter().map(|peer| peer.notify_tool_list_changed()))
+                .await;
+        Self::count_sent(results, "tool")
+    }
+
+    /// Send `notifications/prompts/list_changed` to the connected peers, returning how many
+    /// were notified.
+    pub async fn notify_prompt_list_changed(&self) -> usize {
+        let peers = self.connected_peers();
+        let results =
+            futures::future::join_all(peers.iter().map(|peer| peer.notify_prompt_list_changed()))
+                .await;
+        Self::count_sent(results, "prompt")
+    }
+
+    fn tools(&self) -> std::sync::RwLockReadGuard<'_, ToolRouter<S>> {
+        self.inner.tools.read().expect("registry lock poisoned")
+    }
+
+    fn tools_mut(&self) -> std::sync::RwLockWriteGuard<'_, ToolRouter<S>> {
+        self.inner.tools.write().expect("registry lock poisoned")
+    }
+
+    fn prompts(&self) -> std::sync::RwLockReadGuard<'_, PromptRouter<S>> {
+        self.inner.prompts.read().expect("registry lock poisoned")
+    }
+
+    fn prompts_mut(&self) -> std::sync::RwLockWriteGuard<'_, PromptRouter<S>> {
+        self.inner.prompts.write().expect("registry lock poisoned")
+    }
+
+    fn connected_peers(&self) -> Vec<Peer<RoleServer>> {
+        self.inner
+            .peers
+            .lock()
+            .expect("registry lock poisoned")
+            .iter()
+            .filter(|peer| !peer.is_transport_closed())
+            .cloned()
+            .collect()
+    }
+
+    fn count_sent(results: Vec<Result<(), crate::service::ServiceError>>, list: &str) -> usize {
+        results
+            .into_iter()
+            .filter(|result| match result {
+                Ok(()) => true,
+                Err(error) => {
+                    tracing::warn!(%error, "failed to notify {list} list change");
+                    false
+                }
+            })
+            .count()
+    }
+
+    fn remove_when_closed(inner: Weak<Inner<S>>, peer: Peer<RoleServer>) {
+        tokio::spawn(async move {
+            peer.closed().await;
+            if let Some(inner) = inner.upgrade() {
+                inner
+                    .peers
+                    .lock()
+                    .expect("registry lock poisoned")
+                    .retain(|connected| !connected.is_same_peer(&peer));
+            }
+        });
+    }
+}
@@ -1 +1,328 @@
+//! Resource handling infrastructure for MCP servers
+//!
+//! This module provides the core types and traits for implementing resource handlers
+//! in MCP servers. A handler serves either a single resource, or every resource matching
+//! a URI template, in which case the variables extracted from the URI can be injected
+//! as typed [`Parameters`].
 
+use std::{collections::HashMap, marker::PhantomData};
+
+use futures::future::{BoxFuture, FutureExt};
+use serde::de::{self, DeserializeOwned, IntoDeserializer, Unexpected, Visitor};
+
+use super::common::{AsRequestContext, FromContextPart};
+pub use super::common::{Extension, RequestId};
+use crate::{
+    RoleServer,
+    handler::server::wrapper::Parameters,
+    model::{ReadResourceResult, ResourceContents},
+    service::RequestContext,
+};
+
+/// Context for resource read operations
+pub struct ResourceContext<'a, S> {
+    pub server: &'a S,
+    pub uri: String,
+    /// The variables extracted from the URI, empty for a static resource
+    pub variables: HashMap<String, String>,
+    pub context: RequestContext<RoleServer>,
+}
+
+impl<'a, S> ResourceContext<'a, S> {
+    pub fn new(server: &'a S, uri: String, context: RequestContext<RoleServer>) -> Self {
+        Self {
+            server,
+            uri,
+            variables: HashMap::new(),
+            context,
+        }
+    }
+}
+
+impl<S> AsRequestContext for ResourceContext<'_, S> {
+    fn as_request_context(&self) -> &RequestContext<RoleServer> {
+        &self.context
+    }
+
+    fn as_request_context_mut(&mut self) -> &mut RequestContext<RoleServer> {
+        &mut self.context
+    }
+}
+
+/// Trait for handling resource reads
+pub trait ReadResourceHandler<S, A> {
+    fn handle(
+        self,
+        context: ResourceContext<'_, S>,
+    ) -> BoxFuture<'_, Result<ReadResourceResult, crate::ErrorData>>;
+}
+
+/// Type alias for dynamic resource handlers
+pub type DynReadResourceHandler<S> = dyn for<'a> Fn(
+        ResourceContext<'a, S>,
+    ) -> BoxFuture<'a, Result<ReadResourceResult, crate::ErrorData>>
+    + Send
+    + Sync;
+
+/// Adapter type for sync methods
+pub struct SyncResourceMethodAdapter<P, R>(PhantomData<fn(P) -> R>);
+
+/// Trait for types that can be converted into ReadResourceResult
+pub trait IntoReadResourceResult {
+    /// Convert into the result of reading `uri`.
+    fn into_read_resource_result(self, uri: &str) -> Result<ReadResourceResult, crate::ErrorData>;
+}
+
+impl IntoReadResourceResult for ReadResourceResult {
+    fn into_read_resource_result(self, _uri: &str) -> Result<ReadResourceResult, crate::ErrorData> {
+        Ok(self)
+    }
+}
+
+impl IntoReadResourceResult for Vec<ResourceContents> {
+    fn into_read_resource_result(self, _uri: &str) -> Result<ReadResourceResult, crate::ErrorData> {
+        Ok(ReadResourceResult { contents: self })
+    }
+}
+
+impl IntoReadResourceResult for ResourceContents {
+    fn into_read_resource_result(self, _uri: &str) -> Result<ReadResourceResult, crate::ErrorData> {
+        Ok(ReadResourceResult {
+            contents: vec![self],
+        })
+    }
+}
+
+impl IntoReadResourceResult for String {
+    fn into_read_resource_result(self, uri: &str) -> Result<ReadResourceResult, crate::ErrorData> {
+        ResourceContents::text(self, uri).into_read_resource_result(uri)
+    }
+}
+
+impl IntoReadResourceResult for &'static str {
+    fn into_read_resource_result(self, uri: &str) -> Result<ReadResourceResult, crate::ErrorData> {
+        ResourceContents::text(self, uri).into_read_resource_result(uri)
+    }
+}
+
+impl<T: IntoReadResourceResult> IntoReadResourceResult for Result<T, crate::ErrorData> {
+    fn into_read_resource_result(self, uri: &str) -> Result<ReadResourceResult, crate::ErrorData> {
+        self.and_then(|v| v.into_read_resource_result(uri))
+    }
+}
+
+// Resource-specific extractor for the requested URI
+pub struct ResourceUri(pub String);
+
+impl<S> FromContextPart<ResourceContext<'_, S>> for ResourceUri {
+    fn from_context_part(context: &mut ResourceContext<S>) -> Result<Self, crate::ErrorData> {
+        Ok(Self(context.uri.clone()))
+    }
+}
+
+// Parameters of a resource are the variables of its URI template
+impl<S, P> FromContextPart<ResourceContext<'_, S>> for Parameters<P>
+where
+    P: DeserializeOwned,
+{
+    fn from_context_part(context: &mut ResourceContext<S>) -> Result<Self, crate::ErrorData> {
+        let variables = de::value::MapDeserializer::<_, de::value::Error>::new(
+            context
+                .variables
+                .iter()
+                .map(|(name, value)| (name.as_str(), VariableDeserializer(value))),
+        );
+        P::deserialize(variables).map(Parameters).map_err(|e| {
+            crate::ErrorData::invalid_params(
+                format!("Failed to parse URI template variables: {}", e),
+                None,
+            )
+        })
+    }
+}
+
+/// Deserialize a URI template variable, parsing it when a number or a boolean is expected.
+struct VariableDeserializer<'a>(&'a str);
+
+impl<'de> IntoDeserializer<'de, de::value::Error> for VariableDeserializer<'_> {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self::Deserializer {
+        self
+    }
+}
+
+macro_rules! deserialize_parsed {
+    ($($method: ident => $visit: ident)*) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+                match self.0.parse() {
+                    Ok(value) => visitor.$visit(value),
+                    Err(_) => Err(de::Error::invalid_value(Unexpected::Str(self.0), &visitor)),
+                }
+            }
+        )*
+    };
+}
+
+impl<'de> de::Deserializer<'de> for VariableDeserializer<'_> {
+    type Error = de::value::Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_str(self.0)
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_enum(self.0.into_deserializer())
+    }
+
+    deserialize_parsed! {
+        deserialize_bool => visit_bool
+        deserialize_i8 => visit_i8
+        deserialize_i16 => visit_i16
+        deserialize_i32 => visit_i32
+        deserialize_i64 => visit_i64
+        deserialize_u8 => visit_u8
+        deserialize_u16 => visit_u16
+        deserialize_u32 => visit_u32
+        deserialize_u64 => visit_u64
+        deserialize_f32 => visit_f32
+        deserialize_f64 => visit_f64
+    }
+
+    serde::forward_to_deserialize_any! {
+        char str string bytes byte_buf unit unit_struct seq tuple tuple_struct map struct
+        identifier ignored_any
+    }
+}
+
+// Macro to generate ReadResourceHandler implementations for various parameter combinations
+macro_rules! impl_read_resource_handler_for {
+    ($($T: ident)*) => {
+        impl_read_resource_handler_for!([] [$($T)*]);
+    };
+    // finished
+    ([$($Tn: ident)*] []) => {
+        impl_read_resource_handler_for!(@impl $($Tn)*);
+    };
+    ([$($Tn: ident)*] [$Tn_1: ident $($Rest: ident)*]) => {
+        impl_read_resource_handler_for!(@impl $($Tn)*);
+        impl_read_resource_handler_for!([$($Tn)* $Tn_1] [$($Rest)*]);
+    };
+    (@impl $($Tn: ident)*) => {
+        // Implementation for async methods (transformed by #[resource] macro)
+        impl<$($Tn,)* S, F, R> ReadResourceHandler<S, ($($Tn,)*)> for F
+        where
+            $(
+                $Tn: for<'a> FromContextPart<ResourceContext<'a, S>> + Send,
+            )*
+            F: FnOnce(&S, $($Tn,)*) -> BoxFuture<'_, R> + Send,
+            R: IntoReadResourceResult + Send + 'static,
+            S: Send + Sync + 'static,
+        {
+            #[allow(unused_variables, non_snake_case, unused_mut)]
+            fn handle(
+                self,
+                mut context: ResourceContext<'_, S>,
+            ) -> BoxFuture<'_, Result<ReadResourceResult, crate::ErrorData>>
+            {
+                $(
+                    let result = $Tn::from_context_part(&mut context);
+                    let $Tn = match result {
+                        Ok(value) => value,
+                        Err(e) => return std::future::ready(Err(e)).boxed(),
+                    };
+                )*
+                let service = context.server;
+                let uri = context.uri;
+                let fut = self(service, $($Tn,)*);
+                async move {
+                    let result = fut.await;
+                    result.into_read_resource_result(&uri)
+                }.boxed()
+            }
+        }
+
+        // Implementation for sync methods
+        impl<$($Tn,)* S, F, R> ReadResourceHandler<S, SyncResourceMethodAdapter<($($Tn,)*), R>> for F
+        where
+            $(
+                $Tn: for<'a> FromContextPart<ResourceContext<'a, S>> + Send,
+            )*
+            F: FnOnce(&S, $($Tn,)*) -> R + Send,
+            R: IntoReadResourceResult + Send,
+            S: Send + Sync,
+        {
+            #[allow(unused_variables, non_snake_case, unused_mut)]
+            fn handle(
+                self,
+                mut context: ResourceContext<'_, S>,
+            ) -> BoxFuture<'_, Result<ReadResourceResult, crate::ErrorData>>
+            {
+                $(
+                    let result = $Tn::from_context_part(&mut context);
+                    let $Tn = match result {
+                        Ok(value) => value,
+                        Err(e) => return std::future::ready(Err(e)).boxed(),
+                    };
+                )*
+                let service = context.server;
+                let result = self(service, $($Tn,)*);
+                std::future::ready(result.into_read_resource_result(&context.uri)).boxed()
+            }
+        }
+    };
+}
+
+// Invoke the macro to generate implementations for up to 16 parameters
+impl_read_resource_handler_for!(T0 T1 T2 T3 T4 T5 T6 T7 T8 T9 T10 T11 T12 T13 T14 T15);
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+
+    use super::*;
+
+    #[test]
+    fn test_typed_variables() {
+        #[derive(serde::Deserialize)]
+        struct Post {
+            id: u32,
+            slug: String,
+            draft: Option<bool>,
+        }
+
+        let variables = HashMap::from([
+            ("id".to_owned(), "42".to_owned()),
+            ("slug".to_owned(), "007".to_owned()),
+            ("draft".to_owned(), "true".to_owned()),
+        ]);
+        let map = de::value::MapDeserializer::<_, de::value::Error>::new(
+            variables
+                .iter()
+                .map(|(name, value)| (name.as_str(), VariableDeserializer(value))),
+        );
+        let post = Post::deserialize(map).unwrap();
+        assert_eq!(
+            (post.id, post.slug.as_str(), post.draft),
+            (42, "007", Some(true))
+        );
+    }
+}
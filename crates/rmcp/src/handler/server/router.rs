@@ -1,21 +1,44 @@
 use std::sync::Arc;
 
 use prompt::{IntoPromptRoute, PromptRoute};
+use resource::{IntoResourceRoute, ResourceRoute};
 use tool::{IntoToolRoute, ToolRoute};
 
-use super::ServerHandler;
+use super::{ServerHandler, registry::DynamicRegistry, subscription::ResourceSubscriptions};
 use crate::{
     RoleServer, Service,
-    model::{ClientRequest, ListPromptsResult, ListToolsResult, ServerResult},
+    model::{
+        ClientNotification, ClientRequest, ListPromptsResult, ListResourceTemplatesResult,
+        ListResourcesResult, ListToolsResult, ServerCapabilities, ServerResult,
+    },
     service::NotificationContext,
 };
 
+pub mod completion;
 pub mod prompt;
+pub mod resource;
 pub mod tool;
 
+/// Why the routes of two routers can't be combined.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum ComposeError {
+    #[error("a route named '{0}' already exists")]
+    Duplicate(String),
+    #[error("'{0}' is not a valid tool name")]
+    InvalidToolName(String),
+    /// A setting of a nested router which would not apply to its routes once nested
+    #[error("the nested router has {0}, which its routes would lose")]
+    NestedSetting(&'static str),
+}
+
 pub struct Router<S> {
     pub tool_router: tool::ToolRouter<S>,
     pub prompt_router: prompt::PromptRouter<S>,
+    pub resource_router: resource::ResourceRouter<S>,
+    /// Answers `resources/subscribe` and `resources/unsubscribe` when set
+    pub subscriptions: Option<ResourceSubscriptions>,
+    /// Tools and prompts served after the routers' own, when set
+    pub registry: Option<DynamicRegistry<S>>,
     pub service: Arc<S>,
 }
 
@@ -27,6 +50,9 @@ where
         Self {
             tool_router: tool::ToolRouter::new(),
             prompt_router: prompt::PromptRouter::new(),
+            resource_router: resource::ResourceRouter::new(),
+            subscriptions: None,
+            registry: None,
             service: Arc::new(service),
         }
     }
@@ -60,6 +86,57 @@ where
         }
         self
     }
+
+    pub fn with_resource<R, A: 'static>(mut self, route: R) -> Self
+    where
+        R: IntoResourceRoute<S, A>,
+    {
+        self.resource_router.add_route(route.into_resource_route());
+        self
+    }
+
+    pub fn with_resources(mut self, routes: impl IntoIterator<Item = ResourceRoute<S>>) -> Self {
+        for route in routes {
+            self.resource_router.add_route(route);
+        }
+        self
+    }
+
+    /// Record resource subscriptions in `subscriptions`, advertising the `resources.subscribe`
+    /// capability.
+    pub fn with_subscriptions(mut self, subscriptions: ResourceSubscriptions) -> Self {
+        self.subscriptions = Some(subscriptions);
+        self
+    }
+
+    /// Serve the tools and prompts of `registry`, advertising the `listChanged` capabilities
+    /// and notifying the initialized peers of its changes.
+    pub fn with_registry(mut self, registry: DynamicRegistry<S>) -> Self {
+        self.registry = Some(registry);
+        self
+    }
+
+    fn enable_capabilities(&self, mut capabilities: ServerCapabilities) -> ServerCapabilities {
+        if let Some(subscriptions) = &self.subscriptions {
+            capabilities = subscriptions.enable_capability(capabilities);
+        }
+        if let Some(registry) = &self.registry {
+            capabilities = registry.enable_capability(capabilities);
+        }
+        capabilities
+    }
+
+    fn registry_tool(&self, name: &str) -> Option<&DynamicRegistry<S>> {
+        self.registry
+            .as_ref()
+            .filter(|registry| !self.tool_router.has_route(name) && registry.has_tool(name))
+    }
+
+    fn registry_prompt(&self, name: &str) -> Option<&DynamicRegistry<S>> {
+        self.registry
+            .as_ref()
+            .filter(|registry| !self.prompt_router.has_route(name) && registry.has_prompt(name))
+    }
 }
 
 impl<S> Service<RoleServer> for Router<S>
@@ -71,6 +148,11 @@ where
         notification: <RoleServer as crate::service::ServiceRole>::PeerNot,
         context: NotificationContext<RoleServer>,
     ) -> Result<(), crate::ErrorData> {
+        if let (ClientNotification::InitializedNotification(_), Some(registry)) =
+            (&notification, &self.registry)
+        {
+            registry.connect(&context.peer);
+        }
         self.service
             .handle_notification(notification, context)
             .await
@@ -81,6 +163,20 @@ where
         context: crate::service::RequestContext<RoleServer>,
     ) -> Result<<RoleServer as crate::service::ServiceRole>::Resp, crate::ErrorData> {
         match request {
+            ClientRequest::CallToolRequest(request)
+                if self.registry_tool(request.params.name.as_ref()).is_some() =>
+            {
+                let registry = self
+                    .registry_tool(request.params.name.as_ref())
+                    .expect("checked by the guard");
+                let tool_call_context = crate::handler::server::tool::ToolCallContext::new(
+                    self.service.as_ref(),
+                    request.params,
+                    context,
+                );
+                let result = registry.call_tool(tool_call_context).await?;
+                Ok(ServerResult::CallToolResult(result))
+            }
             ClientRequest::CallToolRequest(request) => {
                 if self.tool_router.has_route(request.params.name.as_ref())
                     || !self.tool_router.transparent_when_not_found
@@ -98,13 +194,42 @@ where
                         .await
                 }
             }
-            ClientRequest::ListToolsRequest(_) => {
-                let tools = self.tool_router.list_all();
-                Ok(ServerResult::ListToolsResult(ListToolsResult {
+            ClientRequest::ListToolsRequest(request) => {
+                let mut tools = self.tool_router.list_for(&context);
+                if let Some(registry) = &self.registry {
+                    tools.extend(
+                        registry
+                            .list_tools_for(&context)
+                            .into_iter()
+                            .filter(|tool| !self.tool_router.has_route(&tool.name)),
+                    );
+                }
+                let page = self.tool_router.paginator.paginate(
                     tools,
+                    |tool| &tool.name,
+                    request.params.as_ref(),
+                )?;
+                Ok(ServerResult::ListToolsResult(ListToolsResult {
+                    tools: page.items,
+                    next_cursor: page.next_cursor,
                     ..Default::default()
                 }))
             }
+            ClientRequest::GetPromptRequest(request)
+                if self.registry_prompt(&request.params.name).is_some() =>
+            {
+                let registry = self
+                    .registry_prompt(&request.params.name)
+                    .expect("checked by the guard");
+                let prompt_context = crate::handler::server::prompt::PromptContext::new(
+                    self.service.as_ref(),
+                    request.params.name,
+                    request.params.arguments,
+                    context,
+                );
+                let result = registry.get_prompt(prompt_context).await?;
+                Ok(ServerResult::GetPromptResult(result))
+            }
             ClientRequest::GetPromptRequest(request) => {
                 if self.prompt_router.has_route(request.params.name.as_ref()) {
                     let prompt_context = crate::handler::server::prompt::PromptContext::new(
@@ -121,18 +246,109 @@ where
                         .await
                 }
             }
-            ClientRequest::ListPromptsRequest(_) => {
-                let prompts = self.prompt_router.list_all();
-                Ok(ServerResult::ListPromptsResult(ListPromptsResult {
+            ClientRequest::ListPromptsRequest(request) => {
+                let mut prompts = self.prompt_router.list_for(&context);
+                if let Some(registry) = &self.registry {
+                    prompts.extend(
+                        registry
+                            .list_prompts_for(&context)
+                            .into_iter()
+                            .filter(|prompt| !self.prompt_router.has_route(&prompt.name)),
+                    );
+                }
+                let page = self.prompt_router.paginator.paginate(
                     prompts,
+                    |prompt| &prompt.name,
+                    request.params.as_ref(),
+                )?;
+                Ok(ServerResult::ListPromptsResult(ListPromptsResult {
+                    prompts: page.items,
+                    next_cursor: page.next_cursor,
+                    ..Default::default()
+                }))
+            }
+            ClientRequest::CompleteRequest(request)
+                if self
+                    .prompt_router
+                    .completion_router
+                    .has_route(&(&request.params).into()) =>
+            {
+                let completion_context =
+                    crate::handler::server::completion::CompletionRequestContext::new(
+                        self.service.as_ref(),
+                        request.params,
+                        context,
+                    );
+                let result = self.prompt_router.complete(completion_context).await?;
+                Ok(ServerResult::CompleteResult(result))
+            }
+            ClientRequest::ReadResourceRequest(request)
+                if self.resource_router.has_route(&request.params.uri) =>
+            {
+                let resource_context = crate::handler::server::resource::ResourceContext::new(
+                    self.service.as_ref(),
+                    request.params.uri,
+                    context,
+                );
+                let result = self.resource_router.read_resource(resource_context).await?;
+                Ok(ServerResult::ReadResourceResult(result))
+            }
+            ClientRequest::ListResourcesRequest(request) if !self.resource_router.is_empty() => {
+                let page = self
+                    .resource_router
+                    .list_resources_page(request.params.as_ref())?;
+                Ok(ServerResult::ListResourcesResult(ListResourcesResult {
+                    resources: page.items,
+                    next_cursor: page.next_cursor,
                     ..Default::default()
                 }))
             }
+            ClientRequest::ListResourceTemplatesRequest(request)
+                if !self.resource_router.is_empty() =>
+            {
+                let page = self
+                    .resource_router
+                    .list_resource_templates_page(request.params.as_ref())?;
+                Ok(ServerResult::ListResourceTemplatesResult(
+                    ListResourceTemplatesResult {
+                        resource_templates: page.items,
+                        next_cursor: page.next_cursor,
+                        ..Default::default()
+                    },
+                ))
+            }
+            ClientRequest::SubscribeRequest(request) if self.subscriptions.is_some() => {
+                if let Some(subscriptions) = &self.subscriptions {
+                    subscriptions.on_subscribe(request.params, &context.peer);
+                }
+                Ok(ServerResult::empty(()))
+            }
+            ClientRequest::UnsubscribeRequest(request) if self.subscriptions.is_some() => {
+                if let Some(subscriptions) = &self.subscriptions {
+                    subscriptions.on_unsubscribe(request.params, &context.peer);
+                }
+                Ok(ServerResult::empty(()))
+            }
+            ClientRequest::InitializeRequest(request) => {
+                let result = self
+                    .service
+                    .handle_request(ClientRequest::InitializeRequest(request), context)
+                    .await?;
+                match result {
+                    ServerResult::InitializeResult(mut info) => {
+                        info.capabilities = self.enable_capabilities(info.capabilities);
+                        Ok(ServerResult::InitializeResult(info))
+                    }
+                    result => Ok(result),
+                }
+            }
             rest => self.service.handle_request(rest, context).await,
         }
     }
 
     fn get_info(&self) -> <RoleServer as crate::service::ServiceRole>::Info {
-        ServerHandler::get_info(&self.service)
+        let mut info = ServerHandler::get_info(&self.service);
+        info.capabilities = self.enable_capabilities(info.capabilities);
+        info
     }
 }
@@ -0,0 +1,294 @@
+use std::{collections::HashMap, sync::Arc};
+
+use futures::future::BoxFuture;
+
+use crate::{
+    handler::server::completion::{
+        CompleteHandler, CompletionRequestContext, DynCompleteHandler, complete_from_values,
+        truncate_completion,
+    },
+    model::{CompleteRequestParams, CompleteResult, CompletionInfo, Reference},
+};
+
+/// What a completion is offered for: a prompt, or a resource template.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum CompletionTarget {
+    /// The name of a prompt
+    Prompt(String),
+    /// The URI template of a resource template
+    Resource(String),
+}
+
+impl From<&Reference> for CompletionTarget {
+    fn from(reference: &Reference) -> Self {
+        match reference {
+            Reference::Prompt(prompt) => CompletionTarget::Prompt(prompt.name.clone()),
+            Reference::Resource(resource) => CompletionTarget::Resource(resource.uri.clone()),
+        }
+    }
+}
+
+/// The argument of a prompt, or the variable of a resource template, a route completes.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CompletionAttr {
+    pub target: CompletionTarget,
+    pub argument: String,
+}
+
+impl CompletionAttr {
+    pub fn prompt(name: impl Into<String>, argument: impl Into<String>) -> Self {
+        Self {
+            target: CompletionTarget::Prompt(name.into()),
+            argument: argument.into(),
+        }
+    }
+
+    pub fn resource(uri_template: impl Into<String>, argument: impl Into<String>) -> Self {
+        Self {
+            target: CompletionTarget::Resource(uri_template.into()),
+            argument: argument.into(),
+        }
+    }
+}
+
+impl From<&CompleteRequestParams> for CompletionAttr {
+    fn from(request: &CompleteRequestParams) -> Self {
+        Self {
+            target: (&request.r#ref).into(),
+            argument: request.argument.name.clone(),
+        }
+    }
+}
+
+pub struct CompletionRoute<S> {
+    #[allow(clippy::type_complexity)]
+    pub complete: Arc<DynCompleteHandler<S>>,
+    pub attr: CompletionAttr,
+}
+
+impl<S> std::fmt::Debug for CompletionRoute<S> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CompletionRoute")
+            .field("attr", &self.attr)
+            .finish()
+    }
+}
+
+impl<S> Clone for CompletionRoute<S> {
+    fn clone(&self) -> Self {
+        Self {
+            complete: self.complete.clone(),
+            attr: self.attr.clone(),
+        }
+    }
+}
+
+impl<S: Send + Sync + 'static> CompletionRoute<S> {
+    pub fn new<H, A: 'static>(attr: CompletionAttr, handler: H) -> Self
+    where
+        H: CompleteHandler<S, A> + Send + Sync + Clone + 'static,
+    {
+        Self {
+            complete: Arc::new(move |context: CompletionRequestContext<S>| {
+                let handler = handler.clone();
+                handler.handle(context)
+            }),
+            attr,
+        }
+    }
+
+    pub fn new_dyn<H>(attr: CompletionAttr, handler: H) -> Self
+    where
+        H: for<'a> Fn(
+                CompletionRequestContext<'a, S>,
+            ) -> BoxFuture<'a, Result<CompletionInfo, crate::ErrorData>>
+            + Send
+            + Sync
+            + 'static,
+    {
+        Self {
+            complete: Arc::new(handler),
+            attr,
+        }
+    }
+}
+
+pub trait IntoCompletionRoute<S, A> {
+    fn into_completion_route(self) -> CompletionRoute<S>;
+}
+
+impl<S, H, A> IntoCompletionRoute<S, A> for (CompletionAttr, H)
+where
+    S: Send + Sync + 'static,
+    A: 'static,
+    H: CompleteHandler<S, A> + Send + Sync + Clone + 'static,
+{
+    fn into_completion_route(self) -> CompletionRoute<S> {
+        CompletionRoute::new(self.0, self.1)
+    }
+}
+
+impl<S> IntoCompletionRoute<S, ()> for CompletionRoute<S>
+where
+    S: Send + Sync + 'static,
+{
+    fn into_completion_route(self) -> CompletionRoute<S> {
+        self
+    }
+}
+
+/// Routes `completion/complete` by the prompt or resource template, and the argument.
+///
+/// Besides handlers, an argument can have a fixed list of values, completed by the value
+/// typed so far. Handlers take precedence over fixed values, and completions are truncated
+/// to [`CompletionInfo::MAX_VALUES`]. Unknown arguments complete to nothing.
+#[derive(Debug)]
+pub struct CompletionRouter<S> {
+    routes: HashMap<CompletionAttr, CompletionRoute<S>>,
+    values: HashMap<CompletionAttr, Vec<String>>,
+}
+
+impl<S> Default for CompletionRouter<S> {
+    fn default() -> Self {
+        Self {
+            routes: HashMap::new(),
+            values: HashMap::new(),
+        }
+    }
+}
+
+impl<S> Clone for CompletionRouter<S> {
+    fn clone(&self) -> Self {
+        Self {
+            routes: self.routes.clone(),
+            values: self.values.clone(),
+        }
+    }
+}
+
+impl<S> CompletionRouter<S>
+where
+    S: Send + Sync + 'static,
+{
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_route<R, A: 'static>(mut self, route: R) -> Self
+    where
+        R: IntoCompletionRoute<S, A>,
+    {
+        self.add_route(route.into_completion_route());
+        self
+    }
+
+    pub fn add_route(&mut self, item: CompletionRoute<S>) {
+        self.routes.insert(item.attr.clone(), item);
+    }
+
+    /// Complete the argument from a fixed list of values.
+    pub fn with_values(
+        mut self,
+        attr: CompletionAttr,
+        values: impl IntoIterator<Item = impl Into<String>>,
+    ) -> Self {
+        self.add_values(attr, values);
+        self
+    }
+
+    pub fn add_values(
+        &mut self,
+        attr: CompletionAttr,
+        values: impl IntoIterator<Item = impl Into<String>>,
+    ) {
+        self.values
+            .insert(attr, values.into_iter().map(Into::into).collect());
+    }
+
+    pub fn merge(&mut self, other: CompletionRouter<S>) {
+        self.routes.extend(other.routes);
+        self.values.extend(other.values);
+    }
+
+    pub fn remove_route(&mut self, attr: &CompletionAttr) {
+        self.routes.remove(attr);
+        self.values.remove(attr);
+    }
+
+    /// Prefix the names of the prompts completed, as when their router is nested.
+    pub fn with_prompt_prefix(self, prefix: &str) -> Self {
+        let prefixed = |mut attr: CompletionAttr| {
+            if let CompletionTarget::Prompt(name) = &mut attr.target {
+                name.insert_str(0, prefix);
+            }
+            attr
+        };
+        Self {
+            routes: self
+                .routes
+                .into_values()
+                .map(|mut route| {
+                    route.attr = prefixed(route.attr);
+                    (route.attr.clone(), route)
+                })
+                .collect(),
+            values: self
+                .values
+                .into_iter()
+                .map(|(attr, values)| (prefixed(attr), values))
+                .collect(),
+        }
+    }
+
+    /// Remove every completion of a prompt or resource template.
+    pub fn remove_target(&mut self, target: &CompletionTarget) {
+        self.routes.retain(|attr, _| &attr.target != target);
+        self.values.retain(|attr, _| &attr.target != target);
+    }
+
+    pub fn has_route(&self, attr: &CompletionAttr) -> bool {
+        self.routes.contains_key(attr) || self.values.contains_key(attr)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.routes.is_empty() && self.values.is_empty()
+    }
+
+    pub async fn complete(
+        &self,
+        context: CompletionRequestContext<'_, S>,
+    ) -> Result<CompleteResult, crate::ErrorData> {
+        let attr = CompletionAttr::from(&context.request);
+        let completion = if let Some(route) = self.routes.get(&attr) {
+            (route.complete)(context).await?
+        } else if let Some(values) = self.values.get(&attr) {
+            complete_from_values(values, &context.request.argument.value)
+        } else {
+            CompletionInfo::default()
+        };
+        Ok(CompleteResult {
+            completion: truncate_completion(completion),
+        })
+    }
+}
+
+impl<S> std::ops::Add<CompletionRouter<S>> for CompletionRouter<S>
+where
+    S: Send + Sync + 'static,
+{
+    type Output = Self;
+
+    fn add(mut self, other: CompletionRouter<S>) -> Self::Output {
+        self.merge(other);
+        self
+    }
+}
+
+impl<S> std::ops::AddAssign<CompletionRouter<S>> for CompletionRouter<S>
+where
+    S: Send + Sync + 'static,
+{
+    fn add_assign(&mut self, other: CompletionRouter<S>) {
+        self.merge(other);
+    }
+}
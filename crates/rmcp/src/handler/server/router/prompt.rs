@@ -2,9 +2,19 @@ use std::{borrow::Cow, sync::Arc};
 
 use futures::future::BoxFuture;
 
+use super::{
+    ComposeError,
+    completion::{CompletionAttr, CompletionRouter, CompletionTarget, IntoCompletionRoute},
+};
 use crate::{
-    handler::server::prompt::{DynGetPromptHandler, GetPromptHandler, PromptContext},
-    model::{GetPromptResult, Prompt},
+    handler::server::{
+        completion::CompletionRequestContext,
+        pagination::{Page, Paginator},
+        prompt::{DynGetPromptHandler, GetPromptHandler, PromptContext},
+        visibility::Visibility,
+    },
+    model::{CompleteResult, GetPromptResult, PaginatedRequestParams, Prompt},
+    service::{RequestContext, RoleServer},
 };
 
 pub struct PromptRoute<S> {
@@ -108,12 +118,21 @@ where
 pub struct PromptRouter<S> {
     #[allow(clippy::type_complexity)]
     pub map: std::collections::HashMap<Cow<'static, str>, PromptRoute<S>>,
+    /// Completions of the prompt arguments, and of any resource template variables
+    pub completion_router: CompletionRouter<S>,
+    /// Splits `prompts/list` results into pages, a single one unless set by `with_page_size`
+    pub paginator: Paginator,
+    /// Which prompts each session sees
+    pub visibility: Visibility<Prompt>,
 }
 
 impl<S> Default for PromptRouter<S> {
     fn default() -> Self {
         Self {
             map: std::collections::HashMap::new(),
+            completion_router: CompletionRouter::default(),
+            paginator: Paginator::default(),
+            visibility: Visibility::default(),
         }
     }
 }
@@ -122,6 +141,9 @@ impl<S> Clone for PromptRouter<S> {
     fn clone(&self) -> Self {
         Self {
             map: self.map.clone(),
+            completion_router: self.completion_router.clone(),
+            paginator: self.paginator,
+            visibility: self.visibility.clone(),
         }
     }
 }
@@ -140,9 +162,23 @@ where
     S: Send + Sync + 'static,
 {
     pub fn new() -> Self {
-        Self {
-            map: std::collections::HashMap::new(),
-        }
+        Self::default()
+    }
+
+    /// List at most `page_size` prompts per page.
+    pub fn with_page_size(mut self, page_size: usize) -> Self {
+        self.paginator = Paginator::new(page_size);
+        self
+    }
+
+    /// Hide the prompts for which `filter` returns false from the session of the request,
+    /// see [`visibility`](crate::handler::server::visibility).
+    pub fn with_filter(
+        mut self,
+        filter: impl Fn(&Prompt, &RequestContext<RoleServer>) -> bool + Send + Sync + 'static,
+    ) -> Self {
+        self.visibility.add_filter(filter);
+        self
     }
 
     pub fn with_route<R, A: 'static>(mut self, route: R) -> Self
@@ -157,14 +193,86 @@ where
         self.map.insert(item.attr.name.clone().into(), item);
     }
 
+    /// Complete an argument of a prompt with a handler.
+    ///
+    /// ```rust,ignore
+    /// router.with_completion((CompletionAttr::prompt("sql_query", "table"), Self::tables))
+    /// ```
+    pub fn with_completion<R, A: 'static>(mut self, route: R) -> Self
+    where
+        R: IntoCompletionRoute<S, A>,
+    {
+        self.completion_router
+            .add_route(route.into_completion_route());
+        self
+    }
+
+    /// Complete the arguments of a prompt from fixed lists of values, as generated from the
+    /// enum-typed fields of its parameters.
+    pub fn with_argument_values(
+        mut self,
+        prompt: impl Into<String>,
+        arguments: impl IntoIterator<Item = (String, Vec<String>)>,
+    ) -> Self {
+        let prompt = prompt.into();
+        for (argument, values) in arguments {
+            self.completion_router
+                .add_values(CompletionAttr::prompt(prompt.clone(), argument), values);
+        }
+        self
+    }
+
+    /// Add the prompts of `other`, replacing those with the same name, which
+    /// [`try_merge`](Self::try_merge) reports instead.
     pub fn merge(&mut self, other: PromptRouter<S>) {
         for item in other.map.into_values() {
             self.add_route(item);
         }
+        self.completion_router.merge(other.completion_router);
+    }
+
+    /// Like [`merge`](Self::merge), but fails without adding anything when a prompt of
+    /// `other` has the name of a prompt of this router.
+    pub fn try_merge(&mut self, other: PromptRouter<S>) -> Result<(), ComposeError> {
+        if let Some(name) = other.map.keys().find(|name| self.map.contains_key(*name)) {
+            return Err(ComposeError::Duplicate(name.to_string()));
+        }
+        self.merge(other);
+        Ok(())
+    }
+
+    /// Add the prompts of `other` with their names prefixed, along with the completions of
+    /// their arguments.
+    ///
+    /// Fails without adding anything when a prefixed name is already routed, or when `other`
+    /// has visibility filters, which would not apply to its prompts once nested.
+    pub fn nest(&mut self, prefix: &str, other: PromptRouter<S>) -> Result<(), ComposeError> {
+        if other.visibility.has_filters() {
+            return Err(ComposeError::NestedSetting("visibility filters"));
+        }
+        let prompts = other
+            .map
+            .into_values()
+            .map(|mut route| {
+                route.attr.name = format!("{prefix}{}", route.attr.name);
+                route
+            })
+            .collect::<Vec<_>>();
+        if let Some(route) = prompts.iter().find(|route| self.has_route(route.name())) {
+            return Err(ComposeError::Duplicate(route.name().to_owned()));
+        }
+        for route in prompts {
+            self.add_route(route);
+        }
+        self.completion_router
+            .merge(other.completion_router.with_prompt_prefix(prefix));
+        Ok(())
     }
 
     pub fn remove_route(&mut self, name: &str) {
         self.map.remove(name);
+        self.completion_router
+            .remove_target(&CompletionTarget::Prompt(name.to_owned()));
     }
 
     pub fn has_route(&self, name: &str) -> bool {
@@ -175,22 +283,91 @@ where
         &self,
         context: PromptContext<'_, S>,
     ) -> Result<GetPromptResult, crate::ErrorData> {
-        let item = self.map.get(context.name.as_str()).ok_or_else(|| {
+        let item = self
+            .map
+            .get(context.name.as_str())
+            .filter(|item| self.visibility.is_visible(&item.attr, &context.context));
+        let item = item.ok_or_else(|| {
             crate::ErrorData::invalid_params(
                 format!("prompt '{}' not found", context.name),
                 Some(serde_json::json!({
-                    "available_prompts": self.list_all().iter().map(|p| &p.name).collect::<Vec<_>>()
+                    "available_prompts": self.visible(&context.context).map(|p| &p.attr.name).collect::<Vec<_>>()
                 })),
             )
         })?;
         (item.get)(context).await
     }
 
+    pub async fn complete(
+        &self,
+        context: CompletionRequestContext<'_, S>,
+    ) -> Result<CompleteResult, crate::ErrorData> {
+        self.completion_router.complete(context).await
+    }
+
     pub fn list_all(&self) -> Vec<crate::model::Prompt> {
         self.map.values().map(|item| item.attr.clone()).collect()
     }
+
+    /// The page of prompts requested, ordered by name.
+    pub fn list_page(
+        &self,
+        request: Option<&PaginatedRequestParams>,
+    ) -> Result<Page<crate::model::Prompt>, crate::ErrorData> {
+        self.paginator
+            .paginate(self.list_all(), |prompt| &prompt.name, request)
+    }
+
+    fn visible<'a>(
+        &'a self,
+        context: &'a RequestContext<RoleServer>,
+    ) -> impl Iterator<Item = &'a PromptRoute<S>> {
+        self.map
+            .values()
+            .filter(|item| self.visibility.is_visible(&item.attr, context))
+    }
+
+    /// The prompts no filter hides from the session of `context`.
+    ///
+    /// They are remembered for [`refresh_visibility`](Self::refresh_visibility).
+    pub fn list_for(&self, context: &RequestContext<RoleServer>) -> Vec<crate::model::Prompt> {
+        let prompts = self
+            .visible(context)
+            .map(|item| item.attr.clone())
+            .collect::<Vec<_>>();
+        self.visibility
+            .record(context, prompts.iter().map(|prompt| prompt.name.as_str()));
+        prompts
+    }
+
+    /// The page of prompts requested, among the prompts the session of `context` sees.
+    pub fn list_page_for(
+        &self,
+        request: Option<&PaginatedRequestParams>,
+        context: &RequestContext<RoleServer>,
+    ) -> Result<Page<crate::model::Prompt>, crate::ErrorData> {
+        self.paginator
+            .paginate(self.list_for(context), |prompt| &prompt.name, request)
+    }
+
+    /// Send `notifications/prompts/list_changed` to the session of `context` if the prompts
+    /// it sees changed since they were listed, returning whether they did. Only the changes
+    /// made by filters are tracked.
+    pub async fn refresh_visibility(&self, context: &RequestContext<RoleServer>) -> bool {
+        let changed = self
+            .visibility
+            .record(context, self.visible(context).map(PromptRoute::name));
+        if changed {
+            if let Err(error) = context.peer.notify_prompt_list_changed().await {
+                tracing::warn!(%error, "failed to notify prompt list change");
+            }
+        }
+        changed
+    }
 }
 
+/// [Merges](Self::merge) the prompts, those of the right-hand side replacing the ones with the same
+/// name; use [`try_merge`](Self::try_merge) to detect collisions.
 impl<S> std::ops::Add<PromptRouter<S>> for PromptRouter<S>
 where
     S: Send + Sync + 'static,
@@ -203,6 +380,7 @@ where
     }
 }
 
+/// [Merges](Self::merge) the prompts, like [`Add`](std::ops::Add).
 impl<S> std::ops::AddAssign<PromptRouter<S>> for PromptRouter<S>
 where
     S: Send + Sync + 'static,
@@ -0,0 +1,359 @@
+use std::{collections::BTreeMap, sync::Arc};
+
+use futures::future::BoxFuture;
+
+use crate::{
+    handler::server::{
+        pagination::{Page, Paginator},
+        resource::{DynReadResourceHandler, ReadResourceHandler, ResourceContext},
+    },
+    model::{
+        AnnotateAble, PaginatedRequestParams, RawResource, RawResourceTemplate, ReadResourceResult,
+        Resource, ResourceTemplate, UriTemplate,
+    },
+};
+
+/// What a [`ResourceRoute`] serves: a single resource, or every resource matching a template.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ResourceAttr {
+    Resource(Resource),
+    Template(ResourceTemplate),
+}
+
+impl ResourceAttr {
+    /// The URI of the resource, or the URI template.
+    pub fn uri(&self) -> &str {
+        match self {
+            ResourceAttr::Resource(resource) => &resource.uri,
+            ResourceAttr::Template(template) => &template.uri_template,
+        }
+    }
+}
+
+impl From<Resource> for ResourceAttr {
+    fn from(resource: Resource) -> Self {
+        ResourceAttr::Resource(resource)
+    }
+}
+
+impl From<RawResource> for ResourceAttr {
+    fn from(resource: RawResource) -> Self {
+        ResourceAttr::Resource(resource.no_annotation())
+    }
+}
+
+impl From<ResourceTemplate> for ResourceAttr {
+    fn from(template: ResourceTemplate) -> Self {
+        ResourceAttr::Template(template)
+    }
+}
+
+impl From<RawResourceTemplate> for ResourceAttr {
+    fn from(template: RawResourceTemplate) -> Self {
+        ResourceAttr::Template(template.no_annotation())
+    }
+}
+
+pub struct ResourceRoute<S> {
+    #[allow(clippy::type_complexity)]
+    pub read: Arc<DynReadResourceHandler<S>>,
+    pub attr: ResourceAttr,
+}
+
+impl<S> std::fmt::Debug for ResourceRoute<S> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ResourceRoute")
+            .field("attr", &self.attr)
+            .finish()
+    }
+}
+
+impl<S> Clone for ResourceRoute<S> {
+    fn clone(&self) -> Self {
+        Self {
+            read: self.read.clone(),
+            attr: self.attr.clone(),
+        }
+    }
+}
+
+impl<S: Send + Sync + 'static> ResourceRoute<S> {
+    pub fn new<H, A: 'static>(attr: impl Into<ResourceAttr>, handler: H) -> Self
+    where
+        H: ReadResourceHandler<S, A> + Send + Sync + Clone + 'static,
+    {
+        Self {
+            read: Arc::new(move |context: ResourceContext<S>| {
+                let handler = handler.clone();
+                handler.handle(context)
+            }),
+            attr: attr.into(),
+        }
+    }
+
+    pub fn new_dyn<H>(attr: impl Into<ResourceAttr>, handler: H) -> Self
+    where
+        H: for<'a> Fn(
+                ResourceContext<'a, S>,
+            ) -> BoxFuture<'a, Result<ReadResourceResult, crate::ErrorData>>
+            + Send
+            + Sync
+            + 'static,
+    {
+        Self {
+            read: Arc::new(handler),
+            attr: attr.into(),
+        }
+    }
+
+    /// The URI of the resource, or the URI template.
+    pub fn uri(&self) -> &str {
+        self.attr.uri()
+    }
+}
+
+pub trait IntoResourceRoute<S, A> {
+    fn into_resource_route(self) -> ResourceRoute<S>;
+}
+
+impl<S, H, A, R> IntoResourceRoute<S, A> for (R, H)
+where
+    S: Send + Sync + 'static,
+    A: 'static,
+    H: ReadResourceHandler<S, A> + Send + Sync + Clone + 'static,
+    R: Into<ResourceAttr>,
+{
+    fn into_resource_route(self) -> ResourceRoute<S> {
+        ResourceRoute::new(self.0.into(), self.1)
+    }
+}
+
+impl<S> IntoResourceRoute<S, ()> for ResourceRoute<S>
+where
+    S: Send + Sync + 'static,
+{
+    fn into_resource_route(self) -> ResourceRoute<S> {
+        self
+    }
+}
+
+/// Adapter for functions generated by the #\[resource\] macro
+pub struct ResourceAttrGenerateFunctionAdapter;
+
+impl<S, F> IntoResourceRoute<S, ResourceAttrGenerateFunctionAdapter> for F
+where
+    S: Send + Sync + 'static,
+    F: Fn() -> ResourceRoute<S>,
+{
+    fn into_resource_route(self) -> ResourceRoute<S> {
+        (self)()
+    }
+}
+
+/// Routes `resources/read` to the route serving the URI.
+///
+/// A route of a single resource takes precedence over templates, and templates are tried in
+/// the order they were added.
+#[derive(Debug)]
+pub struct ResourceRouter<S> {
+    resources: BTreeMap<String, ResourceRoute<S>>,
+    /// `None` for a template which is not a valid URI template, which matches nothing
+    templates: Vec<(Option<UriTemplate>, ResourceRoute<S>)>,
+    paginator: Paginator,
+}
+
+impl<S> Default for ResourceRouter<S> {
+    fn default() -> Self {
+        Self {
+            resources: BTreeMap::new(),
+            templates: Vec::new(),
+            paginator: Paginator::default(),
+        }
+    }
+}
+
+impl<S> Clone for ResourceRouter<S> {
+    fn clone(&self) -> Self {
+        Self {
+            resources: self.resources.clone(),
+            templates: self.templates.clone(),
+            paginator: self.paginator,
+        }
+    }
+}
+
+impl<S> IntoIterator for ResourceRouter<S> {
+    type Item = ResourceRoute<S>;
+    type IntoIter = std::vec::IntoIter<ResourceRoute<S>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.resources
+            .into_values()
+            .chain(self.templates.into_iter().map(|(_, route)| route))
+            .collect::<Vec<_>>()
+            .into_iter()
+    }
+}
+
+impl<S> ResourceRouter<S>
+where
+    S: Send + Sync + 'static,
+{
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// List at most `page_size` resources or resource templates per page.
+    pub fn with_page_size(mut self, page_size: usize) -> Self {
+        self.paginator = Paginator::new(page_size);
+        self
+    }
+
+    /// Splits `resources/list` and `resources/templates/list` results into pages.
+    pub fn paginator(&self) -> Paginator {
+        self.paginator
+    }
+
+    pub fn with_route<R, A: 'static>(mut self, route: R) -> Self
+    where
+        R: IntoResourceRoute<S, A>,
+    {
+        self.add_route(route.into_resource_route());
+        self
+    }
+
+    /// Add a route, replacing the route of the same URI or URI template.
+    pub fn add_route(&mut self, item: ResourceRoute<S>) {
+        match &item.attr {
+            ResourceAttr::Resource(resource) => {
+                self.resources.insert(resource.uri.clone(), item);
+            }
+            ResourceAttr::Template(template) => {
+                let matcher = UriTemplate::parse(&template.uri_template)
+                    .inspect_err(|error| {
+                        tracing::warn!(
+                            uri_template = %template.uri_template,
+                            %error,
+                            "invalid resource template, it will never be read"
+                        )
+                    })
+                    .ok();
+                match self
+                    .templates
+                    .iter_mut()
+                    .find(|(_, route)| route.uri() == template.uri_template)
+                {
+                    Some(existing) => *existing = (matcher, item),
+                    None => self.templates.push((matcher, item)),
+                }
+            }
+        }
+    }
+
+    pub fn merge(&mut self, other: ResourceRouter<S>) {
+        for item in other {
+            self.add_route(item);
+        }
+    }
+
+    /// Remove the route of a URI or a URI template.
+    pub fn remove_route(&mut self, uri: &str) {
+        self.resources.remove(uri);
+        self.templates.retain(|(_, route)| route.uri() != uri);
+    }
+
+    /// Whether a route serves `uri`.
+    pub fn has_route(&self, uri: &str) -> bool {
+        self.resources.contains_key(uri)
+            || self
+                .templates
+                .iter()
+                .any(|(matcher, _)| matcher.as_ref().is_some_and(|m| m.match_uri(uri).is_some()))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.resources.is_empty() && self.templates.is_empty()
+    }
+
+    pub async fn read_resource(
+        &self,
+        mut context: ResourceContext<'_, S>,
+    ) -> Result<ReadResourceResult, crate::ErrorData> {
+        if let Some(item) = self.resources.get(context.uri.as_str()) {
+            return (item.read)(context).await;
+        }
+        for (matcher, item) in &self.templates {
+            if let Some(variables) = matcher.as_ref().and_then(|m| m.match_uri(&context.uri)) {
+                context.variables = variables;
+                return (item.read)(context).await;
+            }
+        }
+        Err(crate::ErrorData::resource_not_found(
+            format!("resource '{}' not found", context.uri),
+            Some(serde_json::json!({ "uri": context.uri })),
+        ))
+    }
+
+    /// The resources of the routes serving a single resource.
+    pub fn list_resources(&self) -> Vec<Resource> {
+        self.resources
+            .values()
+            .filter_map(|item| match &item.attr {
+                ResourceAttr::Resource(resource) => Some(resource.clone()),
+                ResourceAttr::Template(_) => None,
+            })
+            .collect()
+    }
+
+    pub fn list_resource_templates(&self) -> Vec<ResourceTemplate> {
+        self.templates
+            .iter()
+            .filter_map(|(_, item)| match &item.attr {
+                ResourceAttr::Template(template) => Some(template.clone()),
+                ResourceAttr::Resource(_) => None,
+            })
+            .collect()
+    }
+
+    /// The page of resources requested, ordered by URI.
+    pub fn list_resources_page(
+        &self,
+        request: Option<&PaginatedRequestParams>,
+    ) -> Result<Page<Resource>, crate::ErrorData> {
+        self.paginator
+            .paginate(self.list_resources(), |resource| &resource.uri, request)
+    }
+
+    /// The page of resource templates requested, ordered by URI template.
+    pub fn list_resource_templates_page(
+        &self,
+        request: Option<&PaginatedRequestParams>,
+    ) -> Result<Page<ResourceTemplate>, crate::ErrorData> {
+        self.paginator.paginate(
+            self.list_resource_templates(),
+            |template| &template.uri_template,
+            request,
+        )
+    }
+}
+
+impl<S> std::ops::Add<ResourceRouter<S>> for ResourceRouter<S>
+where
+    S: Send + Sync + 'static,
+{
+    type Output = Self;
+
+    fn add(mut self, other: ResourceRouter<S>) -> Self::Output {
+        self.merge(other);
+        self
+    }
+}
+
+impl<S> std::ops::AddAssign<ResourceRouter<S>> for ResourceRouter<S>
+where
+    S: Send + Sync + 'static,
+{
+    fn add_assign(&mut self, other: ResourceRouter<S>) {
+        self.merge(other);
+    }
+}
@@ -1,20 +1,29 @@
-use std::{borrow::Cow, sync::Arc};
+use std::{borrow::Cow, sync::Arc, time::Duration};
 
 use futures::{FutureExt, future::BoxFuture};
 use schemars::JsonSchema;
 
+use super::ComposeError;
 use crate::{
     handler::server::{
+        authorization::{Identity, IdentityResolver, ToolPolicy},
+        pagination::{Page, Paginator},
         tool::{CallToolHandler, DynCallToolHandler, ToolCallContext, schema_for_type},
         tool_name_validation::validate_and_warn_tool_name,
+        visibility::Visibility,
     },
-    model::{CallToolResult, Tool, ToolAnnotations},
+    model::{CallToolResult, Content, Extensions, PaginatedRequestParams, Tool, ToolAnnotations},
+    service::{RequestContext, RoleServer},
 };
 
 pub struct ToolRoute<S> {
     #[allow(clippy::type_complexity)]
     pub call: Arc<DynCallToolHandler<S>>,
     pub attr: crate::model::Tool,
+    /// Who may call the tool, anyone if `None`
+    pub policy: Option<ToolPolicy>,
+    /// How long a call may run, the router's default if `None`
+    pub timeout: Option<Duration>,
 }
 
 impl<S> std::fmt::Debug for ToolRoute<S> {
@@ -23,6 +32,8 @@ impl<S> std::fmt::Debug for ToolRoute<S> {
             .field("name", &self.attr.name)
             .field("description", &self.attr.description)
             .field("input_schema", &self.attr.input_schema)
+            .field("policy", &self.policy)
+            .field("timeout", &self.timeout)
             .finish()
     }
 }
@@ -32,6 +43,8 @@ impl<S> Clone for ToolRoute<S> {
         Self {
             call: self.call.clone(),
             attr: self.attr.clone(),
+            policy: self.policy.clone(),
+            timeout: self.timeout,
         }
     }
 }
@@ -47,6 +60,8 @@ impl<S: Send + Sync + 'static> ToolRoute<S> {
                 context.invoke(call).boxed()
             }),
             attr: attr.into(),
+            policy: None,
+            timeout: None,
         }
     }
     pub fn new_dyn<C>(attr: impl Into<Tool>, call: C) -> Self
@@ -61,11 +76,29 @@ impl<S: Send + Sync + 'static> ToolRoute<S> {
         Self {
             call: Arc::new(call),
             attr: attr.into(),
+            policy: None,
+            timeout: None,
         }
     }
     pub fn name(&self) -> &str {
         &self.attr.name
     }
+    /// Only allow callers granted every one of `scopes`.
+    pub fn with_scopes(
+        self,
+        scopes: impl IntoIterator<Item = impl Into<Cow<'static, str>>>,
+    ) -> Self {
+        self.with_policy(ToolPolicy::scopes(scopes))
+    }
+    pub fn with_policy(mut self, policy: ToolPolicy) -> Self {
+        self.policy = Some(policy);
+        self
+    }
+    /// Stop calls running longer than `timeout`, see [`ToolRouter::with_timeout_response`].
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
 }
 
 pub trait IntoToolRoute<S, A> {
@@ -167,12 +200,45 @@ where
         self
     }
 }
-#[derive(Debug)]
+/// How a [`ToolRouter`] answers calls which timed out.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TimeoutResponse {
+    /// An internal error
+    #[default]
+    Error,
+    /// A result with `isError` set, which the model can see
+    ToolError,
+}
+
 pub struct ToolRouter<S> {
     #[allow(clippy::type_complexity)]
     pub map: std::collections::HashMap<Cow<'static, str>, ToolRoute<S>>,
 
     pub transparent_when_not_found: bool,
+
+    /// Splits `tools/list` results into pages, a single one unless set by `with_page_size`
+    pub paginator: Paginator,
+
+    /// Finds the caller of tools with a policy, [`Identity::from_extensions`] if `None`
+    pub identity_resolver: Option<IdentityResolver>,
+
+    /// How long a call of a tool without its own timeout may run
+    pub default_timeout: Option<Duration>,
+
+    pub timeout_response: TimeoutResponse,
+
+    /// Which tools each session sees
+    pub visibility: Visibility<Tool>,
+
+    /// Validates the arguments of each call against the input schema of the tool
+    #[cfg(feature = "schema-validation")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "schema-validation")))]
+    pub validator: Option<crate::handler::server::validation::ArgumentValidator>,
+
+    /// Validates the structured content of each result against the output schema of the tool
+    #[cfg(feature = "schema-validation")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "schema-validation")))]
+    pub output_validator: Option<crate::handler::server::validation::OutputValidator>,
 }
 
 impl<S> Default for ToolRouter<S> {
@@ -180,6 +246,15 @@ impl<S> Default for ToolRouter<S> {
         Self {
             map: std::collections::HashMap::new(),
             transparent_when_not_found: false,
+            paginator: Paginator::default(),
+            identity_resolver: None,
+            default_timeout: None,
+            timeout_response: TimeoutResponse::default(),
+            visibility: Visibility::default(),
+            #[cfg(feature = "schema-validation")]
+            validator: None,
+            #[cfg(feature = "schema-validation")]
+            output_validator: None,
         }
     }
 }
@@ -187,11 +262,46 @@ impl<S> Clone for ToolRouter<S> {
     fn clone(&self) -> Self {
         Self {
             map: self.map.clone(),
+            ..self.without_routes()
+        }
+    }
+}
+
+impl<S> ToolRouter<S> {
+    /// A router with the same settings and no routes.
+    pub(crate) fn without_routes(&self) -> Self {
+        Self {
+            map: std::collections::HashMap::new(),
             transparent_when_not_found: self.transparent_when_not_found,
+            paginator: self.paginator,
+            identity_resolver: self.identity_resolver.clone(),
+            default_timeout: self.default_timeout,
+            timeout_response: self.timeout_response,
+            visibility: self.visibility.clone(),
+            #[cfg(feature = "schema-validation")]
+            validator: self.validator.clone(),
+            #[cfg(feature = "schema-validation")]
+            output_validator: self.output_validator.clone(),
         }
     }
 }
 
+impl<S> std::fmt::Debug for ToolRouter<S> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ToolRouter")
+            .field("map", &self.map)
+            .field(
+                "transparent_when_not_found",
+                &self.transparent_when_not_found,
+            )
+            .field("paginator", &self.paginator)
+            .field("default_timeout", &self.default_timeout)
+            .field("timeout_response", &self.timeout_response)
+            .field("visibility", &self.visibility)
+            .finish_non_exhaustive()
+    }
+}
+
 impl<S> IntoIterator for ToolRouter<S> {
     type Item = ToolRoute<S>;
     type IntoIter = std::collections::hash_map::IntoValues<Cow<'static, str>, ToolRoute<S>>;
@@ -206,10 +316,67 @@ where
     S: Send + Sync + 'static,
 {
     pub fn new() -> Self {
-        Self {
-            map: std::collections::HashMap::new(),
-            transparent_when_not_found: false,
-        }
+        Self::default()
+    }
+
+    /// Find the caller of tools with a policy with `resolver`, e.g. from the claims of a
+    /// validated token.
+    pub fn with_identity_resolver(
+        mut self,
+        resolver: impl Fn(&Extensions) -> Option<Identity> + Send + Sync + 'static,
+    ) -> Self {
+        self.identity_resolver = Some(Arc::new(resolver));
+        self
+    }
+
+    /// Stop calls of tools without their own timeout running longer than `timeout`.
+    pub fn with_default_timeout(mut self, timeout: Duration) -> Self {
+        self.default_timeout = Some(timeout);
+        self
+    }
+
+    pub fn with_timeout_response(mut self, response: TimeoutResponse) -> Self {
+        self.timeout_response = response;
+        self
+    }
+
+    /// Hide the tools for which `filter` returns false from the session of the request, see
+    /// [`visibility`](crate::handler::server::visibility).
+    pub fn with_filter(
+        mut self,
+        filter: impl Fn(&Tool, &RequestContext<RoleServer>) -> bool + Send + Sync + 'static,
+    ) -> Self {
+        self.visibility.add_filter(filter);
+        self
+    }
+
+    /// List at most `page_size` tools per page.
+    pub fn with_page_size(mut self, page_size: usize) -> Self {
+        self.paginator = Paginator::new(page_size);
+        self
+    }
+
+    /// Reject calls whose arguments don't match the input schema of the tool, with an
+    /// invalid params error listing every failure.
+    #[cfg(feature = "schema-validation")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "schema-validation")))]
+    pub fn with_argument_validation(mut self) -> Self {
+        self.validator = Some(Default::default());
+        self
+    }
+
+    /// Turn results of tools declaring an output schema into internal errors when their
+    /// structured content is missing or doesn't match the schema.
+    #[cfg(feature = "schema-validation")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "schema-validation")))]
+    pub fn with_output_validation(
+        mut self,
+        mode: crate::handler::server::validation::OutputValidation,
+    ) -> Self {
+        self.output_validator = Some(crate::handler::server::validation::OutputValidator::new(
+            mode,
+        ));
+        self
     }
     pub fn with_route<R, A>(mut self, route: R) -> Self
     where
@@ -225,37 +392,251 @@ where
         self.map.insert(new_name.clone(), item);
     }
 
+    /// Add the routes of `other`, replacing those with the same name, which
+    /// [`try_merge`](Self::try_merge) reports instead.
     pub fn merge(&mut self, other: ToolRouter<S>) {
         for item in other.map.into_values() {
             self.add_route(item);
         }
     }
 
+    /// Like [`merge`](Self::merge), but fails without adding anything when a route of
+    /// `other` has the name of a route of this router.
+    pub fn try_merge(&mut self, other: ToolRouter<S>) -> Result<(), ComposeError> {
+        if let Some(name) = other.map.keys().find(|name| self.map.contains_key(*name)) {
+            return Err(ComposeError::Duplicate(name.to_string()));
+        }
+        self.merge(other);
+        Ok(())
+    }
+
+    /// Add the routes of `other` with their names prefixed, e.g. `git.status` for the
+    /// `status` tool nested under `git.`.
+    ///
+    /// The routes keep their policy and timeout, the default timeout of `other` applying to
+    /// those without one. Fails without adding anything when a prefixed name isn't a valid
+    /// tool name or is already routed, or when `other` has a setting its routes can't carry:
+    /// visibility filters, an identity resolver, a timeout response or validation this router
+    /// lacks.
+    pub fn nest(&mut self, prefix: &str, other: ToolRouter<S>) -> Result<(), ComposeError> {
+        self.check_nested_settings(&other)?;
+        let routes = other
+            .map
+            .into_values()
+            .map(|mut route| {
+                route.attr.name = format!("{prefix}{}", route.attr.name).into();
+                route.timeout = route.timeout.or(other.default_timeout);
+                route
+            })
+            .collect::<Vec<_>>();
+        for route in &routes {
+            if !validate_and_warn_tool_name(route.name()) {
+                return Err(ComposeError::InvalidToolName(route.name().to_owned()));
+            }
+            if self.has_route(route.name()) {
+                return Err(ComposeError::Duplicate(route.name().to_owned()));
+            }
+        }
+        for route in routes {
+            self.map.insert(route.attr.name.clone(), route);
+        }
+        Ok(())
+    }
+
+    fn check_nested_settings(&self, other: &ToolRouter<S>) -> Result<(), ComposeError> {
+        if other.visibility.has_filters() {
+            return Err(ComposeError::NestedSetting("visibility filters"));
+        }
+        if other.identity_resolver.is_some() {
+            return Err(ComposeError::NestedSetting("an identity resolver"));
+        }
+        if other.timeout_response != self.timeout_response
+            && other.timeout_response != TimeoutResponse::default()
+        {
+            return Err(ComposeError::NestedSetting("a timeout response"));
+        }
+        #[cfg(feature = "schema-validation")]
+        if other.validator.is_some() && self.validator.is_none() {
+            return Err(ComposeError::NestedSetting("argument validation"));
+        }
+        #[cfg(feature = "schema-validation")]
+        if other.output_validator.is_some() && self.output_validator.is_none() {
+            return Err(ComposeError::NestedSetting("output validation"));
+        }
+        Ok(())
+    }
+
     pub fn remove_route(&mut self, name: &str) {
         self.map.remove(name);
     }
     pub fn has_route(&self, name: &str) -> bool {
         self.map.contains_key(name)
     }
+    /// Call a tool.
+    ///
+    /// The call is dropped when it times out, or when the client cancels the request, which
+    /// is a [`REQUEST_CANCELLED`](crate::model::ErrorCode::REQUEST_CANCELLED) error.
     pub async fn call(
         &self,
         context: ToolCallContext<'_, S>,
     ) -> Result<CallToolResult, crate::ErrorData> {
+        // tools the caller may not call are as unknown as hidden ones
         let item = self
             .map
             .get(context.name())
+            .filter(|item| {
+                item.policy.as_ref().is_none_or(|policy| {
+                    let identity = self.identity(&context.request_context.extensions);
+                    policy.allows(identity.as_ref())
+                }) && self
+                    .visibility
+                    .is_visible(&item.attr, &context.request_context)
+            })
             .ok_or_else(|| crate::ErrorData::invalid_params("tool not found", None))?;
+        #[cfg(feature = "schema-validation")]
+        if let Some(validator) = &self.validator {
+            validator.validate(&item.attr, context.arguments.as_ref())?;
+        }
 
-        let result = (item.call)(context).await?;
+        let name = context.name.clone();
+        let ct = context.request_context.ct.clone();
+        let call = (item.call)(context);
+        let result = match item.timeout.or(self.default_timeout) {
+            Some(timeout) => tokio::select! {
+                result = tokio::time::timeout(timeout, call) => match result {
+                    Ok(result) => result?,
+                    Err(_) => return self.timed_out(&name, timeout),
+                },
+                _ = ct.cancelled() => return Err(Self::cancelled(&name)),
+            },
+            None => tokio::select! {
+                result = call => result?,
+                _ = ct.cancelled() => return Err(Self::cancelled(&name)),
+            },
+        };
+        #[cfg(feature = "schema-validation")]
+        if let Some(validator) = &self.output_validator {
+            validator.validate(&item.attr, &result)?;
+        }
 
         Ok(result)
     }
 
+    fn timed_out(&self, name: &str, timeout: Duration) -> Result<CallToolResult, crate::ErrorData> {
+        let message = format!("tool '{name}' timed out after {timeout:?}");
+        match self.timeout_response {
+            TimeoutResponse::Error => Err(crate::ErrorData::internal_error(
+                message,
+                Some(serde_json::json!({ "timeoutMs": timeout.as_millis() as u64 })),
+            )),
+            TimeoutResponse::ToolError => Ok(CallToolResult::error(vec![Content::text(message)])),
+        }
+    }
+
+    fn cancelled(name: &str) -> crate::ErrorData {
+        crate::ErrorData::request_cancelled(format!("call of tool '{name}' was cancelled"), None)
+    }
+
     pub fn list_all(&self) -> Vec<crate::model::Tool> {
         self.map.values().map(|item| item.attr.clone()).collect()
     }
+
+    /// The page of tools requested, ordered by name.
+    pub fn list_page(
+        &self,
+        request: Option<&PaginatedRequestParams>,
+    ) -> Result<Page<crate::model::Tool>, crate::ErrorData> {
+        self.paginator
+            .paginate(self.list_all(), |tool| &tool.name, request)
+    }
+
+    /// The identity of the caller of a request with `extensions`.
+    pub fn identity(&self, extensions: &Extensions) -> Option<Identity> {
+        match &self.identity_resolver {
+            Some(resolver) => resolver(extensions),
+            None => Identity::from_extensions(extensions),
+        }
+    }
+
+    /// The tools the caller of a request with `extensions` may call.
+    pub fn list_authorized(&self, extensions: &Extensions) -> Vec<crate::model::Tool> {
+        let identity = self.identity(extensions);
+        self.map
+            .values()
+            .filter(|item| {
+                item.policy
+                    .as_ref()
+                    .is_none_or(|policy| policy.allows(identity.as_ref()))
+            })
+            .map(|item| item.attr.clone())
+            .collect()
+    }
+
+    /// The page of tools requested, among the tools the caller may call.
+    pub fn list_authorized_page(
+        &self,
+        request: Option<&PaginatedRequestParams>,
+        extensions: &Extensions,
+    ) -> Result<Page<crate::model::Tool>, crate::ErrorData> {
+        self.paginator
+            .paginate(self.list_authorized(extensions), |tool| &tool.name, request)
+    }
+
+    fn visible<'a>(
+        &'a self,
+        context: &'a RequestContext<RoleServer>,
+    ) -> impl Iterator<Item = &'a ToolRoute<S>> {
+        let identity = self.identity(&context.extensions);
+        self.map.values().filter(move |item| {
+            item.policy
+                .as_ref()
+                .is_none_or(|policy| policy.allows(identity.as_ref()))
+                && self.visibility.is_visible(&item.attr, context)
+        })
+    }
+
+    /// The tools the session of `context` sees, which the caller may call and no filter
+    /// hides.
+    ///
+    /// They are remembered for [`refresh_visibility`](Self::refresh_visibility).
+    pub fn list_for(&self, context: &RequestContext<RoleServer>) -> Vec<crate::model::Tool> {
+        let tools = self
+            .visible(context)
+            .map(|item| item.attr.clone())
+            .collect::<Vec<_>>();
+        self.visibility
+            .record(context, tools.iter().map(|tool| tool.name.as_ref()));
+        tools
+    }
+
+    /// The page of tools requested, among the tools the session of `context` sees.
+    pub fn list_page_for(
+        &self,
+        request: Option<&PaginatedRequestParams>,
+        context: &RequestContext<RoleServer>,
+    ) -> Result<Page<crate::model::Tool>, crate::ErrorData> {
+        self.paginator
+            .paginate(self.list_for(context), |tool| &tool.name, request)
+    }
+
+    /// Send `notifications/tools/list_changed` to the session of `context` if the tools it
+    /// sees changed since they were listed, returning whether they did. Only the changes
+    /// made by filters are tracked.
+    pub async fn refresh_visibility(&self, context: &RequestContext<RoleServer>) -> bool {
+        let changed = self
+            .visibility
+            .record(context, self.visible(context).map(ToolRoute::name));
+        if changed {
+            if let Err(error) = context.peer.notify_tool_list_changed().await {
+                tracing::warn!(%error, "failed to notify tool list change");
+            }
+        }
+        changed
+    }
 }
 
+/// [Merges](Self::merge) the routes, those of the right-hand side replacing the ones with the same
+/// name; use [`try_merge`](Self::try_merge) to detect collisions.
 impl<S> std::ops::Add<ToolRouter<S>> for ToolRouter<S>
 where
     S: Send + Sync + 'static,
@@ -268,6 +649,7 @@ where
     }
 }
 
+/// [Merges](Self::merge) the routes, like [`Add`](std::ops::Add).
 impl<S> std::ops::AddAssign<ToolRouter<S>> for ToolRouter<S>
 where
     S: Send + Sync + 'static,
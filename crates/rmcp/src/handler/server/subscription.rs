@@ -0,0 +1,266 @@
+//! Track resource subscriptions of the connected peers and notify them of updates.
+use std::sync::{Arc, Mutex, Weak};
+
+use crate::{
+    RoleServer,
+    model::{
+        ResourceUpdatedNotificationParam, ResourcesCapability, ServerCapabilities,
+        SubscribeRequestParams, UnsubscribeRequestParams, UriTemplate,
+    },
+    service::Peer,
+};
+
+/// What a peer subscribed to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ResourceSubscription {
+    /// A single resource
+    Uri(String),
+    /// Every resource whose URI starts with the prefix, see
+    /// [`ResourceSubscriptions::subscribe_prefix`]
+    Prefix(String),
+    /// Every resource whose URI matches the template
+    Template(UriTemplate),
+}
+
+impl ResourceSubscription {
+    /// Interpret the URI of a `resources/subscribe` request.
+    ///
+    /// A URI template with variables subscribes to the URIs matching it. Anything else, such as
+    /// a URI ending with `*`, is a single resource, so a request never yields a prefix.
+    pub fn from_requested(uri: impl Into<String>) -> Self {
+        let uri = uri.into();
+        if uri.contains('{') {
+            if let Ok(template) = UriTemplate::parse(&uri) {
+                if !template.variable_names().is_empty() {
+                    return ResourceSubscription::Template(template);
+                }
+            }
+        }
+        ResourceSubscription::Uri(uri)
+    }
+
+    pub fn matches(&self, uri: &str) -> bool {
+        match self {
+            ResourceSubscription::Uri(subscribed) => subscribed == uri,
+            ResourceSubscription::Prefix(prefix) => uri.starts_with(prefix.as_str()),
+            ResourceSubscription::Template(template) => template.match_uri(uri).is_some(),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Session {
+    peer: Peer<RoleServer>,
+    subscriptions: Vec<ResourceSubscription>,
+}
+
+/// A registry of the resource subscriptions of every session of a server.
+///
+/// Subscriptions of a session are dropped when its service stops. Cloning shares the registry.
+///
+/// ```rust,ignore
+/// impl ServerHandler for MyServer {
+///     fn get_info(&self) -> ServerInfo {
+///         ServerInfo {
+///             capabilities: self.subscriptions.enable_capability(
+///                 ServerCapabilities::builder().enable_resources().build(),
+///             ),
+///             ..Default::default()
+///         }
+///     }
+///
+///     async fn subscribe(
+///         &self,
+///         request: SubscribeRequestParams,
+///         context: RequestContext<RoleServer>,
+///     ) -> Result<(), ErrorData> {
+///         self.subscriptions.on_subscribe(request, &context.peer);
+///         Ok(())
+///     }
+/// }
+///
+/// // later, when a resource changes
+/// subscriptions.notify_updated("file:///notes.md").await;
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ResourceSubscriptions {
+    sessions: Arc<Mutex<Vec<Session>>>,
+}
+
+impl ResourceSubscriptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set `resources.subscribe` in the capabilities.
+    pub fn enable_capability(&self, mut capabilities: ServerCapabilities) -> ServerCapabilities {
+        capabilities
+            .resources
+            .get_or_insert_with(ResourcesCapability::default)
+            .subscribe = Some(true);
+        capabilities
+    }
+
+    pub fn subscribe(&self, peer: &Peer<RoleServer>, subscription: ResourceSubscription) {
+        let mut sessions = self.sessions.lock().expect("subscriptions lock poisoned");
+        match sessions
+            .iter_mut()
+            .find(|session| session.peer.is_same_peer(peer))
+        {
+            Some(session) => {
+                if !session.subscriptions.contains(&subscription) {
+                    session.subscriptions.push(subscription);
+                }
+            }
+            None => {
+                sessions.push(Session {
+                    peer: peer.clone(),
+                    subscriptions: vec![subscription],
+                });
+                Self::remove_when_closed(Arc::downgrade(&self.sessions), peer.clone());
+            }
+        }
+    }
+
+    /// Subscribe `peer` to every resource whose URI starts with `prefix`.
+    ///
+    /// The protocol has no such request, so this is for servers deciding it themselves, e.g.
+    /// when a client subscribes to a directory.
+    pub fn subscribe_prefix(&self, peer: &Peer<RoleServer>, prefix: impl Into<String>) {
+        self.subscribe(peer, ResourceSubscription::Prefix(prefix.into()));
+    }
+
+    /// Remove a subscription, returning whether the peer had it.
+    pub fn unsubscribe(
+        &self,
+        peer: &Peer<RoleServer>,
+        subscription: &ResourceSubscription,
+    ) -> bool {
+        let mut sessions = self.sessions.lock().expect("subscriptions lock poisoned");
+        let Some(session) = sessions
+            .iter_mut()
+            .find(|session| session.peer.is_same_peer(peer))
+        else {
+            return false;
+        };
+        let count = session.subscriptions.len();
+        session
+            .subscriptions
+            .retain(|existing| existing != subscription);
+        session.subscriptions.len() != count
+    }
+
+    /// Record the subscription of a `resources/subscribe` request.
+    pub fn on_subscribe(&self, request: SubscribeRequestParams, peer: &Peer<RoleServer>) {
+        self.subscribe(peer, ResourceSubscription::from_requested(request.uri));
+    }
+
+    /// Remove the subscription of a `resources/unsubscribe` request.
+    pub fn on_unsubscribe(&self, request: UnsubscribeRequestParams, peer: &Peer<RoleServer>) {
+        self.unsubscribe(peer, &ResourceSubscription::from_requested(request.uri));
+    }
+
+    /// The peers subscribed to `uri`.
+    pub fn subscribers(&self, uri: &str) -> Vec<Peer<RoleServer>> {
+        self.sessions
+            .lock()
+            .expect("subscriptions lock poisoned")
+            .iter()
+            .filter(|session| {
+                !session.peer.is_transport_closed()
+                    && session
+                        .subscriptions
+                        .iter()
+                        .any(|subscription| subscription.matches(uri))
+            })
+            .map(|session| session.peer.clone())
+            .collect()
+    }
+
+    pub fn is_subscribed(&self, peer: &Peer<RoleServer>, uri: &str) -> bool {
+        self.subscribers(uri)
+            .iter()
+            .any(|subscriber| subscriber.is_same_peer(peer))
+    }
+
+    /// The number of sessions with subscriptions.
+    pub fn session_count(&self) -> usize {
+        self.sessions
+            .lock()
+            .expect("subscriptions lock poisoned")
+            .iter()
+            .filter(|session| !session.subscriptions.is_empty())
+            .count()
+    }
+
+    /// Send `notifications/resources/updated` to the peers subscribed to `uri`, returning how
+    /// many were notified.
+    pub async fn notify_updated(&self, uri: impl Into<String>) -> usize {
+        let uri = uri.into();
+        let subscribers = self.subscribers(&uri);
+        let results = futures::future::join_all(subscribers.iter().map(|peer| {
+            peer.notify_resource_updated(ResourceUpdatedNotificationParam { uri: uri.clone() })
+        }))
+        .await;
+        results
+            .into_iter()
+            .filter(|result| match result {
+                Ok(()) => true,
+                Err(error) => {
+                    tracing::warn!(%uri, %error, "failed to notify resource update");
+                    false
+                }
+            })
+            .count()
+    }
+
+    fn remove_when_closed(sessions: Weak<Mutex<Vec<Session>>>, peer: Peer<RoleServer>) {
+        tokio::spawn(async move {
+            peer.closed().await;
+            if let Some(sessions) = sessions.upgrade() {
+                sessions
+                    .lock()
+                    .expect("subscriptions lock poisoned")
+                    .retain(|session| !session.peer.is_same_peer(&peer));
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_subscription_matching() {
+        let subscription = ResourceSubscription::from_requested("file:///notes.md");
+        assert_eq!(
+            subscription,
+            ResourceSubscription::Uri("file:///notes.md".to_owned())
+        );
+        assert!(subscription.matches("file:///notes.md"));
+        assert!(!subscription.matches("file:///notes.md.bak"));
+
+        // a literal `*` is part of the URI
+        let subscription = ResourceSubscription::from_requested("file:///logs/*");
+        assert_eq!(
+            subscription,
+            ResourceSubscription::Uri("file:///logs/*".to_owned())
+        );
+        assert!(subscription.matches("file:///logs/*"));
+        assert!(!subscription.matches("file:///logs/today.log"));
+
+        let subscription = ResourceSubscription::Prefix("file:///logs/".to_owned());
+        assert!(subscription.matches("file:///logs/today.log"));
+        assert!(!subscription.matches("file:///notes.md"));
+
+        let subscription = ResourceSubscription::from_requested("users://{id}");
+        assert!(matches!(subscription, ResourceSubscription::Template(_)));
+        assert!(subscription.matches("users://42"));
+        assert!(!subscription.matches("users://42/posts"));
+
+        // not a valid template
+        let subscription = ResourceSubscription::from_requested("users://{id");
+        assert!(matches!(subscription, ResourceSubscription::Uri(_)));
+    }
+}
@@ -13,9 +13,9 @@ pub use super::{
     router::tool::{ToolRoute, ToolRouter},
 };
 use crate::{
-    RoleServer,
+    Peer, RoleServer,
     handler::server::wrapper::Parameters,
-    model::{CallToolRequestParams, CallToolResult, IntoContents, JsonObject},
+    model::{CallToolRequestParams, CallToolResult, IntoContents, JsonObject, ProgressToken},
     service::RequestContext,
 };
 
@@ -73,8 +73,40 @@ impl<S> AsRequestContext for ToolCallContext<'_, S> {
     }
 }
 
+/// Where a tool call sends its partial results, see
+/// [`ContentStream`](super::wrapper::ContentStream).
+#[derive(Debug, Clone)]
+pub struct ToolCallOutput {
+    pub(crate) peer: Peer<RoleServer>,
+    /// Partial results are only sent when the client asked for progress
+    pub(crate) progress_token: Option<ProgressToken>,
+}
+
+impl ToolCallOutput {
+    pub fn new(context: &RequestContext<RoleServer>) -> Self {
+        Self {
+            peer: context.peer.clone(),
+            progress_token: context.meta.get_progress_token(),
+        }
+    }
+}
+
 pub trait IntoCallToolResult {
     fn into_call_tool_result(self) -> Result<CallToolResult, crate::ErrorData>;
+
+    /// Produce the result, sending partial results to `output` meanwhile.
+    ///
+    /// The result is ready at once unless overridden, as for [`ContentStream`](super::wrapper::ContentStream).
+    fn into_streamed_result(
+        self,
+        output: ToolCallOutput,
+    ) -> BoxFuture<'static, Result<CallToolResult, crate::ErrorData>>
+    where
+        Self: Sized,
+    {
+        let _ = output;
+        std::future::ready(self.into_call_tool_result()).boxed()
+    }
 }
 
 impl<T: IntoContents> IntoCallToolResult for T {
@@ -99,6 +131,16 @@ impl<T: IntoCallToolResult> IntoCallToolResult for Result<T, crate::ErrorData> {
             Err(error) => Err(error),
         }
     }
+
+    fn into_streamed_result(
+        self,
+        output: ToolCallOutput,
+    ) -> BoxFuture<'static, Result<CallToolResult, crate::ErrorData>> {
+        match self {
+            Ok(value) => value.into_streamed_result(output),
+            Err(error) => std::future::ready(Err(error)).boxed(),
+        }
+    }
 }
 
 pin_project_lite::pin_project! {
@@ -233,6 +275,7 @@ macro_rules! impl_for {
                 self,
                 mut context: ToolCallContext<'_, S>,
             ) -> BoxFuture<'_, Result<CallToolResult, crate::ErrorData>>{
+                let output = ToolCallOutput::new(&context.request_context);
                 $(
                     let result = $Tn::from_context_part(&mut context);
                     let $Tn = match result {
@@ -244,7 +287,7 @@ macro_rules! impl_for {
                 let fut = self(service, $($Tn,)*);
                 async move {
                     let result = fut.await;
-                    result.into_call_tool_result()
+                    result.into_streamed_result(output).await
                 }.boxed()
             }
         }
@@ -264,6 +307,7 @@ macro_rules! impl_for {
                 self,
                 mut context: ToolCallContext<S>,
             ) -> BoxFuture<'static, Result<CallToolResult, crate::ErrorData>>{
+                let output = ToolCallOutput::new(&context.request_context);
                 $(
                     let result = $Tn::from_context_part(&mut context);
                     let $Tn = match result {
@@ -274,7 +318,7 @@ macro_rules! impl_for {
                 let fut = self($($Tn,)*);
                 async move {
                     let result = fut.await;
-                    result.into_call_tool_result()
+                    result.into_streamed_result(output).await
                 }.boxed()
             }
         }
@@ -293,6 +337,7 @@ macro_rules! impl_for {
                 self,
                 mut context: ToolCallContext<S>,
             ) -> BoxFuture<'static, Result<CallToolResult, crate::ErrorData>> {
+                let output = ToolCallOutput::new(&context.request_context);
                 $(
                     let result = $Tn::from_context_part(&mut context);
                     let $Tn = match result {
@@ -300,7 +345,7 @@ macro_rules! impl_for {
                         Err(e) => return std::future::ready(Err(e)).boxed(),
                     };
                 )*
-                std::future::ready(self(context.service, $($Tn,)*).into_call_tool_result()).boxed()
+                self(context.service, $($Tn,)*).into_streamed_result(output)
             }
         }
 
@@ -318,6 +363,7 @@ macro_rules! impl_for {
                 self,
                 mut context: ToolCallContext<S>,
             ) -> BoxFuture<'static, Result<CallToolResult, crate::ErrorData>>  {
+                let output = ToolCallOutput::new(&context.request_context);
                 $(
                     let result = $Tn::from_context_part(&mut context);
                     let $Tn = match result {
@@ -325,7 +371,7 @@ macro_rules! impl_for {
                         Err(e) => return std::future::ready(Err(e)).boxed(),
                     };
                 )*
-                std::future::ready(self($($Tn,)*).into_call_tool_result()).boxed()
+                self($($Tn,)*).into_streamed_result(output)
             }
         }
     };
@@ -0,0 +1,316 @@
+//! Validate tool arguments and results against the schemas of the tool.
+//!
+//! Serde ignores many schema constraints, such as `minimum`, `pattern` or `maxItems`. A
+//! [`ToolRouter`](super::router::tool::ToolRouter) created
+//! [`with_argument_validation`](super::router::tool::ToolRouter::with_argument_validation)
+//! checks the arguments of each call before dispatching it:
+//!
+//! ```rust,ignore
+//! Self {
+//!     tool_router: Self::tool_router().with_argument_validation(),
+//! }
+//! ```
+//!
+//! Likewise, [`with_output_validation`](super::router::tool::ToolRouter::with_output_validation)
+//! checks that tools declaring an output schema return conforming structured content. With the
+//! `client` feature, [`Peer::call_tool`](crate::Peer::call_tool) checks the results of the
+//! tools it listed.
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use serde_json::Value;
+
+use crate::model::{CallToolResult, JsonObject, Tool};
+
+/// Compiled schemas, by tool name.
+#[derive(Clone, Default)]
+struct SchemaCache {
+    #[allow(clippy::type_complexity)]
+    compiled: Arc<Mutex<HashMap<String, (Arc<JsonObject>, Arc<jsonschema::Validator>)>>>,
+}
+
+impl std::fmt::Debug for SchemaCache {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let compiled = self.compiled.lock().expect("validator lock poisoned");
+        f.debug_list().entries(compiled.keys()).finish()
+    }
+}
+
+impl SchemaCache {
+    /// The compiled `schema` of the tool `name`, compiled again if the schema was replaced.
+    fn get(
+        &self,
+        name: &str,
+        schema: &Arc<JsonObject>,
+    ) -> Result<Arc<jsonschema::Validator>, crate::ErrorData> {
+        let mut compiled = self.compiled.lock().expect("validator lock poisoned");
+        if let Some((cached, validator)) = compiled.get(name) {
+            if Arc::ptr_eq(cached, schema) {
+                return Ok(validator.clone());
+            }
+        }
+        let validator = jsonschema::validator_for(&Value::Object(schema.as_ref().clone()))
+            .map_err(|error| {
+                crate::ErrorData::internal_error(
+                    format!("invalid schema for tool '{name}': {error}"),
+                    None,
+                )
+            })?;
+        let validator = Arc::new(validator);
+        compiled.insert(name.to_owned(), (schema.clone(), validator.clone()));
+        Ok(validator)
+    }
+}
+
+/// The failures of `instance` as a message and the `errors` data of an error, or `None` if
+/// it is valid.
+fn failures(validator: &jsonschema::Validator, instance: &Value) -> Option<(String, Value)> {
+    let errors = validator
+        .iter_errors(instance)
+        .map(|error| (error.instance_path.to_string(), error.to_string()))
+        .collect::<Vec<_>>();
+    if errors.is_empty() {
+        return None;
+    }
+    let message = errors
+        .iter()
+        .map(|(pointer, error)| format!("'{pointer}': {error}"))
+        .collect::<Vec<_>>()
+        .join("; ");
+    let errors = errors
+        .into_iter()
+        .map(|(pointer, message)| serde_json::json!({ "pointer": pointer, "message": message }))
+        .collect::<Vec<_>>();
+    Some((message, serde_json::json!({ "errors": errors })))
+}
+
+/// Validates tool arguments, caching the compiled schema of each tool.
+///
+/// Cloning shares the cache. A schema is compiled again when the tool is replaced by one
+/// with another schema.
+#[derive(Debug, Clone, Default)]
+pub struct ArgumentValidator {
+    cache: SchemaCache,
+}
+
+impl ArgumentValidator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Check `arguments` against the input schema of `tool`, missing arguments being an
+    /// empty object.
+    ///
+    /// Each failure is reported in the `errors` of an invalid params error, with the JSON
+    /// pointer of the failing value.
+    pub fn validate(
+        &self,
+        tool: &Tool,
+        arguments: Option<&JsonObject>,
+    ) -> Result<(), crate::ErrorData> {
+        let validator = self.cache.get(&tool.name, &tool.input_schema)?;
+        let instance = Value::Object(arguments.cloned().unwrap_or_default());
+        match failures(&validator, &instance) {
+            None => Ok(()),
+            Some((message, data)) => Err(crate::ErrorData::invalid_params(
+                format!("invalid arguments for tool '{}': {message}", tool.name),
+                Some(data),
+            )),
+        }
+    }
+}
+
+/// When an [`OutputValidator`] checks tool results.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OutputValidation {
+    /// Check every result
+    #[default]
+    Always,
+    /// Check results in builds with debug assertions only
+    Debug,
+}
+
+impl OutputValidation {
+    fn enabled(self) -> bool {
+        match self {
+            OutputValidation::Always => true,
+            OutputValidation::Debug => cfg!(debug_assertions),
+        }
+    }
+}
+
+/// Validates the structured content of tool results against the output schema of the tool,
+/// caching the compiled schema of each tool.
+///
+/// A tool declaring an output schema must return structured content, unless the result is
+/// an error. Cloning shares the cache.
+#[derive(Debug, Clone, Default)]
+pub struct OutputValidator {
+    mode: OutputValidation,
+    cache: SchemaCache,
+}
+
+impl OutputValidator {
+    pub fn new(mode: OutputValidation) -> Self {
+        Self {
+            mode,
+            cache: SchemaCache::default(),
+        }
+    }
+
+    pub fn mode(&self) -> OutputValidation {
+        self.mode
+    }
+
+    /// Check `result` against the output schema of `tool`.
+    ///
+    /// A violation is an internal error, with the JSON pointer of each failing value in its
+    /// `errors`.
+    pub fn validate(&self, tool: &Tool, result: &CallToolResult) -> Result<(), crate::ErrorData> {
+        let Some(schema) = &tool.output_schema else {
+            return Ok(());
+        };
+        if !self.mode.enabled() || result.is_error == Some(true) {
+            return Ok(());
+        }
+        let Some(structured_content) = &result.structured_content else {
+            return Err(crate::ErrorData::internal_error(
+                format!(
+                    "tool '{}' declares an output schema but returned no structured content",
+                    tool.name
+                ),
+                None,
+            ));
+        };
+        let validator = self.cache.get(&tool.name, schema)?;
+        match failures(&validator, structured_content) {
+            None => Ok(()),
+            Some((message, data)) => Err(crate::ErrorData::internal_error(
+                format!(
+                    "structured content of tool '{}' doesn't match its output schema: {message}",
+                    tool.name
+                ),
+                Some(data),
+            )),
+        }
+    }
+}
+
+/// The tools listed by a server, to validate their results on the client.
+#[cfg(feature = "client")]
+#[derive(Debug, Default)]
+pub(crate) struct ListedTools {
+    tools: std::sync::RwLock<HashMap<String, Tool>>,
+    validator: OutputValidator,
+}
+
+#[cfg(feature = "client")]
+impl ListedTools {
+    /// Remember the tools declaring an output schema, forgetting the others.
+    pub(crate) fn record(&self, tools: &[Tool]) {
+        let mut listed = self.tools.write().expect("tool cache lock poisoned");
+        for tool in tools {
+            if tool.output_schema.is_some() {
+                listed.insert(tool.name.to_string(), tool.clone());
+            } else {
+                listed.remove(tool.name.as_ref());
+            }
+        }
+    }
+
+    /// Check a result of the tool `name`, if it was listed.
+    pub(crate) fn validate(
+        &self,
+        name: &str,
+        result: &CallToolResult,
+    ) -> Result<(), crate::ErrorData> {
+        let tool = self
+            .tools
+            .read()
+            .expect("tool cache lock poisoned")
+            .get(name)
+            .cloned();
+        match tool {
+            Some(tool) => self.validator.validate(&tool, result),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::ErrorCode;
+
+    fn tool(schema: Value) -> Tool {
+        Tool::new("test", "", Arc::new(crate::model::object(schema)))
+    }
+
+    #[test]
+    fn test_validate_arguments() {
+        let validator = ArgumentValidator::new();
+        let tool = tool(serde_json::json!({
+            "type": "object",
+            "properties": {
+                "count": { "type": "integer", "minimum": 1 },
+                "tags": { "type": "array", "maxItems": 1 }
+            },
+            "required": ["count"]
+        }));
+        let arguments = crate::model::object(serde_json::json!({ "count": 1 }));
+        assert!(validator.validate(&tool, Some(&arguments)).is_ok());
+
+        let arguments = crate::model::object(serde_json::json!({ "count": 0, "tags": ["a", "b"] }));
+        let error = validator.validate(&tool, Some(&arguments)).unwrap_err();
+        assert_eq!(error.code, ErrorCode::INVALID_PARAMS);
+        let mut pointers = error.data.unwrap()["errors"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|error| error["pointer"].as_str().unwrap().to_owned())
+            .collect::<Vec<_>>();
+        pointers.sort();
+        assert_eq!(pointers, ["/count", "/tags"]);
+
+        let error = validator.validate(&tool, None).unwrap_err();
+        assert!(error.message.contains("count"), "{}", error.message);
+    }
+
+    #[test]
+    fn test_validate_output() {
+        let validator = OutputValidator::new(OutputValidation::Always);
+        let mut tool = tool(serde_json::json!({ "type": "object" }));
+        tool.output_schema = Some(Arc::new(crate::model::object(serde_json::json!({
+            "type": "object",
+            "properties": { "sum": { "type": "integer" } },
+            "required": ["sum"]
+        }))));
+        let result = CallToolResult::structured(serde_json::json!({ "sum": 3 }));
+        assert!(validator.validate(&tool, &result).is_ok());
+
+        let result = CallToolResult::structured(serde_json::json!({ "sum": "3" }));
+        let error = validator.validate(&tool, &result).unwrap_err();
+        assert_eq!(error.code, ErrorCode::INTERNAL_ERROR);
+        assert_eq!(error.data.unwrap()["errors"][0]["pointer"], "/sum");
+
+        let result = CallToolResult::success(vec![]);
+        assert!(validator.validate(&tool, &result).is_err());
+        let result = CallToolResult::error(vec![]);
+        assert!(validator.validate(&tool, &result).is_ok());
+    }
+
+    #[test]
+    fn test_recompile_replaced_schema() {
+        let validator = ArgumentValidator::new();
+        let arguments = crate::model::object(serde_json::json!({ "name": "a" }));
+        let strict = tool(serde_json::json!({
+            "type": "object",
+            "properties": { "name": { "type": "string", "minLength": 2 } }
+        }));
+        assert!(validator.validate(&strict, Some(&arguments)).is_err());
+        let lenient = tool(serde_json::json!({ "type": "object" }));
+        assert!(validator.validate(&lenient, Some(&arguments)).is_ok());
+    }
+}
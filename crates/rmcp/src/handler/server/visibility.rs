@@ -0,0 +1,193 @@
+//! Show each session its own subset of tools and prompts.
+//!
+//! A filter decides, from the request being handled, whether a tool or prompt exists for the
+//! session. Hidden ones are neither listed nor callable, so calling them fails as if they
+//! were unknown:
+//!
+//! ```rust,ignore
+//! let tool_router = Self::tool_router().with_filter(|tool, context| {
+//!     // only clients supporting sampling see the tools which need it
+//!     !tool.name.starts_with("sample_")
+//!         || context
+//!             .peer
+//!             .peer_info()
+//!             .is_some_and(|info| info.capabilities.sampling.is_some())
+//! });
+//! ```
+//!
+//! When what a session may see changes, e.g. after a tool enabled a feature for it, call
+//! [`ToolRouter::refresh_visibility`](super::router::tool::ToolRouter::refresh_visibility) or
+//! [`PromptRouter::refresh_visibility`](super::router::prompt::PromptRouter::refresh_visibility)
+//! to tell the client when its list changed.
+use std::{
+    collections::BTreeSet,
+    sync::{Arc, Mutex, Weak},
+};
+
+use crate::{Peer, RoleServer, service::RequestContext};
+
+/// Whether a tool or prompt is visible to the session of a request.
+pub type VisibilityFilter<T> = Arc<dyn Fn(&T, &RequestContext<RoleServer>) -> bool + Send + Sync>;
+
+/// The filters of a router, and the names each session was last listed, which is only kept
+/// when there are filters.
+pub struct Visibility<T> {
+    filters: Vec<VisibilityFilter<T>>,
+    #[allow(clippy::type_complexity)]
+    listed: Arc<Mutex<Vec<(Peer<RoleServer>, BTreeSet<String>)>>>,
+}
+
+impl<T> Default for Visibility<T> {
+    fn default() -> Self {
+        Self {
+            filters: Vec::new(),
+            listed: Arc::default(),
+        }
+    }
+}
+
+impl<T> Clone for Visibility<T> {
+    fn clone(&self) -> Self {
+        Self {
+            filters: self.filters.clone(),
+            listed: self.listed.clone(),
+        }
+    }
+}
+
+impl<T> std::fmt::Debug for Visibility<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Visibility")
+            .field("filters", &self.filters.len())
+            .finish_non_exhaustive()
+    }
+}
+
+impl<T> Visibility<T> {
+    /// Hide the items for which `filter` returns false.
+    pub fn add_filter(
+        &mut self,
+        filter: impl Fn(&T, &RequestContext<RoleServer>) -> bool + Send + Sync + 'static,
+    ) {
+        self.filters.push(Arc::new(filter));
+    }
+
+    pub(crate) fn has_filters(&self) -> bool {
+        !self.filters.is_empty()
+    }
+
+    /// Whether every filter lets the session of `context` see `item`.
+    pub fn is_visible(&self, item: &T, context: &RequestContext<RoleServer>) -> bool {
+        self.filters.iter().all(|filter| filter(item, context))
+    }
+
+    /// Remember the names listed to the session of `context`, returning whether they differ
+    /// from the ones it was listed before, if it was.
+    ///
+    /// Without filters every session sees the same items, so nothing is remembered.
+    pub(crate) fn record<'a>(
+        &self,
+        context: &RequestContext<RoleServer>,
+        names: impl IntoIterator<Item = &'a str>,
+    ) -> bool {
+        if !self.has_filters() {
+            return false;
+        }
+        let names = names
+            .into_iter()
+            .map(str::to_owned)
+            .collect::<BTreeSet<_>>();
+        let mut listed = self.listed.lock().expect("visibility lock poisoned");
+        match listed
+            .iter_mut()
+            .find(|(peer, _)| peer.is_same_peer(&context.peer))
+        {
+            Some((_, previous)) if *previous == names => false,
+            Some((_, previous)) => {
+                *previous = names;
+                true
+            }
+            None => {
+                listed.push((context.peer.clone(), names));
+                Self::remove_when_closed(Arc::downgrade(&self.listed), context.peer.clone());
+                false
+            }
+        }
+    }
+
+    #[allow(clippy::type_complexity)]
+    fn remove_when_closed(
+        listed: Weak<Mutex<Vec<(Peer<RoleServer>, BTreeSet<String>)>>>,
+        peer: Peer<RoleServer>,
+    ) {
+        tokio::spawn(async move {
+            peer.closed().await;
+            if let Some(listed) = listed.upgrade() {
+                listed
+                    .lock()
+                    .expect("visibility lock poisoned")
+                    .retain(|(listed, _)| !listed.is_same_peer(&peer));
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{model::NumberOrString, service::AtomicU32RequestIdProvider};
+
+    /// A request context, and the end of its peer's channel, which closes the peer when dropped
+    fn context() -> (RequestContext<RoleServer>, impl Sized) {
+        let (peer, outbound) = Peer::<RoleServer>::new(
+            Arc::new(AtomicU32RequestIdProvider::default()),
+            None,
+            Default::default(),
+        );
+        let context = RequestContext {
+            ct: Default::default(),
+            id: NumberOrString::Number(1),
+            meta: Default::default(),
+            extensions: Default::default(),
+            peer,
+        };
+        (context, outbound)
+    }
+
+    fn sessions<T>(visibility: &Visibility<T>) -> usize {
+        visibility.listed.lock().unwrap().len()
+    }
+
+    #[tokio::test]
+    async fn test_record_only_with_filters() {
+        let (context, _outbound) = context();
+        let visibility = Visibility::<String>::default();
+        assert!(!visibility.record(&context, ["a"]));
+        assert!(!visibility.record(&context, ["b"]));
+        assert_eq!(sessions(&visibility), 0);
+
+        let mut visibility = Visibility::<String>::default();
+        visibility.add_filter(|_, _| true);
+        assert!(!visibility.record(&context, ["a"]));
+        assert!(visibility.record(&context, ["b"]));
+        assert_eq!(sessions(&visibility), 1);
+    }
+
+    #[tokio::test]
+    async fn test_closed_session_is_removed() {
+        let (context, outbound) = context();
+        let mut visibility = Visibility::<String>::default();
+        visibility.add_filter(|_, _| true);
+        visibility.record(&context, ["a"]);
+        assert_eq!(sessions(&visibility), 1);
+
+        drop(outbound);
+        tokio::time::timeout(std::time::Duration::from_secs(5), async {
+            while sessions(&visibility) > 0 {
+                tokio::task::yield_now().await;
+            }
+        })
+        .await
+        .expect("session removed");
+    }
+}
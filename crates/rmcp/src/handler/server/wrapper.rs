@@ -1,4 +1,6 @@
 mod json;
 mod parameters;
+mod stream;
 pub use json::*;
 pub use parameters::*;
+pub use stream::*;
@@ -0,0 +1,73 @@
+use futures::{
+    Stream, StreamExt,
+    future::{BoxFuture, FutureExt},
+};
+
+use crate::{
+    handler::server::tool::{IntoCallToolResult, ToolCallOutput},
+    model::{
+        CallToolResult, Content, Meta, ProgressNotification, ProgressNotificationParam,
+        ServerNotification,
+    },
+};
+
+/// Stream wrapper for incremental output
+///
+/// When returned by a tool, each chunk is sent to the client as soon as it is produced, in
+/// a progress notification carrying it under [`Meta::PARTIAL_CONTENT_FIELD`], if the client
+/// asked for progress. The result of the call is then every chunk, in order.
+///
+/// Progress is the number of chunks sent, so the tool must not report progress on its own.
+///
+/// ```rust,ignore
+/// #[tool(description = "Tail the build log")]
+/// async fn build_log(&self) -> ContentStream<BoxStream<'static, Content>> {
+///     ContentStream(self.log_lines().map(Content::text).boxed())
+/// }
+/// ```
+pub struct ContentStream<S>(pub S);
+
+impl<S> IntoCallToolResult for ContentStream<S>
+where
+    S: Stream<Item = Content> + Send + 'static,
+{
+    fn into_call_tool_result(self) -> Result<CallToolResult, crate::ErrorData> {
+        Err(crate::ErrorData::internal_error(
+            "a content stream can only be collected by a tool call",
+            None,
+        ))
+    }
+
+    fn into_streamed_result(
+        self,
+        output: ToolCallOutput,
+    ) -> BoxFuture<'static, Result<CallToolResult, crate::ErrorData>> {
+        async move {
+            let mut stream = std::pin::pin!(self.0);
+            let mut content = Vec::new();
+            while let Some(chunk) = stream.next().await {
+                if let Some(token) = &output.progress_token {
+                    let mut notification = ProgressNotification::new(ProgressNotificationParam {
+                        progress_token: token.clone(),
+                        progress: (content.len() + 1) as f64,
+                        total: None,
+                        message: None,
+                    });
+                    let mut meta = Meta::new();
+                    meta.set_partial_content(std::slice::from_ref(&chunk));
+                    notification.extensions.insert(meta);
+                    if let Err(error) = output
+                        .peer
+                        .send_notification(ServerNotification::ProgressNotification(notification))
+                        .await
+                    {
+                        tracing::warn!(%error, "failed to send partial content");
+                    }
+                }
+                content.push(chunk);
+            }
+            Ok(CallToolResult::success(content))
+        }
+        .boxed()
+    }
+}
@@ -0,0 +1,275 @@
+//! Built-in protocol metrics.
+//!
+//! With the `metrics` feature enabled, rmcp records the following metrics through the
+//! [`metrics`](::metrics) facade, so they end up in whatever recorder the application installs:
+//!
+//! | name | kind | labels |
+//! |------|------|--------|
+//! | [`REQUESTS_TOTAL`] | counter | `role`, `direction`, `method`, `tool`, `status` |
+//! | [`REQUEST_DURATION_SECONDS`] | histogram | `role`, `direction`, `method`, `tool` |
+//! | [`REQUEST_ERRORS_TOTAL`] | counter | `role`, `direction`, `method`, `tool`, `code` |
+//! | [`NOTIFICATIONS_TOTAL`] | counter | `role`, `direction`, `method` |
+//! | [`TRANSPORT_BYTES_TOTAL`] | counter | `transport`, `direction` |
+//! | [`SESSIONS_ACTIVE`] | gauge | |
+//! | [`TASKS_TOTAL`] | counter | `status` |
+//! | [`TASKS_RUNNING`] | gauge | |
+//!
+//! - `role` is the local role, `client` or `server`.
+//! - `direction` is `inbound` for messages received from the peer and `outbound` for messages
+//!   sent to it. Transport bytes use `in` and `out`.
+//! - `tool` is the tool name for `tools/call`, and empty for every other method.
+//! - `status` is one of `ok`, `error` (a JSON-RPC error, whose code is counted in
+//!   [`REQUEST_ERRORS_TOTAL`]), `cancelled` or `transport_error`.
+//!
+//! Transport bytes are counted for the newline-delimited stream transports (stdio, child
+//! process and other async read/write pairs) and for the streamable HTTP server.
+//!
+//! The `metrics-prometheus` feature adds [`PrometheusMetricsService`], a `/metrics` endpoint in
+//! the Prometheus text exposition format that can be mounted next to the streamable HTTP service:
+//!
+//! ```rust,ignore
+//! let metrics = PrometheusMetricsService::install()?;
+//! let router = axum::Router::new()
+//!     .nest_service("/mcp", mcp_service)
+//!     .route_service("/metrics", metrics);
+//! ```
+use ::metrics::{counter, describe_counter, describe_gauge, describe_histogram, gauge, histogram};
+
+use crate::model::ErrorCode;
+
+#[cfg(feature = "metrics-prometheus")]
+#[cfg_attr(docsrs, doc(cfg(feature = "metrics-prometheus")))]
+mod prometheus;
+#[cfg(feature = "metrics-prometheus")]
+#[cfg_attr(docsrs, doc(cfg(feature = "metrics-prometheus")))]
+pub use prometheus::*;
+
+/// Number of requests, by outcome.
+pub const REQUESTS_TOTAL: &str = "mcp_requests_total";
+/// Time from receiving a request to sending its response, or from sending a request to
+/// receiving its response.
+pub const REQUEST_DURATION_SECONDS: &str = "mcp_request_duration_seconds";
+/// Number of requests answered with a JSON-RPC error, by error code.
+pub const REQUEST_ERRORS_TOTAL: &str = "mcp_request_errors_total";
+/// Number of notifications.
+pub const NOTIFICATIONS_TOTAL: &str = "mcp_notifications_total";
+/// Number of bytes read from and written to transports.
+pub const TRANSPORT_BYTES_TOTAL: &str = "mcp_transport_bytes_total";
+/// Number of live sessions in [`LocalSessionManager`](crate::transport::streamable_http_server::session::local::LocalSessionManager)s.
+pub const SESSIONS_ACTIVE: &str = "mcp_sessions_active";
+/// Number of tasks handled by [`OperationProcessor`](crate::task_manager::OperationProcessor)s,
+/// by status: `submitted`, `completed`, `failed`, `timed_out`, `cancelled` or `abandoned` (still
+/// running when the processor was dropped).
+pub const TASKS_TOTAL: &str = "mcp_tasks_total";
+/// Number of tasks currently running in [`OperationProcessor`](crate::task_manager::OperationProcessor)s.
+pub const TASKS_RUNNING: &str = "mcp_tasks_running";
+
+/// Register the descriptions and units of rmcp's metrics with the installed recorder.
+///
+/// [`PrometheusMetricsService::install`] calls this for you.
+pub fn describe() {
+    describe_counter!(REQUESTS_TOTAL, "Number of MCP requests");
+    describe_histogram!(
+        REQUEST_DURATION_SECONDS,
+        ::metrics::Unit::Seconds,
+        "Duration of MCP requests"
+    );
+    describe_counter!(
+        REQUEST_ERRORS_TOTAL,
+        "Number of MCP requests answered with a JSON-RPC error"
+    );
+    describe_counter!(NOTIFICATIONS_TOTAL, "Number of MCP notifications");
+    describe_counter!(
+        TRANSPORT_BYTES_TOTAL,
+        ::metrics::Unit::Bytes,
+        "Number of bytes read from and written to MCP transports"
+    );
+    describe_gauge!(SESSIONS_ACTIVE, "Number of active MCP sessions");
+    describe_counter!(TASKS_TOTAL, "Number of MCP tasks by status");
+    describe_gauge!(TASKS_RUNNING, "Number of running MCP tasks");
+}
+
+fn role(is_client: bool) -> &'static str {
+    if is_client { "client" } else { "server" }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum Direction {
+    Inbound,
+    Outbound,
+}
+
+impl Direction {
+    fn message_label(self) -> &'static str {
+        match self {
+            Direction::Inbound => "inbound",
+            Direction::Outbound => "outbound",
+        }
+    }
+
+    fn bytes_label(self) -> &'static str {
+        match self {
+            Direction::Inbound => "in",
+            Direction::Outbound => "out",
+        }
+    }
+}
+
+/// How a request ended, as seen by the side recording it.
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum RequestOutcome {
+    Ok,
+    Error(ErrorCode),
+    Cancelled,
+    TransportError,
+}
+
+/// Labels and start time of a request in flight.
+#[derive(Debug)]
+pub(crate) struct RequestObservation {
+    labels: [(&'static str, String); 4],
+    started_at: std::time::Instant,
+}
+
+impl RequestObservation {
+    pub(crate) fn start(
+        is_client: bool,
+        direction: Direction,
+        method: &str,
+        tool: Option<&str>,
+    ) -> Self {
+        Self {
+            labels: [
+                ("role", role(is_client).to_owned()),
+                ("direction", direction.message_label().to_owned()),
+                ("method", method.to_owned()),
+                ("tool", tool.unwrap_or_default().to_owned()),
+            ],
+            started_at: std::time::Instant::now(),
+        }
+    }
+
+    pub(crate) fn finish(self, outcome: RequestOutcome) {
+        let Self { labels, started_at } = self;
+        let status = match outcome {
+            RequestOutcome::Ok => "ok",
+            RequestOutcome::Error(code) => {
+                let [role, direction, method, tool] = &labels;
+                counter!(
+                    REQUEST_ERRORS_TOTAL,
+                    &[
+                        role.clone(),
+                        direction.clone(),
+                        method.clone(),
+                        tool.clone(),
+                        ("code", code.0.to_string()),
+                    ]
+                )
+                .increment(1);
+                "error"
+            }
+            RequestOutcome::Cancelled => "cancelled",
+            RequestOutcome::TransportError => "transport_error",
+        };
+        histogram!(REQUEST_DURATION_SECONDS, &labels).record(started_at.elapsed());
+        let [role, direction, method, tool] = labels;
+        counter!(
+            REQUESTS_TOTAL,
+            &[role, direction, method, tool, ("status", status.to_owned())]
+        )
+        .increment(1);
+    }
+}
+
+pub(crate) fn record_notification(is_client: bool, direction: Direction, method: &str) {
+    counter!(
+        NOTIFICATIONS_TOTAL,
+        "role" => role(is_client),
+        "direction" => direction.message_label(),
+        "method" => method.to_owned(),
+    )
+    .increment(1);
+}
+
+#[cfg_attr(
+    not(any(feature = "transport-async-rw", feature = "server-side-http")),
+    allow(dead_code)
+)]
+pub(crate) fn record_transport_bytes(transport: &'static str, direction: Direction, bytes: usize) {
+    counter!(
+        TRANSPORT_BYTES_TOTAL,
+        "transport" => transport,
+        "direction" => direction.bytes_label(),
+    )
+    .increment(bytes as u64);
+}
+
+/// Counts a session in [`SESSIONS_ACTIVE`] for as long as it is alive.
+#[derive(Debug)]
+#[cfg_attr(
+    not(feature = "transport-streamable-http-server-session"),
+    allow(dead_code)
+)]
+pub(crate) struct ActiveSessionGuard(());
+
+impl ActiveSessionGuard {
+    #[cfg_attr(
+        not(feature = "transport-streamable-http-server-session"),
+        allow(dead_code)
+    )]
+    pub(crate) fn new() -> Self {
+        gauge!(SESSIONS_ACTIVE).increment(1);
+        Self(())
+    }
+}
+
+impl Drop for ActiveSessionGuard {
+    fn drop(&mut self) {
+        gauge!(SESSIONS_ACTIVE).decrement(1);
+    }
+}
+
+pub(crate) fn record_task_submitted() {
+    counter!(TASKS_TOTAL, "status" => "submitted").increment(1);
+    gauge!(TASKS_RUNNING).increment(1);
+}
+
+/// Record `count` tasks leaving the running state with `status`.
+pub(crate) fn record_tasks_finished(status: &'static str, count: usize) {
+    if count == 0 {
+        return;
+    }
+    counter!(TASKS_TOTAL, "status" => status).increment(count as u64);
+    gauge!(TASKS_RUNNING).decrement(count as f64);
+}
+
+/// Observe the response of an outbound request on its way to the caller.
+#[cfg(any(feature = "client", feature = "server"))]
+pub(crate) fn observe_outbound_response<T: Send + 'static>(
+    observation: RequestObservation,
+    mut responder: tokio::sync::oneshot::Sender<Result<T, crate::service::ServiceError>>,
+) -> tokio::sync::oneshot::Sender<Result<T, crate::service::ServiceError>> {
+    use crate::service::ServiceError;
+    let (tx, rx) = tokio::sync::oneshot::channel();
+    tokio::spawn(async move {
+        tokio::select! {
+            response = rx => {
+                let outcome = match &response {
+                    Ok(Ok(_)) => RequestOutcome::Ok,
+                    Ok(Err(ServiceError::McpError(error))) => RequestOutcome::Error(error.code),
+                    Ok(Err(ServiceError::Cancelled { .. } | ServiceError::Timeout { .. })) => {
+                        RequestOutcome::Cancelled
+                    }
+                    Ok(Err(_)) | Err(_) => RequestOutcome::TransportError,
+                };
+                observation.finish(outcome);
+                if let Ok(response) = response {
+                    let _ = responder.send(response);
+                }
+            }
+            // the caller stopped waiting, e.g. because it timed out
+            _ = responder.closed() => observation.finish(RequestOutcome::Cancelled),
+        }
+    });
+    tx
+}
@@ -0,0 +1,99 @@
+use std::convert::Infallible;
+
+use bytes::Bytes;
+use futures::future::{Ready, ready};
+use http::{Request, Response, StatusCode, header};
+use http_body_util::Full;
+use metrics_exporter_prometheus::Matcher;
+pub use metrics_exporter_prometheus::{BuildError, PrometheusBuilder, PrometheusHandle};
+
+use super::REQUEST_DURATION_SECONDS;
+
+/// The content type of the Prometheus text exposition format.
+pub const PROMETHEUS_TEXT_MIME_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+/// Histogram buckets used for [`REQUEST_DURATION_SECONDS`], in seconds.
+pub const REQUEST_DURATION_BUCKETS: &[f64] = &[
+    0.001, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0, 60.0,
+];
+
+/// A `/metrics` endpoint serving a [`PrometheusHandle`] in the text exposition format.
+///
+/// This is a tower service, so it can be mounted in an axum router next to
+/// [`StreamableHttpService`](crate::transport::streamable_http_server::StreamableHttpService):
+///
+/// ```rust,ignore
+/// let metrics = PrometheusMetricsService::install()?;
+/// let router = axum::Router::new()
+///     .nest_service("/mcp", mcp_service)
+///     .route_service("/metrics", metrics);
+/// ```
+#[derive(Debug, Clone)]
+pub struct PrometheusMetricsService {
+    handle: PrometheusHandle,
+}
+
+impl PrometheusMetricsService {
+    /// Serve the metrics of an already installed Prometheus recorder.
+    pub fn new(handle: PrometheusHandle) -> Self {
+        Self { handle }
+    }
+
+    /// A [`PrometheusBuilder`] with histogram buckets for rmcp's request durations.
+    pub fn builder() -> PrometheusBuilder {
+        PrometheusBuilder::new()
+            .set_buckets_for_metric(
+                Matcher::Full(REQUEST_DURATION_SECONDS.to_owned()),
+                REQUEST_DURATION_BUCKETS,
+            )
+            .expect("buckets are not empty")
+    }
+
+    /// Install a Prometheus recorder built by [`Self::builder`] as the global recorder, and
+    /// serve it.
+    ///
+    /// This fails if a global recorder is already installed.
+    pub fn install() -> Result<Self, BuildError> {
+        let handle = Self::builder().install_recorder()?;
+        super::describe();
+        Ok(Self::new(handle))
+    }
+
+    pub fn handle(&self) -> &PrometheusHandle {
+        &self.handle
+    }
+
+    /// Render the current metrics in the text exposition format.
+    pub fn render(&self) -> String {
+        self.handle.run_upkeep();
+        self.handle.render()
+    }
+}
+
+impl<RequestBody> tower_service::Service<Request<RequestBody>> for PrometheusMetricsService {
+    type Response = Response<Full<Bytes>>;
+    type Error = Infallible;
+    type Future = Ready<Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(
+        &mut self,
+        _cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Result<(), Self::Error>> {
+        std::task::Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: Request<RequestBody>) -> Self::Future {
+        let response = if request.method() == http::Method::GET {
+            Response::builder()
+                .status(StatusCode::OK)
+                .header(header::CONTENT_TYPE, PROMETHEUS_TEXT_MIME_TYPE)
+                .body(Full::new(Bytes::from(self.render())))
+        } else {
+            Response::builder()
+                .status(StatusCode::METHOD_NOT_ALLOWED)
+                .header(header::ALLOW, "GET")
+                .body(Full::new(Bytes::from("Method Not Allowed")))
+        };
+        ready(Ok(response.expect("valid response")))
+    }
+}
@@ -2,6 +2,7 @@ use std::{borrow::Cow, sync::Arc};
 mod annotated;
 mod capabilities;
 mod content;
+mod custom;
 mod elicitation_schema;
 mod extension;
 mod meta;
@@ -10,9 +11,12 @@ mod resource;
 mod serde_impl;
 mod task;
 mod tool;
+mod uri_template;
+mod version;
 pub use annotated::*;
 pub use capabilities::*;
 pub use content::*;
+pub use custom::*;
 pub use elicitation_schema::*;
 pub use extension::*;
 pub use meta::*;
@@ -22,6 +26,8 @@ use serde::{Deserialize, Serialize, de::DeserializeOwned};
 use serde_json::Value;
 pub use task::*;
 pub use tool::*;
+pub use uri_template::*;
+pub use version::*;
 
 /// A JSON object type alias for convenient handling of JSON data.
 ///
@@ -151,11 +157,27 @@ impl std::fmt::Display for ProtocolVersion {
 }
 
 impl ProtocolVersion {
+    pub const V_2025_11_25: Self = Self(Cow::Borrowed("2025-11-25"));
     pub const V_2025_06_18: Self = Self(Cow::Borrowed("2025-06-18"));
     pub const V_2025_03_26: Self = Self(Cow::Borrowed("2025-03-26"));
     pub const V_2024_11_05: Self = Self(Cow::Borrowed("2024-11-05"));
     //  Keep LATEST at 2025-03-26 until full 2025-06-18 compliance and automated testing are in place.
     pub const LATEST: Self = Self::V_2025_03_26;
+    /// Every revision this crate can negotiate, oldest first.
+    ///
+    /// Versions newer than [`LATEST`](Self::LATEST) are only negotiated by services declaring
+    /// them, or accepting them with
+    /// [`RequestPolicy::with_protocol_versions`](crate::service::RequestPolicy::with_protocol_versions).
+    pub const KNOWN: &'static [Self] = &[
+        Self::V_2024_11_05,
+        Self::V_2025_03_26,
+        Self::V_2025_06_18,
+        Self::V_2025_11_25,
+    ];
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
 }
 
 impl Serialize for ProtocolVersion {
@@ -178,6 +200,7 @@ impl<'de> Deserialize<'de> for ProtocolVersion {
             "2024-11-05" => return Ok(ProtocolVersion::V_2024_11_05),
             "2025-03-26" => return Ok(ProtocolVersion::V_2025_03_26),
             "2025-06-18" => return Ok(ProtocolVersion::V_2025_06_18),
+            "2025-11-25" => return Ok(ProtocolVersion::V_2025_11_25),
             _ => {}
         }
         Ok(ProtocolVersion(Cow::Owned(s)))
@@ -453,6 +476,8 @@ impl ErrorCode {
     pub const INVALID_PARAMS: Self = Self(-32602);
     pub const INTERNAL_ERROR: Self = Self(-32603);
     pub const PARSE_ERROR: Self = Self(-32700);
+    /// The request was cancelled by its sender, the code LSP uses
+    pub const REQUEST_CANCELLED: Self = Self(-32800);
 }
 
 /// Error information for JSON-RPC error responses.
@@ -504,6 +529,9 @@ impl ErrorData {
     pub fn internal_error(message: impl Into<Cow<'static, str>>, data: Option<Value>) -> Self {
         Self::new(ErrorCode::INTERNAL_ERROR, message, data)
     }
+    pub fn request_cancelled(message: impl Into<Cow<'static, str>>, data: Option<Value>) -> Self {
+        Self::new(ErrorCode::REQUEST_CANCELLED, message, data)
+    }
 }
 
 /// Represents any JSON-RPC message that can be sent or received.
@@ -1126,8 +1154,9 @@ pub type ToolListChangedNotification = NotificationNoParam<ToolListChangedNotifi
 // LOGGING
 // =============================================================================
 
-/// Logging levels supported by the MCP protocol
-#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Copy)]
+/// Logging levels supported by the MCP protocol, ordered from the least to the most severe
+// the derived ordering relies on the declaration order
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Copy)]
 #[serde(rename_all = "lowercase")] //match spec
 #[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub enum LoggingLevel {
@@ -2161,6 +2190,35 @@ impl ClientRequest {
     }
 }
 
+/// Access to the JSON-RPC method name of a request or notification union.
+pub trait GetMethod {
+    fn method(&self) -> &str;
+}
+
+macro_rules! variant_method {
+    (
+        $Enum: ident {
+            $($variant: ident)*
+        }
+    ) => {
+        impl GetMethod for $Enum {
+            fn method(&self) -> &str {
+                match self {
+                    $(
+                        $Enum::$variant(v) => v.method.as_str(),
+                    )*
+                }
+            }
+        }
+    };
+}
+
+impl GetMethod for ClientRequest {
+    fn method(&self) -> &str {
+        ClientRequest::method(self)
+    }
+}
+
 ts_union!(
     export type ClientNotification =
     | CancelledNotification
@@ -2170,6 +2228,16 @@ ts_union!(
     | CustomNotification;
 );
 
+variant_method! {
+    ClientNotification {
+        CancelledNotification
+        ProgressNotification
+        InitializedNotification
+        RootsListChangedNotification
+        CustomNotification
+    }
+}
+
 ts_union!(
     export type ClientResult =
     box CreateMessageResult
@@ -2196,6 +2264,16 @@ ts_union!(
     | CustomRequest;
 );
 
+variant_method! {
+    ServerRequest {
+        PingRequest
+        CreateMessageRequest
+        ListRootsRequest
+        CreateElicitationRequest
+        CustomRequest
+    }
+}
+
 ts_union!(
     export type ServerNotification =
     | CancelledNotification
@@ -2208,6 +2286,19 @@ ts_union!(
     | CustomNotification;
 );
 
+variant_method! {
+    ServerNotification {
+        CancelledNotification
+        ProgressNotification
+        LoggingMessageNotification
+        ResourceUpdatedNotification
+        ResourceListChangedNotification
+        ToolListChangedNotification
+        PromptListChangedNotification
+        CustomNotification
+    }
+}
+
 ts_union!(
     export type ServerResult =
     | InitializeResult
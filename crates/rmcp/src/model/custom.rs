@@ -0,0 +1,40 @@
+use super::*;
+
+/// A custom method with typed params and result, sent as a [`CustomRequest`] or a
+/// [`CustomNotification`].
+///
+/// It's usually derived on the params type, the result defaults to [`EmptyObject`] and the
+/// capability to the method name:
+///
+/// ```rust,ignore
+/// #[derive(Serialize, Deserialize, CustomMethod)]
+/// #[custom_method(method = "acme/search", result = SearchResult, capability = "acme")]
+/// struct Search {
+///     query: String,
+/// }
+/// ```
+///
+/// Params and results are sent as JSON objects, so they should serialize to one. The result of a
+/// method only used as a notification is ignored.
+pub trait CustomMethod: Send + Sync + 'static {
+    const METHOD: &'static str;
+    /// The key of the `experimental` capability the method is advertised under, see
+    /// [`CustomRouter::experimental_capabilities`](crate::handler::custom::CustomRouter::experimental_capabilities).
+    const CAPABILITY: &'static str = Self::METHOD;
+    type Params: Serialize + DeserializeOwned + Send + 'static;
+    type Result: Serialize + DeserializeOwned + Send + 'static;
+}
+
+impl CustomRequest {
+    /// A request of the custom method `M`.
+    pub fn typed<M: CustomMethod>(params: &M::Params) -> Result<Self, serde_json::Error> {
+        Ok(Self::new(M::METHOD, Some(serde_json::to_value(params)?)))
+    }
+}
+
+impl CustomNotification {
+    /// A notification of the custom method `M`.
+    pub fn typed<M: CustomMethod>(params: &M::Params) -> Result<Self, serde_json::Error> {
+        Ok(Self::new(M::METHOD, Some(serde_json::to_value(params)?)))
+    }
+}
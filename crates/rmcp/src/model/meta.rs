@@ -4,8 +4,8 @@ use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
 use super::{
-    ClientNotification, ClientRequest, CustomNotification, CustomRequest, Extensions, JsonObject,
-    JsonRpcMessage, NumberOrString, ProgressToken, ServerNotification, ServerRequest,
+    ClientNotification, ClientRequest, Content, CustomNotification, CustomRequest, Extensions,
+    JsonObject, JsonRpcMessage, NumberOrString, ProgressToken, ServerNotification, ServerRequest,
 };
 
 pub trait GetMeta {
@@ -197,6 +197,9 @@ variant_extension! {
 pub struct Meta(pub JsonObject);
 const PROGRESS_TOKEN_FIELD: &str = "progressToken";
 impl Meta {
+    /// The key of the content chunks a streaming tool attaches to its progress notifications.
+    pub const PARTIAL_CONTENT_FIELD: &str = "rmcp/partialContent";
+
     pub fn new() -> Self {
         Self(JsonObject::new())
     }
@@ -245,6 +248,21 @@ impl Meta {
         };
     }
 
+    /// The partial content of a streaming tool call, if this is the meta of one of its
+    /// progress notifications.
+    pub fn get_partial_content(&self) -> Option<Vec<Content>> {
+        self.0
+            .get(Self::PARTIAL_CONTENT_FIELD)
+            .and_then(|value| serde_json::from_value(value.clone()).ok())
+    }
+
+    pub fn set_partial_content(&mut self, content: &[Content]) {
+        if let Ok(value) = serde_json::to_value(content) {
+            self.0
+                .insert(Self::PARTIAL_CONTENT_FIELD.to_string(), value);
+        }
+    }
+
     pub fn extend(&mut self, other: Meta) {
         for (k, v) in other.0.into_iter() {
             self.0.insert(k, v);
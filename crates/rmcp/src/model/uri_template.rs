@@ -0,0 +1,878 @@
+//! [RFC 6570](https://www.rfc-editor.org/rfc/rfc6570) URI templates, up to level 4.
+use std::{collections::HashMap, fmt, str::FromStr};
+
+use super::RawResourceTemplate;
+
+/// A parsed URI template, as used by [`RawResourceTemplate::uri_template`].
+///
+/// ```rust
+/// # use std::collections::HashMap;
+/// # use rmcp::model::UriTemplate;
+/// let template: UriTemplate = "file:///{+path}{?version}".parse().unwrap();
+/// let uri = template
+///     .expand(&HashMap::from([("path".to_owned(), "docs/read me.md".into())]))
+///     .unwrap();
+/// assert_eq!(uri, "file:///docs/read%20me.md");
+/// assert_eq!(template.match_uri(&uri).unwrap()["path"], "docs/read me.md");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UriTemplate {
+    source: String,
+    parts: Vec<Part>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum UriTemplateError {
+    #[error("unclosed expression starting at {0}")]
+    UnclosedExpression(usize),
+    #[error("unexpected '}}' at {0}")]
+    UnexpectedClose(usize),
+    #[error("invalid expression `{{{0}}}`")]
+    InvalidExpression(String),
+    #[error("variable `{0}` cannot have both a prefix and an explode modifier")]
+    InvalidModifier(String),
+    #[error("variable `{0}` is not a string, a prefix modifier cannot be applied")]
+    PrefixOfComposite(String),
+}
+
+/// The value of a template variable. A variable missing from the variables is undefined.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UriTemplateValue {
+    String(String),
+    List(Vec<String>),
+    /// An associative array, in order
+    Map(Vec<(String, String)>),
+}
+
+pub type UriTemplateVariables = HashMap<String, UriTemplateValue>;
+
+impl From<String> for UriTemplateValue {
+    fn from(value: String) -> Self {
+        UriTemplateValue::String(value)
+    }
+}
+
+impl From<&str> for UriTemplateValue {
+    fn from(value: &str) -> Self {
+        UriTemplateValue::String(value.to_owned())
+    }
+}
+
+impl From<Vec<String>> for UriTemplateValue {
+    fn from(value: Vec<String>) -> Self {
+        UriTemplateValue::List(value)
+    }
+}
+
+impl From<Vec<(String, String)>> for UriTemplateValue {
+    fn from(value: Vec<(String, String)>) -> Self {
+        UriTemplateValue::Map(value)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Part {
+    Literal(String),
+    Expression(Expression),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Expression {
+    operator: Operator,
+    variables: Vec<VarSpec>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct VarSpec {
+    name: String,
+    prefix: Option<usize>,
+    explode: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Operator {
+    Simple,
+    Reserved,
+    Fragment,
+    Label,
+    Path,
+    PathParameter,
+    Query,
+    QueryContinuation,
+}
+
+impl Operator {
+    fn from_char(c: char) -> Option<Self> {
+        Some(match c {
+            '+' => Operator::Reserved,
+            '#' => Operator::Fragment,
+            '.' => Operator::Label,
+            '/' => Operator::Path,
+            ';' => Operator::PathParameter,
+            '?' => Operator::Query,
+            '&' => Operator::QueryContinuation,
+            _ => return None,
+        })
+    }
+
+    fn first(self) -> &'static str {
+        match self {
+            Operator::Simple | Operator::Reserved => "",
+            Operator::Fragment => "#",
+            Operator::Label => ".",
+            Operator::Path => "/",
+            Operator::PathParameter => ";",
+            Operator::Query => "?",
+            Operator::QueryContinuation => "&",
+        }
+    }
+
+    fn separator(self) -> char {
+        match self {
+            Operator::Simple | Operator::Reserved | Operator::Fragment => ',',
+            Operator::Label => '.',
+            Operator::Path => '/',
+            Operator::PathParameter => ';',
+            Operator::Query | Operator::QueryContinuation => '&',
+        }
+    }
+
+    fn named(self) -> bool {
+        matches!(
+            self,
+            Operator::PathParameter | Operator::Query | Operator::QueryContinuation
+        )
+    }
+
+    fn if_empty(self) -> &'static str {
+        match self {
+            Operator::Query | Operator::QueryContinuation => "=",
+            _ => "",
+        }
+    }
+
+    fn allow_reserved(self) -> bool {
+        matches!(self, Operator::Reserved | Operator::Fragment)
+    }
+}
+
+impl UriTemplate {
+    pub fn parse(template: &str) -> Result<Self, UriTemplateError> {
+        let mut parts = Vec::new();
+        let mut literal = String::new();
+        let mut expression_start = None;
+        for (index, c) in template.char_indices() {
+            match (c, expression_start) {
+                ('{', None) => {
+                    if !literal.is_empty() {
+                        parts.push(Part::Literal(std::mem::take(&mut literal)));
+                    }
+                    expression_start = Some(index);
+                }
+                ('}', Some(start)) => {
+                    parts.push(Part::Expression(parse_expression(
+                        &template[start + 1..index],
+                    )?));
+                    expression_start = None;
+                }
+                ('}', None) => return Err(UriTemplateError::UnexpectedClose(index)),
+                (c, None) => literal.push(c),
+                (_, Some(_)) => {}
+            }
+        }
+        if let Some(start) = expression_start {
+            return Err(UriTemplateError::UnclosedExpression(start));
+        }
+        if !literal.is_empty() {
+            parts.push(Part::Literal(literal));
+        }
+        Ok(Self {
+            source: template.to_owned(),
+            parts,
+        })
+    }
+
+    /// The template as written.
+    pub fn as_str(&self) -> &str {
+        &self.source
+    }
+
+    /// The names of the variables, in order of first appearance.
+    pub fn variable_names(&self) -> Vec<&str> {
+        let mut names = Vec::new();
+        for part in &self.parts {
+            if let Part::Expression(expression) = part {
+                for variable in &expression.variables {
+                    if !names.contains(&variable.name.as_str()) {
+                        names.push(variable.name.as_str());
+                    }
+                }
+            }
+        }
+        names
+    }
+
+    pub fn has_variable(&self, name: &str) -> bool {
+        self.variable_names().contains(&name)
+    }
+
+    /// Expand the template, treating missing variables as undefined.
+    pub fn expand(&self, variables: &UriTemplateVariables) -> Result<String, UriTemplateError> {
+        let mut uri = String::with_capacity(self.source.len());
+        for part in &self.parts {
+            match part {
+                Part::Literal(literal) => uri.push_str(literal),
+                Part::Expression(expression) => expression.expand(variables, &mut uri)?,
+            }
+        }
+        Ok(uri)
+    }
+
+    /// Match a URI against the template, returning the percent-decoded variables.
+    ///
+    /// Expressions match greedily, from left to right. Lists are returned joined with `,`.
+    /// Undefined variables are absent from the result, and variables of simple (`{var}`) and
+    /// reserved (`{+var}`) expressions must be non-empty.
+    pub fn match_uri(&self, uri: &str) -> Option<HashMap<String, String>> {
+        let mut variables = HashMap::new();
+        match_parts(&self.parts, uri, &mut variables).then_some(variables)
+    }
+}
+
+impl FromStr for UriTemplate {
+    type Err = UriTemplateError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl fmt::Display for UriTemplate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.source)
+    }
+}
+
+impl RawResourceTemplate {
+    pub fn parse_uri_template(&self) -> Result<UriTemplate, UriTemplateError> {
+        UriTemplate::parse(&self.uri_template)
+    }
+
+    /// The names of the variables of the URI template.
+    pub fn variable_names(&self) -> Result<Vec<String>, UriTemplateError> {
+        Ok(self
+            .parse_uri_template()?
+            .variable_names()
+            .into_iter()
+            .map(str::to_owned)
+            .collect())
+    }
+
+    /// Expand the URI template into the URI of a resource.
+    pub fn expand(&self, variables: &UriTemplateVariables) -> Result<String, UriTemplateError> {
+        self.parse_uri_template()?.expand(variables)
+    }
+
+    /// The variables of `uri` if it matches the URI template, see [`UriTemplate::match_uri`].
+    pub fn match_uri(&self, uri: &str) -> Option<HashMap<String, String>> {
+        self.parse_uri_template().ok()?.match_uri(uri)
+    }
+}
+
+fn parse_expression(expression: &str) -> Result<Expression, UriTemplateError> {
+    let invalid = || UriTemplateError::InvalidExpression(expression.to_owned());
+    let (operator, variables) = match expression.chars().next() {
+        Some(c) => match Operator::from_char(c) {
+            Some(operator) => (operator, &expression[c.len_utf8()..]),
+            None => (Operator::Simple, expression),
+        },
+        None => return Err(invalid()),
+    };
+    let variables = variables
+        .split(',')
+        .map(|spec| {
+            let (spec, explode) = match spec.strip_suffix('*') {
+                Some(spec) => (spec, true),
+                None => (spec, false),
+            };
+            let (name, prefix) = match spec.split_once(':') {
+                Some((name, prefix)) => {
+                    if explode {
+                        return Err(UriTemplateError::InvalidModifier(name.to_owned()));
+                    }
+                    let prefix = (!prefix.is_empty()
+                        && prefix.len() <= 4
+                        && prefix.bytes().all(|b| b.is_ascii_digit())
+                        && !prefix.starts_with('0'))
+                    .then(|| prefix.parse::<usize>().ok())
+                    .flatten()
+                    .ok_or_else(invalid)?;
+                    (name, Some(prefix))
+                }
+                None => (spec, None),
+            };
+            if !is_valid_name(name) {
+                return Err(invalid());
+            }
+            Ok(VarSpec {
+                name: name.to_owned(),
+                prefix,
+                explode,
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(Expression {
+        operator,
+        variables,
+    })
+}
+
+fn is_valid_name(name: &str) -> bool {
+    let bytes = name.as_bytes();
+    if bytes.is_empty() || bytes[0] == b'.' || bytes[bytes.len() - 1] == b'.' {
+        return false;
+    }
+    let mut index = 0;
+    while index < bytes.len() {
+        match bytes[index] {
+            b'%' => {
+                if !bytes
+                    .get(index + 1..index + 3)
+                    .is_some_and(|hex| hex.iter().all(u8::is_ascii_hexdigit))
+                {
+                    return false;
+                }
+                index += 3;
+            }
+            b'.' if bytes[index - 1] == b'.' => return false,
+            b if b.is_ascii_alphanumeric() || b == b'_' || b == b'.' => index += 1,
+            _ => return false,
+        }
+    }
+    true
+}
+
+impl Expression {
+    fn expand(
+        &self,
+        variables: &UriTemplateVariables,
+        uri: &mut String,
+    ) -> Result<(), UriTemplateError> {
+        let operator = self.operator;
+        let separator = operator.separator();
+        let mut first = true;
+        for spec in &self.variables {
+            let Some(value) = variables.get(&spec.name) else {
+                continue;
+            };
+            let defined = match value {
+                UriTemplateValue::String(_) => true,
+                UriTemplateValue::List(items) => !items.is_empty(),
+                UriTemplateValue::Map(entries) => !entries.is_empty(),
+            };
+            if !defined {
+                continue;
+            }
+            if spec.prefix.is_some() && !matches!(value, UriTemplateValue::String(_)) {
+                return Err(UriTemplateError::PrefixOfComposite(spec.name.clone()));
+            }
+            if first {
+                uri.push_str(operator.first());
+                first = false;
+            } else {
+                uri.push(separator);
+            }
+            let encode =
+                |uri: &mut String, value: &str| encode_into(uri, value, operator.allow_reserved());
+            let named = |uri: &mut String, name: &str, value: &str| {
+                encode(uri, name);
+                if value.is_empty() {
+                    uri.push_str(operator.if_empty());
+                } else {
+                    uri.push('=');
+                    encode(uri, value);
+                }
+            };
+            match value {
+                UriTemplateValue::String(value) => {
+                    let value = match spec.prefix {
+                        Some(prefix) => match value.char_indices().nth(prefix) {
+                            Some((end, _)) => &value[..end],
+                            None => value.as_str(),
+                        },
+                        None => value.as_str(),
+                    };
+                    if operator.named() {
+                        named(uri, &spec.name, value);
+                    } else {
+                        encode(uri, value);
+                    }
+                }
+                UriTemplateValue::List(items) if spec.explode => {
+                    for (index, item) in items.iter().enumerate() {
+                        if index > 0 {
+                            uri.push(separator);
+                        }
+                        if operator.named() {
+                            named(uri, &spec.name, item);
+                        } else {
+                            encode(uri, item);
+                        }
+                    }
+                }
+                UriTemplateValue::Map(entries) if spec.explode => {
+                    for (index, (key, value)) in entries.iter().enumerate() {
+                        if index > 0 {
+                            uri.push(separator);
+                        }
+                        if operator.named() {
+                            named(uri, key, value);
+                        } else {
+                            encode(uri, key);
+                            uri.push('=');
+                            encode(uri, value);
+                        }
+                    }
+                }
+                UriTemplateValue::List(_) | UriTemplateValue::Map(_) => {
+                    if operator.named() {
+                        encode(uri, &spec.name);
+                        uri.push('=');
+                    }
+                    let items: Vec<&str> = match value {
+                        UriTemplateValue::List(items) => items.iter().map(String::as_str).collect(),
+                        UriTemplateValue::Map(entries) => entries
+                            .iter()
+                            .flat_map(|(key, value)| [key.as_str(), value.as_str()])
+                            .collect(),
+                        UriTemplateValue::String(_) => unreachable!(),
+                    };
+                    for (index, item) in items.into_iter().enumerate() {
+                        if index > 0 {
+                            uri.push(',');
+                        }
+                        encode(uri, item);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether `c` can appear in the expansion, after the operator prefix.
+    fn allows(&self, c: char) -> bool {
+        if is_unreserved(c) || c == '%' || c == ',' || self.operator.allow_reserved() {
+            return true;
+        }
+        let explode = self.variables.iter().any(|spec| spec.explode);
+        match self.operator {
+            Operator::Path => c == '/' || (explode && c == '='),
+            Operator::PathParameter => c == ';' || c == '=',
+            Operator::Query | Operator::QueryContinuation => c == '&' || c == '=',
+            _ => explode && c == '=',
+        }
+    }
+
+    /// Assign the pieces of a matched expansion to the variables.
+    fn assign(&self, expansion: &str, variables: &mut HashMap<String, String>) -> bool {
+        let operator = self.operator;
+        let Some(expansion) = expansion.strip_prefix(operator.first()) else {
+            return expansion.is_empty();
+        };
+        let separator = operator.separator();
+        if operator.named() {
+            let exploded = self.variables.iter().find(|spec| spec.explode);
+            for piece in expansion.split(separator) {
+                let (name, value) = piece.split_once('=').unwrap_or((piece, ""));
+                let (name, value) = match self.variables.iter().find(|spec| spec.name == name) {
+                    Some(spec) => (spec, value.to_owned()),
+                    None => match exploded {
+                        Some(spec) => (spec, piece.to_owned()),
+                        None => return false,
+                    },
+                };
+                if !insert(variables, name, &value) {
+                    return false;
+                }
+            }
+            return true;
+        }
+        let pieces = expansion
+            .splitn(self.variables.len(), separator)
+            .collect::<Vec<_>>();
+        for (spec, piece) in self.variables.iter().zip(pieces) {
+            let value = if spec.explode {
+                piece.replace(separator, ",")
+            } else if separator != ',' && piece.contains(separator) {
+                // only a list joins its items with `,`
+                return false;
+            } else {
+                piece.to_owned()
+            };
+            if !insert(variables, spec, &value) {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+/// Insert a percent-encoded value, appending to the value of an exploded variable.
+fn insert(variables: &mut HashMap<String, String>, spec: &VarSpec, value: &str) -> bool {
+    let value = percent_decode(value);
+    if spec
+        .prefix
+        .is_some_and(|prefix| value.chars().count() > prefix)
+    {
+        return false;
+    }
+    match variables.get_mut(&spec.name) {
+        Some(existing) if spec.explode => {
+            existing.push(',');
+            existing.push_str(&value);
+            true
+        }
+        // a variable repeated in the template must match the same value
+        Some(existing) => *existing == value,
+        None => {
+            variables.insert(spec.name.clone(), value);
+            true
+        }
+    }
+}
+
+fn match_parts(parts: &[Part], uri: &str, variables: &mut HashMap<String, String>) -> bool {
+    match parts.split_first() {
+        None => uri.is_empty(),
+        Some((Part::Literal(literal), rest)) => uri
+            .strip_prefix(literal.as_str())
+            .is_some_and(|uri| match_parts(rest, uri, variables)),
+        Some((Part::Expression(expression), rest)) => {
+            let first = expression.operator.first();
+            let start = if !first.is_empty() && uri.starts_with(first) {
+                first.len()
+            } else {
+                0
+            };
+            let max = uri[start..]
+                .char_indices()
+                .find(|(_, c)| !expression.allows(*c))
+                .map_or(uri.len(), |(index, _)| start + index);
+            let min = if first.is_empty() { 1 } else { 0 };
+            // the longest expansion first, backtracking until the rest of the template matches
+            let mut ends = uri[..max]
+                .char_indices()
+                .map(|(index, _)| index)
+                .chain(std::iter::once(max))
+                .filter(|end| *end >= min && (*end == 0 || *end > start))
+                .collect::<Vec<_>>();
+            ends.reverse();
+            for end in ends {
+                let mut candidate = variables.clone();
+                if expression.assign(&uri[..end], &mut candidate)
+                    && match_parts(rest, &uri[end..], &mut candidate)
+                {
+                    *variables = candidate;
+                    return true;
+                }
+            }
+            false
+        }
+    }
+}
+
+fn is_unreserved(c: char) -> bool {
+    c.is_ascii_alphanumeric() || matches!(c, '-' | '.' | '_' | '~')
+}
+
+fn is_reserved(c: char) -> bool {
+    matches!(
+        c,
+        ':' | '/'
+            | '?'
+            | '#'
+            | '['
+            | ']'
+            | '@'
+            | '!'
+            | '$'
+            | '&'
+            | '\''
+            | '('
+            | ')'
+            | '*'
+            | '+'
+            | ','
+            | ';'
+            | '='
+    )
+}
+
+fn encode_into(uri: &mut String, value: &str, allow_reserved: bool) {
+    let bytes = value.as_bytes();
+    for (index, c) in value.char_indices() {
+        let pct_encoded = c == '%'
+            && bytes
+                .get(index + 1..index + 3)
+                .is_some_and(|hex| hex.iter().all(u8::is_ascii_hexdigit));
+        if is_unreserved(c) || (allow_reserved && (is_reserved(c) || pct_encoded)) {
+            uri.push(c);
+        } else {
+            let mut buffer = [0; 4];
+            for byte in c.encode_utf8(&mut buffer).bytes() {
+                uri.push_str(&format!("%{byte:02X}"));
+            }
+        }
+    }
+}
+
+fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        let escaped = (bytes[index] == b'%')
+            .then(|| value.get(index + 1..index + 3))
+            .flatten()
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match escaped {
+            Some(byte) => {
+                decoded.push(byte);
+                index += 3;
+            }
+            None => {
+                decoded.push(bytes[index]);
+                index += 1;
+            }
+        }
+    }
+    String::from_utf8(decoded).unwrap_or_else(|_| value.to_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn variables() -> UriTemplateVariables {
+        // the example variables of RFC 6570, section 3.2
+        HashMap::from([
+            (
+                "count".to_owned(),
+                UriTemplateValue::List(vec!["one".into(), "two".into(), "three".into()]),
+            ),
+            (
+                "dom".to_owned(),
+                UriTemplateValue::List(vec!["example".into(), "com".into()]),
+            ),
+            ("dub".to_owned(), "me/too".into()),
+            ("hello".to_owned(), "Hello World!".into()),
+            ("half".to_owned(), "50%".into()),
+            ("var".to_owned(), "value".into()),
+            ("who".to_owned(), "fred".into()),
+            ("base".to_owned(), "http://example.com/home/".into()),
+            ("path".to_owned(), "/foo/bar".into()),
+            (
+                "list".to_owned(),
+                UriTemplateValue::List(vec!["red".into(), "green".into(), "blue".into()]),
+            ),
+            (
+                "keys".to_owned(),
+                UriTemplateValue::Map(vec![
+                    ("semi".into(), ";".into()),
+                    ("dot".into(), ".".into()),
+                    ("comma".into(), ",".into()),
+                ]),
+            ),
+            ("v".to_owned(), "6".into()),
+            ("x".to_owned(), "1024".into()),
+            ("y".to_owned(), "768".into()),
+            ("empty".to_owned(), "".into()),
+            (
+                "empty_keys".to_owned(),
+                Vec::<(String, String)>::new().into(),
+            ),
+        ])
+    }
+
+    #[test]
+    fn test_expand() {
+        let variables = variables();
+        for (template, expected) in [
+            ("{var}", "value"),
+            ("{hello}", "Hello%20World%21"),
+            ("{half}", "50%25"),
+            ("O{empty}X", "OX"),
+            ("O{undef}X", "OX"),
+            ("{x,y}", "1024,768"),
+            ("{x,hello,y}", "1024,Hello%20World%21,768"),
+            ("?{x,empty}", "?1024,"),
+            ("{var:3}", "val"),
+            ("{var:30}", "value"),
+            ("{list}", "red,green,blue"),
+            ("{list*}", "red,green,blue"),
+            ("{keys}", "semi,%3B,dot,.,comma,%2C"),
+            ("{keys*}", "semi=%3B,dot=.,comma=%2C"),
+            ("{+var}", "value"),
+            ("{+hello}", "Hello%20World!"),
+            ("{+half}", "50%25"),
+            ("{base}index", "http%3A%2F%2Fexample.com%2Fhome%2Findex"),
+            ("{+base}index", "http://example.com/home/index"),
+            ("{+path}/here", "/foo/bar/here"),
+            ("{+path:6}/here", "/foo/b/here"),
+            ("{+keys*}", "semi=;,dot=.,comma=,"),
+            ("{#var}", "#value"),
+            ("{#hello}", "#Hello%20World!"),
+            ("{#path,x}/here", "#/foo/bar,1024/here"),
+            ("{#list*}", "#red,green,blue"),
+            ("X{.var}", "X.value"),
+            ("X{.x,y}", "X.1024.768"),
+            ("www{.dom*}", "www.example.com"),
+            ("X{.list*}", "X.red.green.blue"),
+            ("{/who,who}", "/fred/fred"),
+            ("{/var,empty}", "/value/"),
+            ("{/var,x}/here", "/value/1024/here"),
+            ("{/list*,path:4}", "/red/green/blue/%2Ffoo"),
+            ("{/keys*}", "/semi=%3B/dot=./comma=%2C"),
+            ("{;x,y,empty}", ";x=1024;y=768;empty"),
+            ("{;v,empty,who}", ";v=6;empty;who=fred"),
+            ("{;list*}", ";list=red;list=green;list=blue"),
+            ("{;keys*}", ";semi=%3B;dot=.;comma=%2C"),
+            ("{?x,y,empty}", "?x=1024&y=768&empty="),
+            ("{?var:3}", "?var=val"),
+            ("{?list}", "?list=red,green,blue"),
+            ("{?list*}", "?list=red&list=green&list=blue"),
+            ("{?keys*}", "?semi=%3B&dot=.&comma=%2C"),
+            ("{?undef,empty_keys}", ""),
+            ("?fixed=yes{&x}", "?fixed=yes&x=1024"),
+            ("{&var:3}", "&var=val"),
+            ("{count}/{dub}", "one,two,three/me%2Ftoo"),
+        ] {
+            let uri = UriTemplate::parse(template)
+                .unwrap()
+                .expand(&variables)
+                .unwrap();
+            assert_eq!(uri, expected, "{template}");
+        }
+        assert_eq!(
+            UriTemplate::parse("{list:3}").unwrap().expand(&variables),
+            Err(UriTemplateError::PrefixOfComposite("list".to_owned()))
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        for (template, error) in [
+            ("users://{id", UriTemplateError::UnclosedExpression(8)),
+            ("users://id}", UriTemplateError::UnexpectedClose(10)),
+            ("{a}{b", UriTemplateError::UnclosedExpression(3)),
+            ("{}", UriTemplateError::InvalidExpression("".to_owned())),
+            (
+                "{a b}",
+                UriTemplateError::InvalidExpression("a b".to_owned()),
+            ),
+            (
+                "{var:0}",
+                UriTemplateError::InvalidExpression("var:0".to_owned()),
+            ),
+            (
+                "{var:10000}",
+                UriTemplateError::InvalidExpression("var:10000".to_owned()),
+            ),
+            (
+                "{=var}",
+                UriTemplateError::InvalidExpression("=var".to_owned()),
+            ),
+            (
+                "{var:3*}",
+                UriTemplateError::InvalidModifier("var".to_owned()),
+            ),
+        ] {
+            assert_eq!(UriTemplate::parse(template), Err(error), "{template}");
+        }
+        let template = UriTemplate::parse("{a.b,c%20d}{?a.b}").unwrap();
+        assert_eq!(template.variable_names(), ["a.b", "c%20d"]);
+    }
+
+    #[test]
+    fn test_match() {
+        let matches =
+            |template: &str, uri: &str| UriTemplate::parse(template).unwrap().match_uri(uri);
+        let variables = matches(
+            "users://{id}/posts/{post}",
+            "users://42/posts/hello%20world",
+        )
+        .unwrap();
+        assert_eq!(variables["id"], "42");
+        assert_eq!(variables["post"], "hello world");
+        assert!(matches("users://{id}/posts/{post}", "users://42/posts/").is_none());
+        assert!(matches("users://{id}/posts/{post}", "users://4/2/posts/1").is_none());
+
+        let variables = matches("file:///{+path}", "file:///docs/readme.md").unwrap();
+        assert_eq!(variables["path"], "docs/readme.md");
+        assert!(matches("file:///{+path}", "http:///docs").is_none());
+
+        let template = "search{?q,lang,page}";
+        let variables = matches(template, "search?lang=en&q=rust%20mcp").unwrap();
+        assert_eq!(variables.len(), 2);
+        assert_eq!(variables["q"], "rust mcp");
+        assert_eq!(variables["lang"], "en");
+        assert!(matches(template, "search").unwrap().is_empty());
+        assert!(matches(template, "search?unknown=1").is_none());
+
+        let variables = matches("repos{/owner,repo}{/path*}", "repos/rmcp/sdk/src/lib.rs").unwrap();
+        assert_eq!(variables["owner"], "rmcp");
+        assert_eq!(variables["repo"], "sdk");
+        assert_eq!(variables["path"], "src,lib.rs");
+
+        let variables = matches("{name}.{ext}", "archive.tar.gz").unwrap();
+        assert_eq!(variables["name"], "archive.tar");
+        assert_eq!(variables["ext"], "gz");
+        let variables = matches("{name}{.ext}", "archive.tar").unwrap();
+        assert_eq!(variables["name"], "archive.tar");
+        assert!(!variables.contains_key("ext"));
+
+        let variables = matches("tags{?tag*}", "tags?tag=a&tag=b").unwrap();
+        assert_eq!(variables["tag"], "a,b");
+
+        assert!(matches("{code:2}", "abc").is_none());
+        assert!(matches("{who}/{who}", "fred/bob").is_none());
+        assert_eq!(matches("{who}/{who}", "fred/fred").unwrap()["who"], "fred");
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let variables = variables();
+        for template in ["{var}/{+path}", "{/var,x}{?y,hello}", "X{.var}{#who}"] {
+            let template = UriTemplate::parse(template).unwrap();
+            let uri = template.expand(&variables).unwrap();
+            let matched = template.match_uri(&uri).unwrap();
+            for name in template.variable_names() {
+                let UriTemplateValue::String(value) = &variables[name] else {
+                    unreachable!()
+                };
+                assert_eq!(&matched[name], value, "{template}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_resource_template() {
+        let template = RawResourceTemplate {
+            uri_template: "users://{id}{?fields}".to_owned(),
+            name: "user".to_owned(),
+            title: None,
+            description: None,
+            mime_type: None,
+            icons: None,
+        };
+        assert_eq!(template.variable_names().unwrap(), ["id", "fields"]);
+        let uri = template
+            .expand(&HashMap::from([("id".to_owned(), "7".into())]))
+            .unwrap();
+        assert_eq!(uri, "users://7");
+        assert_eq!(
+            template.match_uri("users://7?fields=name").unwrap()["fields"],
+            "name"
+        );
+    }
+}
@@ -0,0 +1,315 @@
+//! Downgrading messages to older protocol revisions.
+//!
+//! The model follows the latest protocol revision. Before a message is sent to a peer which
+//! negotiated a revision older than the one the service declared, [`Downgrade::downgrade`]
+//! strips or transforms what that revision does not know about:
+//!
+//! | introduced in | stripped or transformed |
+//! |---------------|-------------------------|
+//! | `2025-03-26` | tool `annotations`, progress `message`, `completions` capability |
+//! | `2025-06-18` | `title` of tools, prompts, prompt arguments, resources, templates and implementations; tool `outputSchema`; `structuredContent`, serialized into a text content if the result has no other content; `resource_link` content, replaced by a text content with its URI |
+//! | `2025-11-25` | `icons`, implementation `websiteUrl`, `task` of requests, `tasks` capability |
+//!
+//! Requests and notifications which do not exist in a revision are not touched, use strict mode
+//! (see [`RequestPolicy::with_strict`](crate::service::RequestPolicy::with_strict)) to keep them
+//! from being sent.
+use super::*;
+
+/// Strip or transform what a protocol revision does not support.
+pub trait Downgrade {
+    fn downgrade(&mut self, version: &ProtocolVersion);
+}
+
+fn before(version: &ProtocolVersion, introduced: ProtocolVersion) -> bool {
+    *version < introduced
+}
+
+impl<T: Downgrade> Downgrade for Vec<T> {
+    fn downgrade(&mut self, version: &ProtocolVersion) {
+        self.iter_mut().for_each(|item| item.downgrade(version));
+    }
+}
+
+impl<T: Downgrade> Downgrade for Option<T> {
+    fn downgrade(&mut self, version: &ProtocolVersion) {
+        if let Some(inner) = self {
+            inner.downgrade(version);
+        }
+    }
+}
+
+impl<T: AnnotateAble + Downgrade> Downgrade for Annotated<T> {
+    fn downgrade(&mut self, version: &ProtocolVersion) {
+        self.raw.downgrade(version);
+    }
+}
+
+impl Downgrade for Implementation {
+    fn downgrade(&mut self, version: &ProtocolVersion) {
+        if before(version, ProtocolVersion::V_2025_06_18) {
+            self.title = None;
+        }
+        if before(version, ProtocolVersion::V_2025_11_25) {
+            self.icons = None;
+            self.website_url = None;
+        }
+    }
+}
+
+impl Downgrade for InitializeResult {
+    fn downgrade(&mut self, version: &ProtocolVersion) {
+        self.server_info.downgrade(version);
+        if before(version, ProtocolVersion::V_2025_03_26) {
+            self.capabilities.completions = None;
+        }
+        if before(version, ProtocolVersion::V_2025_11_25) {
+            self.capabilities.tasks = None;
+        }
+    }
+}
+
+impl Downgrade for Tool {
+    fn downgrade(&mut self, version: &ProtocolVersion) {
+        if before(version, ProtocolVersion::V_2025_03_26) {
+            self.annotations = None;
+        }
+        if before(version, ProtocolVersion::V_2025_06_18) {
+            self.title = None;
+            self.output_schema = None;
+        }
+        if before(version, ProtocolVersion::V_2025_11_25) {
+            self.icons = None;
+        }
+    }
+}
+
+impl Downgrade for Prompt {
+    fn downgrade(&mut self, version: &ProtocolVersion) {
+        if before(version, ProtocolVersion::V_2025_06_18) {
+            self.title = None;
+        }
+        if before(version, ProtocolVersion::V_2025_11_25) {
+            self.icons = None;
+        }
+        self.arguments.downgrade(version);
+    }
+}
+
+impl Downgrade for PromptArgument {
+    fn downgrade(&mut self, version: &ProtocolVersion) {
+        if before(version, ProtocolVersion::V_2025_06_18) {
+            self.title = None;
+        }
+    }
+}
+
+impl Downgrade for RawResource {
+    fn downgrade(&mut self, version: &ProtocolVersion) {
+        if before(version, ProtocolVersion::V_2025_06_18) {
+            self.title = None;
+        }
+        if before(version, ProtocolVersion::V_2025_11_25) {
+            self.icons = None;
+        }
+    }
+}
+
+impl Downgrade for RawResourceTemplate {
+    fn downgrade(&mut self, version: &ProtocolVersion) {
+        if before(version, ProtocolVersion::V_2025_06_18) {
+            self.title = None;
+        }
+        if before(version, ProtocolVersion::V_2025_11_25) {
+            self.icons = None;
+        }
+    }
+}
+
+impl Downgrade for RawContent {
+    fn downgrade(&mut self, version: &ProtocolVersion) {
+        if let RawContent::ResourceLink(link) = self {
+            if before(version, ProtocolVersion::V_2025_06_18) {
+                *self = RawContent::text(std::mem::take(&mut link.uri));
+            } else {
+                link.downgrade(version);
+            }
+        }
+    }
+}
+
+impl Downgrade for PromptMessageContent {
+    fn downgrade(&mut self, version: &ProtocolVersion) {
+        if let PromptMessageContent::ResourceLink { link } = self {
+            if before(version, ProtocolVersion::V_2025_06_18) {
+                *self = PromptMessageContent::text(std::mem::take(&mut link.raw.uri));
+            } else {
+                link.downgrade(version);
+            }
+        }
+    }
+}
+
+impl Downgrade for CallToolResult {
+    fn downgrade(&mut self, version: &ProtocolVersion) {
+        if before(version, ProtocolVersion::V_2025_06_18) {
+            if let Some(structured_content) = self.structured_content.take() {
+                if self.content.is_empty() {
+                    self.content
+                        .push(Content::text(structured_content.to_string()));
+                }
+            }
+        }
+        self.content.downgrade(version);
+    }
+}
+
+impl Downgrade for GetPromptResult {
+    fn downgrade(&mut self, version: &ProtocolVersion) {
+        self.messages
+            .iter_mut()
+            .for_each(|message| message.content.downgrade(version));
+    }
+}
+
+impl Downgrade for CreateMessageResult {
+    fn downgrade(&mut self, version: &ProtocolVersion) {
+        self.message.content.downgrade(version);
+    }
+}
+
+impl Downgrade for ProgressNotificationParam {
+    fn downgrade(&mut self, version: &ProtocolVersion) {
+        if before(version, ProtocolVersion::V_2025_03_26) {
+            self.message = None;
+        }
+    }
+}
+
+impl Downgrade for ClientRequest {
+    fn downgrade(&mut self, version: &ProtocolVersion) {
+        if let ClientRequest::CallToolRequest(request) = self {
+            if before(version, ProtocolVersion::V_2025_11_25) {
+                request.params.task = None;
+            }
+        }
+    }
+}
+
+impl Downgrade for ServerRequest {
+    fn downgrade(&mut self, version: &ProtocolVersion) {
+        if let ServerRequest::CreateMessageRequest(request) = self {
+            if before(version, ProtocolVersion::V_2025_11_25) {
+                request.params.task = None;
+            }
+        }
+    }
+}
+
+impl Downgrade for ClientResult {
+    fn downgrade(&mut self, version: &ProtocolVersion) {
+        if let ClientResult::CreateMessageResult(result) = self {
+            result.downgrade(version);
+        }
+    }
+}
+
+impl Downgrade for ServerResult {
+    fn downgrade(&mut self, version: &ProtocolVersion) {
+        match self {
+            ServerResult::InitializeResult(result) => result.downgrade(version),
+            ServerResult::GetPromptResult(result) => result.downgrade(version),
+            ServerResult::ListPromptsResult(result) => result.prompts.downgrade(version),
+            ServerResult::ListResourcesResult(result) => result.resources.downgrade(version),
+            ServerResult::ListResourceTemplatesResult(result) => {
+                result.resource_templates.downgrade(version)
+            }
+            ServerResult::CallToolResult(result) => result.downgrade(version),
+            ServerResult::ListToolsResult(result) => result.tools.downgrade(version),
+            _ => {}
+        }
+    }
+}
+
+impl Downgrade for ClientNotification {
+    fn downgrade(&mut self, version: &ProtocolVersion) {
+        if let ClientNotification::ProgressNotification(notification) = self {
+            notification.params.downgrade(version);
+        }
+    }
+}
+
+impl Downgrade for ServerNotification {
+    fn downgrade(&mut self, version: &ProtocolVersion) {
+        if let ServerNotification::ProgressNotification(notification) = self {
+            notification.params.downgrade(version);
+        }
+    }
+}
+
+impl<Req: Downgrade, Resp: Downgrade, Not: Downgrade> Downgrade for JsonRpcMessage<Req, Resp, Not> {
+    fn downgrade(&mut self, version: &ProtocolVersion) {
+        match self {
+            JsonRpcMessage::Request(request) => request.request.downgrade(version),
+            JsonRpcMessage::Response(response) => response.result.downgrade(version),
+            JsonRpcMessage::Notification(notification) => {
+                notification.notification.downgrade(version)
+            }
+            JsonRpcMessage::Error(_) => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_downgrade_call_tool_result() {
+        let mut result = CallToolResult::structured(json!({ "sum": 3 }));
+        result.downgrade(&ProtocolVersion::V_2025_06_18);
+        assert!(result.structured_content.is_some());
+
+        result.downgrade(&ProtocolVersion::V_2025_03_26);
+        assert!(result.structured_content.is_none());
+        assert_eq!(result.content.len(), 1);
+
+        let mut result = CallToolResult {
+            content: vec![Content::resource_link(RawResource::new(
+                "file:///a.txt",
+                "a",
+            ))],
+            structured_content: Some(json!({ "sum": 3 })),
+            is_error: None,
+            meta: None,
+        };
+        result.downgrade(&ProtocolVersion::V_2024_11_05);
+        assert_eq!(
+            serde_json::to_value(&result).unwrap(),
+            json!({ "content": [{ "type": "text", "text": "file:///a.txt" }] })
+        );
+    }
+
+    #[test]
+    fn test_downgrade_tool() {
+        let mut tool = Tool {
+            title: Some("Sum".into()),
+            output_schema: Some(Default::default()),
+            annotations: Some(ToolAnnotations::new()),
+            icons: Some(vec![]),
+            ..Tool::new("sum", "Sum two numbers", JsonObject::default())
+        };
+        let mut latest = tool.clone();
+        latest.downgrade(&ProtocolVersion::V_2025_11_25);
+        assert_eq!(latest, tool);
+
+        tool.downgrade(&ProtocolVersion::V_2025_06_18);
+        assert!(tool.icons.is_none() && tool.title.is_some());
+        tool.downgrade(&ProtocolVersion::V_2025_03_26);
+        assert!(tool.title.is_none() && tool.output_schema.is_none());
+        assert!(tool.annotations.is_some());
+        tool.downgrade(&ProtocolVersion::V_2024_11_05);
+        assert!(tool.annotations.is_none());
+    }
+}
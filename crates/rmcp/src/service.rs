@@ -4,9 +4,10 @@ use thiserror::Error;
 use crate::{
     error::ErrorData as McpError,
     model::{
-        CancelledNotification, CancelledNotificationParam, Extensions, GetExtensions, GetMeta,
-        JsonRpcError, JsonRpcMessage, JsonRpcNotification, JsonRpcRequest, JsonRpcResponse, Meta,
-        NumberOrString, ProgressToken, RequestId, ServerJsonRpcMessage,
+        CancelledNotification, CancelledNotificationParam, CustomMethod, CustomNotification,
+        CustomRequest, Downgrade, Extensions, GetExtensions, GetMeta, GetMethod, JsonRpcError,
+        JsonRpcMessage, JsonRpcNotification, JsonRpcRequest, JsonRpcResponse, Meta, NumberOrString,
+        ProgressToken, ProtocolVersion, RequestId, ServerJsonRpcMessage,
     },
     transport::{DynamicTransportError, IntoTransport, Transport},
 };
@@ -22,6 +23,18 @@ mod server;
 #[cfg(feature = "server")]
 #[cfg_attr(docsrs, doc(cfg(feature = "server")))]
 pub use server::*;
+mod middleware;
+pub use middleware::*;
+mod policy;
+pub use policy::*;
+mod strict;
+pub use strict::{DeclaredCapabilities, ProtocolViolation};
+#[cfg(feature = "logging")]
+#[cfg_attr(docsrs, doc(cfg(feature = "logging")))]
+pub mod logging;
+#[cfg(feature = "otel")]
+#[cfg_attr(docsrs, doc(cfg(feature = "otel")))]
+pub mod otel;
 #[cfg(feature = "tower")]
 #[cfg_attr(docsrs, doc(cfg(feature = "tower")))]
 mod tower;
@@ -45,6 +58,18 @@ pub enum ServiceError {
     Cancelled { reason: Option<String> },
     #[error("request timeout after {}", chrono::Duration::from_std(*timeout).unwrap_or_default())]
     Timeout { timeout: Duration },
+    #[error("request failed after {attempts} attempts: {last}")]
+    RetriesExhausted {
+        attempts: u32,
+        last: Box<ServiceError>,
+    },
+    #[error("Protocol violation: {0}")]
+    ProtocolViolation(ProtocolViolation),
+    #[error("Invalid custom message payload: {0}")]
+    CustomPayload(serde_json::Error),
+    /// A tool result which doesn't match the tool definition, or can't be decoded
+    #[error("Invalid tool result: {0}")]
+    InvalidToolResult(McpError),
 }
 
 trait TransferObject:
@@ -65,22 +90,25 @@ impl<T> TransferObject for T where
 
 #[allow(private_bounds, reason = "there's no the third implementation")]
 pub trait ServiceRole: std::fmt::Debug + Send + Sync + 'static + Copy + Clone {
-    type Req: TransferObject + GetMeta + GetExtensions;
-    type Resp: TransferObject;
+    type Req: TransferObject + GetMeta + GetExtensions + GetMethod + Downgrade;
+    type Resp: TransferObject + Downgrade;
     type Not: TryInto<CancelledNotification, Error = Self::Not>
         + From<CancelledNotification>
-        + TransferObject;
-    type PeerReq: TransferObject + GetMeta + GetExtensions;
+        + TransferObject
+        + GetMethod
+        + Downgrade;
+    type PeerReq: TransferObject + GetMeta + GetExtensions + GetMethod;
     type PeerResp: TransferObject;
     type PeerNot: TryInto<CancelledNotification, Error = Self::PeerNot>
         + From<CancelledNotification>
         + TransferObject
         + GetMeta
-        + GetExtensions;
+        + GetExtensions
+        + GetMethod;
     type InitializeError;
     const IS_CLIENT: bool;
-    type Info: TransferObject;
-    type PeerInfo: TransferObject;
+    type Info: TransferObject + DeclaredCapabilities;
+    type PeerInfo: TransferObject + DeclaredCapabilities;
 }
 
 pub type TxJsonRpcMessage<R> =
@@ -112,6 +140,16 @@ pub trait ServiceExt<R: ServiceRole>: Service<R> + Sized {
     fn into_dyn(self) -> Box<dyn DynService<R>> {
         Box::new(self)
     }
+    /// Wrap this service with a [`Middleware`]
+    ///
+    /// Middlewares can be stacked by calling this method repeatedly, the last one added sees
+    /// the messages first.
+    fn with_middleware<M>(self, middleware: M) -> MiddlewareService<Self, M>
+    where
+        M: Middleware<R>,
+    {
+        MiddlewareService::new(self, middleware)
+    }
     fn serve<T, E, A>(
         self,
         transport: T,
@@ -123,11 +161,37 @@ pub trait ServiceExt<R: ServiceRole>: Service<R> + Sized {
     {
         Self::serve_with_ct(self, transport, Default::default())
     }
+    /// Serve with a [`RequestPolicy`] applied to the requests sent to the peer
+    fn serve_with_policy<T, E, A>(
+        self,
+        transport: T,
+        policy: RequestPolicy,
+    ) -> impl Future<Output = Result<RunningService<R, Self>, R::InitializeError>> + Send
+    where
+        T: IntoTransport<R, E, A>,
+        E: std::error::Error + Send + Sync + 'static,
+        Self: Sized,
+    {
+        Self::serve_with_ct_and_policy(self, transport, Default::default(), policy)
+    }
     fn serve_with_ct<T, E, A>(
         self,
         transport: T,
         ct: CancellationToken,
     ) -> impl Future<Output = Result<RunningService<R, Self>, R::InitializeError>> + Send
+    where
+        T: IntoTransport<R, E, A>,
+        E: std::error::Error + Send + Sync + 'static,
+        Self: Sized,
+    {
+        Self::serve_with_ct_and_policy(self, transport, ct, RequestPolicy::default())
+    }
+    fn serve_with_ct_and_policy<T, E, A>(
+        self,
+        transport: T,
+        ct: CancellationToken,
+        policy: RequestPolicy,
+    ) -> impl Future<Output = Result<RunningService<R, Self>, R::InitializeError>> + Send
     where
         T: IntoTransport<R, E, A>,
         E: std::error::Error + Send + Sync + 'static,
@@ -244,6 +308,8 @@ pub struct RequestHandle<R: ServiceRole> {
     pub peer: Peer<R>,
     pub id: RequestId,
     pub progress_token: ProgressToken,
+    /// Send `notifications/cancelled` to the peer if the request times out
+    pub cancel_on_timeout: bool,
 }
 
 impl<R: ServiceRole> RequestHandle<R> {
@@ -258,6 +324,9 @@ impl<R: ServiceRole> RequestHandle<R> {
                 Ok(response) => response,
                 Err(_) => {
                     let error = Err(ServiceError::Timeout { timeout });
+                    if !self.cancel_on_timeout {
+                        return error;
+                    }
                     // cancel this request
                     let notification = CancelledNotification {
                         params: CancelledNotificationParam {
@@ -291,6 +360,17 @@ impl<R: ServiceRole> RequestHandle<R> {
     }
 }
 
+/// The tool name of `request`, if it is a `tools/call` request.
+#[cfg(any(feature = "otel", feature = "metrics"))]
+pub(crate) fn request_tool_name<Req: std::any::Any>(request: &Req) -> Option<&str> {
+    match (request as &dyn std::any::Any).downcast_ref::<crate::model::ClientRequest>() {
+        Some(crate::model::ClientRequest::CallToolRequest(request)) => {
+            Some(request.params.name.as_ref())
+        }
+        _ => None,
+    }
+}
+
 #[derive(Debug)]
 pub(crate) enum PeerSinkMessage<R: ServiceRole> {
     Request {
@@ -304,6 +384,14 @@ pub(crate) enum PeerSinkMessage<R: ServiceRole> {
     },
 }
 
+/// The protocol version negotiated with the peer
+#[derive(Debug)]
+struct NegotiatedVersion {
+    version: ProtocolVersion,
+    /// Whether it is older than the declared version, so messages must be downgraded
+    downgrade: bool,
+}
+
 /// An interface to fetch the remote client or server
 ///
 /// For general purpose, call [`Peer::send_request`] or [`Peer::send_notification`] to send message to remote peer.
@@ -315,6 +403,11 @@ pub struct Peer<R: ServiceRole> {
     request_id_provider: Arc<dyn RequestIdProvider>,
     progress_token_provider: Arc<dyn ProgressTokenProvider>,
     info: Arc<tokio::sync::OnceCell<R::PeerInfo>>,
+    protocol_version: Arc<std::sync::OnceLock<NegotiatedVersion>>,
+    request_policy: Arc<RequestPolicy>,
+    notifications: tokio::sync::broadcast::Sender<R::PeerNot>,
+    #[cfg(all(feature = "client", feature = "schema-validation"))]
+    listed_tools: Arc<crate::handler::server::validation::ListedTools>,
 }
 
 impl<R: ServiceRole> std::fmt::Debug for Peer<R> {
@@ -330,6 +423,7 @@ type ProxyOutbound<R> = mpsc::Receiver<PeerSinkMessage<R>>;
 
 #[derive(Debug, Default)]
 pub struct PeerRequestOptions {
+    /// Overrides the timeout of the peer's [`RequestPolicy`]
     pub timeout: Option<Duration>,
     pub meta: Option<Meta>,
 }
@@ -342,9 +436,11 @@ impl PeerRequestOptions {
 
 impl<R: ServiceRole> Peer<R> {
     const CLIENT_CHANNEL_BUFFER_SIZE: usize = 1024;
+    const NOTIFICATION_BROADCAST_CAPACITY: usize = 256;
     pub(crate) fn new(
         request_id_provider: Arc<dyn RequestIdProvider>,
         peer_info: Option<R::PeerInfo>,
+        request_policy: RequestPolicy,
     ) -> (Peer<R>, ProxyOutbound<R>) {
         let (tx, rx) = mpsc::channel(Self::CLIENT_CHANNEL_BUFFER_SIZE);
         (
@@ -353,10 +449,94 @@ impl<R: ServiceRole> Peer<R> {
                 request_id_provider,
                 progress_token_provider: Arc::new(AtomicU32ProgressTokenProvider::default()),
                 info: Arc::new(tokio::sync::OnceCell::new_with(peer_info)),
+                protocol_version: Default::default(),
+                request_policy: Arc::new(request_policy),
+                notifications: tokio::sync::broadcast::channel(
+                    Self::NOTIFICATION_BROADCAST_CAPACITY,
+                )
+                .0,
+                #[cfg(all(feature = "client", feature = "schema-validation"))]
+                listed_tools: Default::default(),
             },
             rx,
         )
     }
+    /// The protocol version negotiated with the peer.
+    ///
+    /// Messages sent to the peer are [downgraded](Downgrade) to this version if it is older than
+    /// the one the service declared. This is `None` if the service was started without
+    /// initialization, e.g. with [`serve_directly`].
+    pub fn protocol_version(&self) -> Option<&ProtocolVersion> {
+        self.protocol_version
+            .get()
+            .map(|negotiated| &negotiated.version)
+    }
+    /// The version messages sent to the peer are downgraded to, if any.
+    pub(crate) fn downgraded_version(&self) -> Option<&ProtocolVersion> {
+        self.protocol_version
+            .get()
+            .filter(|negotiated| negotiated.downgrade)
+            .map(|negotiated| &negotiated.version)
+    }
+    pub(crate) fn set_protocol_version(
+        &self,
+        version: ProtocolVersion,
+        declared: &ProtocolVersion,
+    ) {
+        let downgrade = version < *declared;
+        if self
+            .protocol_version
+            .set(NegotiatedVersion { version, downgrade })
+            .is_err()
+        {
+            tracing::warn!("protocol version already negotiated");
+        }
+    }
+    pub fn request_policy(&self) -> &RequestPolicy {
+        &self.request_policy
+    }
+    /// A peer sending its requests with `policy` instead, e.g. to give a few calls a longer
+    /// timeout. The original peer is not affected.
+    pub fn with_request_policy(&self, policy: RequestPolicy) -> Self {
+        Self {
+            request_policy: Arc::new(policy),
+            ..self.clone()
+        }
+    }
+    /// A stream of every notification received from the peer from now on.
+    ///
+    /// Notifications are still passed to the service's handler, the stream gets a copy. A stream
+    /// which falls more than 256 notifications behind skips the oldest ones, and the stream ends
+    /// when the service stops.
+    pub fn subscribe_notifications(
+        &self,
+    ) -> impl futures::Stream<Item = R::PeerNot> + Send + 'static {
+        use tokio::sync::broadcast::error::RecvError;
+        let receiver = self.notifications.subscribe();
+        let tx = self.tx.clone();
+        futures::stream::unfold((receiver, tx), |(mut receiver, tx)| async move {
+            loop {
+                let received = tokio::select! {
+                    biased;
+                    received = receiver.recv() => received,
+                    // nothing is buffered anymore, and the service has stopped
+                    _ = tx.closed() => Err(RecvError::Closed),
+                };
+                match received {
+                    Ok(notification) => return Some((notification, (receiver, tx))),
+                    Err(RecvError::Lagged(skipped)) => {
+                        tracing::warn!(skipped, "notification stream lagged behind");
+                    }
+                    Err(RecvError::Closed) => return None,
+                }
+            }
+        })
+    }
+    pub(crate) fn broadcast_notification(&self, notification: &R::PeerNot) {
+        if self.notifications.receiver_count() > 0 {
+            let _ = self.notifications.send(notification.clone());
+        }
+    }
     pub async fn send_notification(&self, notification: R::Not) -> Result<(), ServiceError> {
         let (responder, receiver) = tokio::sync::oneshot::channel();
         self.tx
@@ -368,11 +548,43 @@ impl<R: ServiceRole> Peer<R> {
             .map_err(|_m| ServiceError::TransportClosed)?;
         receiver.await.map_err(|_e| ServiceError::TransportClosed)?
     }
+    /// Send a request and wait for its response, following the peer's [`RequestPolicy`].
+    ///
+    /// If the request is retried and still fails, the last error is returned in
+    /// [`ServiceError::RetriesExhausted`].
     pub async fn send_request(&self, request: R::Req) -> Result<R::PeerResp, ServiceError> {
-        self.send_request_with_option(request, PeerRequestOptions::no_options())
-            .await?
-            .await_response()
-            .await
+        let Some(retry) = self.request_policy.retry(request.method()).cloned() else {
+            return self
+                .send_request_with_option(request, PeerRequestOptions::no_options())
+                .await?
+                .await_response()
+                .await;
+        };
+        let mut attempts = 0;
+        loop {
+            attempts += 1;
+            let result = match self
+                .send_request_with_option(request.clone(), PeerRequestOptions::no_options())
+                .await
+            {
+                Ok(handle) => handle.await_response().await,
+                Err(error) => Err(error),
+            };
+            match result {
+                Err(error) if RetryPolicy::is_retryable(&error) => {
+                    if attempts > retry.max_retries {
+                        return Err(ServiceError::RetriesExhausted {
+                            attempts,
+                            last: Box::new(error),
+                        });
+                    }
+                    let backoff = retry.backoff(attempts);
+                    tracing::debug!(%error, attempts, ?backoff, "retry request");
+                    tokio::time::sleep(backoff).await;
+                }
+                result => return result,
+            }
+        }
     }
 
     pub async fn send_cancellable_request(
@@ -383,6 +595,33 @@ impl<R: ServiceRole> Peer<R> {
         self.send_request_with_option(request, options).await
     }
 
+    /// Send a request of the custom method `M` and deserialize its result.
+    pub async fn send_custom<M: CustomMethod>(
+        &self,
+        params: M::Params,
+    ) -> Result<M::Result, ServiceError>
+    where
+        R::Req: From<CustomRequest>,
+    {
+        let request = CustomRequest::typed::<M>(&params).map_err(ServiceError::CustomPayload)?;
+        let response = self.send_request(request.into()).await?;
+        // the result may have been parsed as any other result with the same shape
+        serde_json::to_value(response)
+            .and_then(serde_json::from_value)
+            .map_err(ServiceError::CustomPayload)
+    }
+    /// Send a notification of the custom method `M`.
+    pub async fn notify_custom<M: CustomMethod>(
+        &self,
+        params: M::Params,
+    ) -> Result<(), ServiceError>
+    where
+        R::Not: From<CustomNotification>,
+    {
+        let notification =
+            CustomNotification::typed::<M>(&params).map_err(ServiceError::CustomPayload)?;
+        self.send_notification(notification.into()).await
+    }
     pub async fn send_request_with_option(
         &self,
         mut request: R::Req,
@@ -390,12 +629,30 @@ impl<R: ServiceRole> Peer<R> {
     ) -> Result<RequestHandle<R>, ServiceError> {
         let id = self.request_id_provider.next_request_id();
         let progress_token = self.progress_token_provider.next_progress_token();
+        let method = request.method();
+        if self.request_policy.strict {
+            if let Err(violation) =
+                strict::check_outbound_request(method, self.peer_info(), R::IS_CLIENT)
+            {
+                tracing::warn!(method, %violation, "refuse to send request");
+                return Err(ServiceError::ProtocolViolation(violation));
+            }
+        }
+        let cancel_on_timeout = self.request_policy.cancel_on_timeout(method);
+        let options = PeerRequestOptions {
+            timeout: options
+                .timeout
+                .or_else(|| self.request_policy.timeout(method)),
+            ..options
+        };
         request
             .get_meta_mut()
             .set_progress_token(progress_token.clone());
         if let Some(meta) = options.meta.clone() {
             request.get_meta_mut().extend(meta);
         }
+        #[cfg(feature = "otel")]
+        otel::inject_current_context(request.get_meta_mut());
         let (responder, receiver) = tokio::sync::oneshot::channel();
         self.tx
             .send(PeerSinkMessage::Request {
@@ -411,6 +668,7 @@ impl<R: ServiceRole> Peer<R> {
             progress_token,
             options,
             peer: self.clone(),
+            cancel_on_timeout,
         })
     }
     pub fn peer_info(&self) -> Option<&R::PeerInfo> {
@@ -428,6 +686,16 @@ impl<R: ServiceRole> Peer<R> {
     pub fn is_transport_closed(&self) -> bool {
         self.tx.is_closed()
     }
+
+    /// Whether `other` is a handle to the same peer.
+    pub fn is_same_peer(&self, other: &Self) -> bool {
+        self.tx.same_channel(&other.tx)
+    }
+
+    /// Wait until the service talking to this peer has stopped.
+    pub async fn closed(&self) {
+        self.tx.closed().await
+    }
 }
 
 #[derive(Debug)]
@@ -625,7 +893,11 @@ where
     T: IntoTransport<R, E, A>,
     E: std::error::Error + Send + Sync + 'static,
 {
-    let (peer, peer_rx) = Peer::new(Arc::new(AtomicU32RequestIdProvider::default()), peer_info);
+    let (peer, peer_rx) = Peer::new(
+        Arc::new(AtomicU32RequestIdProvider::default()),
+        peer_info,
+        RequestPolicy::default(),
+    );
     serve_inner(service, transport.into_transport(), peer, peer_rx, ct)
 }
 
@@ -655,7 +927,12 @@ where
     let mut local_responder_pool =
         HashMap::<RequestId, Responder<Result<R::PeerResp, ServiceError>>>::new();
     let mut local_ct_pool = HashMap::<RequestId, CancellationToken>::new();
+    let mut protocol_state = peer
+        .request_policy()
+        .strict
+        .then(|| strict::ProtocolState::new(peer_info.is_some()));
     let shared_service = Arc::new(service);
+    let local_info = shared_service.get_info();
     // for return
     let service = shared_service.clone();
 
@@ -664,7 +941,16 @@ where
     let serve_loop_ct = ct.child_token();
     let peer_return: Peer<R> = peer.clone();
     let current_span = tracing::Span::current();
+    #[cfg(feature = "logging")]
+    let (current_span, forward_logs) = match logging::register_session(&peer) {
+        Some((span, forward_logs)) => (span, Some(forward_logs)),
+        None => (current_span, None),
+    };
     let handle = tokio::spawn(async move {
+        #[cfg(feature = "logging")]
+        if let Some(forward_logs) = forward_logs {
+            tokio::spawn(forward_logs);
+        }
         let mut transport = transport.into_transport();
         let mut batch_messages = VecDeque::<RxJsonRpcMessage<R>>::new();
         let mut send_task_set = tokio::task::JoinSet::<SendTaskResult>::new();
@@ -768,7 +1054,7 @@ where
                     }
                 }
                 // response and error
-                Event::ToSink(m) => {
+                Event::ToSink(mut m) => {
                     if let Some(id) = match &m {
                         JsonRpcMessage::Response(response) => Some(&response.id),
                         JsonRpcMessage::Error(error) => Some(&error.id),
@@ -777,6 +1063,9 @@ where
                         if let Some(ct) = local_ct_pool.remove(id) {
                             ct.cancel();
                         }
+                        if let Some(version) = peer.downgraded_version() {
+                            m.downgrade(version);
+                        }
                         let send = transport.send(m);
                         let current_span = tracing::Span::current();
                         tokio::spawn(async move {
@@ -788,10 +1077,23 @@ where
                     }
                 }
                 Event::ProxyMessage(PeerSinkMessage::Request {
-                    request,
+                    mut request,
                     id,
                     responder,
                 }) => {
+                    if let Some(version) = peer.downgraded_version() {
+                        request.downgrade(version);
+                    }
+                    #[cfg(feature = "metrics")]
+                    let responder = crate::metrics::observe_outbound_response(
+                        crate::metrics::RequestObservation::start(
+                            R::IS_CLIENT,
+                            crate::metrics::Direction::Outbound,
+                            request.method(),
+                            request_tool_name(&request),
+                        ),
+                        responder,
+                    );
                     local_responder_pool.insert(id.clone(), responder);
                     let send = transport.send(JsonRpcMessage::request(request, id.clone()));
                     {
@@ -809,13 +1111,22 @@ where
                 }) => {
                     // catch cancellation notification
                     let mut cancellation_param = None;
-                    let notification = match notification.try_into() {
+                    let mut notification = match notification.try_into() {
                         Ok::<CancelledNotification, _>(cancelled) => {
                             cancellation_param.replace(cancelled.params.clone());
                             cancelled.into()
                         }
                         Err(notification) => notification,
                     };
+                    if let Some(version) = peer.downgraded_version() {
+                        notification.downgrade(version);
+                    }
+                    #[cfg(feature = "metrics")]
+                    crate::metrics::record_notification(
+                        R::IS_CLIENT,
+                        crate::metrics::Direction::Outbound,
+                        notification.method(),
+                    );
                     let send = transport.send(JsonRpcMessage::notification(notification));
                     let current_span = tracing::Span::current();
                     send_task_set.spawn(send.map(move |result| SendTaskResult::Notification {
@@ -830,6 +1141,20 @@ where
                     ..
                 })) => {
                     tracing::debug!(%id, ?request, "received request");
+                    if let Some(state) = &mut protocol_state {
+                        if let Err(violation) =
+                            state.check_request(request.method(), &local_info, R::IS_CLIENT)
+                        {
+                            tracing::warn!(%id, method = request.method(), %violation, "reject request");
+                            let sink = sink_proxy_tx.clone();
+                            tokio::spawn(async move {
+                                let _ = sink
+                                    .send(JsonRpcMessage::error(violation.to_error_data(), id))
+                                    .await;
+                            });
+                            continue;
+                        }
+                    }
                     {
                         let service = shared_service.clone();
                         let sink = sink_proxy_tx.clone();
@@ -849,17 +1174,34 @@ where
                             meta,
                             extensions,
                         };
+                        #[cfg(not(feature = "otel"))]
                         let current_span = tracing::Span::current();
+                        #[cfg(feature = "otel")]
+                        let current_span = otel::request_span(&request, &id, &context.meta);
+                        #[cfg(feature = "metrics")]
+                        let observation = crate::metrics::RequestObservation::start(
+                            R::IS_CLIENT,
+                            crate::metrics::Direction::Inbound,
+                            request.method(),
+                            request_tool_name(&request),
+                        );
                         tokio::spawn(async move {
                             let result = service
                                 .handle_request(request, context)
                                 .await;
+                            #[cfg(feature = "metrics")]
+                            observation.finish(match &result {
+                                Ok(_) => crate::metrics::RequestOutcome::Ok,
+                                Err(error) => crate::metrics::RequestOutcome::Error(error.code),
+                            });
                             let response = match result {
                                 Ok(result) => {
                                     tracing::debug!(%id, ?result, "response message");
                                     JsonRpcMessage::response(result, id)
                                 }
                                 Err(error) => {
+                                    #[cfg(feature = "otel")]
+                                    otel::record_error(&tracing::Span::current(), &error);
                                     tracing::warn!(%id, ?error, "response error");
                                     JsonRpcMessage::error(error, id)
                                 }
@@ -873,6 +1215,19 @@ where
                     ..
                 })) => {
                     tracing::info!(?notification, "received notification");
+                    #[cfg(feature = "metrics")]
+                    crate::metrics::record_notification(
+                        R::IS_CLIENT,
+                        crate::metrics::Direction::Inbound,
+                        notification.method(),
+                    );
+                    if let Some(state) = &mut protocol_state {
+                        if let Err(violation) = state.check_notification(notification.method()) {
+                            tracing::warn!(method = notification.method(), %violation, "drop notification");
+                            continue;
+                        }
+                    }
+                    peer.broadcast_notification(&notification);
                     // catch cancelled notification
                     let mut notification = match notification.try_into() {
                         Ok::<CancelledNotification, _>(cancelled) => {
@@ -1,5 +1,6 @@
-use std::borrow::Cow;
+use std::{borrow::Cow, collections::VecDeque};
 
+use futures::{FutureExt, StreamExt};
 use thiserror::Error;
 
 use super::*;
@@ -8,15 +9,17 @@ use crate::{
         ArgumentInfo, CallToolRequest, CallToolRequestParams, CallToolResult,
         CancelledNotification, CancelledNotificationParam, ClientInfo, ClientJsonRpcMessage,
         ClientNotification, ClientRequest, ClientResult, CompleteRequest, CompleteRequestParams,
-        CompleteResult, CompletionContext, CompletionInfo, ErrorData, GetPromptRequest,
+        CompleteResult, CompletionContext, CompletionInfo, Content, ErrorData, GetPromptRequest,
         GetPromptRequestParams, GetPromptResult, InitializeRequest, InitializedNotification,
         JsonRpcResponse, ListPromptsRequest, ListPromptsResult, ListResourceTemplatesRequest,
         ListResourceTemplatesResult, ListResourcesRequest, ListResourcesResult, ListToolsRequest,
-        ListToolsResult, PaginatedRequestParams, ProgressNotification, ProgressNotificationParam,
+        ListToolsResult, LoggingLevel, LoggingMessageNotificationParam, Meta,
+        PaginatedRequestParams, ProgressNotification, ProgressNotificationParam, ProgressToken,
         ReadResourceRequest, ReadResourceRequestParams, ReadResourceResult, Reference, RequestId,
-        RootsListChangedNotification, ServerInfo, ServerJsonRpcMessage, ServerNotification,
-        ServerRequest, ServerResult, SetLevelRequest, SetLevelRequestParams, SubscribeRequest,
-        SubscribeRequestParams, UnsubscribeRequest, UnsubscribeRequestParams,
+        ResourceUpdatedNotificationParam, RootsListChangedNotification, ServerInfo,
+        ServerJsonRpcMessage, ServerNotification, ServerRequest, ServerResult, SetLevelRequest,
+        SetLevelRequestParams, SubscribeRequest, SubscribeRequestParams, UnsubscribeRequest,
+        UnsubscribeRequestParams, UriTemplate,
     },
     transport::DynamicTransportError,
 };
@@ -49,6 +52,9 @@ pub enum ClientInitializeError {
 
     #[error("Cancelled")]
     Cancelled,
+
+    #[error("unsupported protocol version: {0}")]
+    UnsupportedProtocolVersion(ProtocolVersion),
 }
 
 impl ClientInitializeError {
@@ -83,9 +89,10 @@ async fn expect_response<T, S>(
     context: &str,
     service: &S,
     peer: Peer<RoleClient>,
+    strict: bool,
 ) -> Result<(ServerResult, RequestId), ClientInitializeError>
 where
-    T: Transport<RoleClient>,
+    T: Transport<RoleClient> + 'static,
     S: Service<RoleClient>,
 {
     loop {
@@ -101,6 +108,12 @@ where
             }
             // Server could send logging messages before handshake
             ServerJsonRpcMessage::Notification(mut notification) => {
+                if matches!(
+                    notification.notification,
+                    ServerNotification::LoggingMessageNotification(_)
+                ) {
+                    peer.broadcast_notification(&notification.notification);
+                }
                 let ServerNotification::LoggingMessageNotification(logging) =
                     &mut notification.notification
                 else {
@@ -132,6 +145,19 @@ where
             {
                 tracing::trace!("Received ping request. Ignored.")
             }
+            // In strict mode, the server gets an error for any other request
+            ServerJsonRpcMessage::Request(JsonRpcRequest { id, request, .. }) if strict => {
+                let violation = ProtocolViolation::NotInitialized {
+                    method: request.method().to_owned(),
+                };
+                tracing::warn!(%id, method = request.method(), %violation, "reject request");
+                transport
+                    .send(ClientJsonRpcMessage::error(violation.to_error_data(), id))
+                    .await
+                    .map_err(|error| {
+                        ClientInitializeError::transport::<T>(error, "sending early response")
+                    })?;
+            }
             // Server SHOULD NOT send any other messages before handshake. We ignore them anyway
             _ => tracing::warn!(?message, "Received unexpected message"),
         }
@@ -157,17 +183,18 @@ impl ServiceRole for RoleClient {
 pub type ServerSink = Peer<RoleClient>;
 
 impl<S: Service<RoleClient>> ServiceExt<RoleClient> for S {
-    fn serve_with_ct<T, E, A>(
+    fn serve_with_ct_and_policy<T, E, A>(
         self,
         transport: T,
         ct: CancellationToken,
+        policy: RequestPolicy,
     ) -> impl Future<Output = Result<RunningService<RoleClient, Self>, ClientInitializeError>> + Send
     where
         T: IntoTransport<RoleClient, E, A>,
         E: std::error::Error + Send + Sync + 'static,
         Self: Sized,
     {
-        serve_client_with_ct(self, transport, ct)
+        serve_client_with_ct_and_policy(self, transport, ct, policy)
     }
 }
 
@@ -188,13 +215,29 @@ pub async fn serve_client_with_ct<S, T, E, A>(
     transport: T,
     ct: CancellationToken,
 ) -> Result<RunningService<RoleClient, S>, ClientInitializeError>
+where
+    S: Service<RoleClient>,
+    T: IntoTransport<RoleClient, E, A>,
+    E: std::error::Error + Send + Sync + 'static,
+{
+    serve_client_with_ct_and_policy(service, transport, ct, RequestPolicy::default()).await
+}
+
+/// Like [`serve_client_with_ct`], with a [`RequestPolicy`] applied to the requests sent to the
+/// peer
+pub async fn serve_client_with_ct_and_policy<S, T, E, A>(
+    service: S,
+    transport: T,
+    ct: CancellationToken,
+    policy: RequestPolicy,
+) -> Result<RunningService<RoleClient, S>, ClientInitializeError>
 where
     S: Service<RoleClient>,
     T: IntoTransport<RoleClient, E, A>,
     E: std::error::Error + Send + Sync + 'static,
 {
     tokio::select! {
-        result = serve_client_with_ct_inner(service, transport.into_transport(), ct.clone()) => { result }
+        result = serve_client_with_ct_inner(service, transport.into_transport(), ct.clone(), policy) => { result }
         _ = ct.cancelled() => {
             Err(ClientInitializeError::Cancelled)
         }
@@ -205,6 +248,7 @@ async fn serve_client_with_ct_inner<S, T>(
     service: S,
     transport: T,
     ct: CancellationToken,
+    policy: RequestPolicy,
 ) -> Result<RunningService<RoleClient, S>, ClientInitializeError>
 where
     S: Service<RoleClient>,
@@ -215,31 +259,49 @@ where
 
     // service
     let id = id_provider.next_request_id();
-    let init_request = InitializeRequest {
+    let info = service.get_info();
+    let declared_version = info.protocol_version.clone();
+    let init_request = ClientRequest::InitializeRequest(InitializeRequest {
         method: Default::default(),
-        params: service.get_info(),
+        params: info,
         extensions: Default::default(),
-    };
+    });
+    #[cfg(feature = "metrics")]
+    let observation = crate::metrics::RequestObservation::start(
+        true,
+        crate::metrics::Direction::Outbound,
+        init_request.method(),
+        None,
+    );
     transport
-        .send(ClientJsonRpcMessage::request(
-            ClientRequest::InitializeRequest(init_request),
-            id.clone(),
-        ))
+        .send(ClientJsonRpcMessage::request(init_request, id.clone()))
         .await
         .map_err(|error| ClientInitializeError::TransportError {
             error: DynamicTransportError::new::<T, _>(error),
             context: "send initialize request".into(),
         })?;
 
-    let (peer, peer_rx) = Peer::new(id_provider, None);
+    let strict = policy.strict;
+    let (peer, peer_rx) = Peer::new(id_provider, None, policy);
 
-    let (response, response_id) = expect_response(
+    let response = expect_response(
         &mut transport,
         "initialize response",
         &service,
         peer.clone(),
+        strict,
     )
-    .await?;
+    .await;
+    #[cfg(feature = "metrics")]
+    observation.finish(match &response {
+        Ok(_) => crate::metrics::RequestOutcome::Ok,
+        Err(ClientInitializeError::JsonRpcError(error)) => {
+            crate::metrics::RequestOutcome::Error(error.code)
+        }
+        Err(ClientInitializeError::Cancelled) => crate::metrics::RequestOutcome::Cancelled,
+        Err(_) => crate::metrics::RequestOutcome::TransportError,
+    });
+    let (response, response_id) = response?;
 
     if id != response_id {
         return Err(ClientInitializeError::ConflictInitResponseId(
@@ -251,15 +313,32 @@ where
     let ServerResult::InitializeResult(initialize_result) = response else {
         return Err(ClientInitializeError::ExpectedInitResult(Some(response)));
     };
+    let accepted = peer
+        .request_policy()
+        .accepted_protocol_versions(&declared_version);
+    if !accepted.contains(&initialize_result.protocol_version) {
+        return Err(ClientInitializeError::UnsupportedProtocolVersion(
+            initialize_result.protocol_version,
+        ));
+    }
+    peer.set_protocol_version(
+        initialize_result.protocol_version.clone(),
+        &declared_version,
+    );
     peer.set_peer_info(initialize_result);
 
     // send notification
-    let notification = ClientJsonRpcMessage::notification(
-        ClientNotification::InitializedNotification(InitializedNotification {
-            method: Default::default(),
-            extensions: Default::default(),
-        }),
+    let notification = ClientNotification::InitializedNotification(InitializedNotification {
+        method: Default::default(),
+        extensions: Default::default(),
+    });
+    #[cfg(feature = "metrics")]
+    crate::metrics::record_notification(
+        true,
+        crate::metrics::Direction::Outbound,
+        notification.method(),
     );
+    let notification = ClientJsonRpcMessage::notification(notification);
     transport.send(notification).await.map_err(|error| {
         ClientInitializeError::transport::<T>(error, "send initialized notification")
     })?;
@@ -359,8 +438,6 @@ impl Peer<RoleClient> {
     method!(peer_req read_resource ReadResourceRequest(ReadResourceRequestParams) => ReadResourceResult);
     method!(peer_req subscribe SubscribeRequest(SubscribeRequestParams) );
     method!(peer_req unsubscribe UnsubscribeRequest(UnsubscribeRequestParams));
-    method!(peer_req call_tool CallToolRequest(CallToolRequestParams) => CallToolResult);
-    method!(peer_req list_tools ListToolsRequest(PaginatedRequestParams)? => ListToolsResult);
 
     method!(peer_not notify_cancelled CancelledNotification(CancelledNotificationParam));
     method!(peer_not notify_progress ProgressNotification(ProgressNotificationParam));
@@ -369,6 +446,272 @@ impl Peer<RoleClient> {
 }
 
 impl Peer<RoleClient> {
+    /// A stream of the `notifications/resources/updated` received for `uri`, see
+    /// [`Peer::subscribe_notifications`].
+    ///
+    /// The server only sends them after [`Peer<RoleClient>::subscribe`] was called for `uri`.
+    pub fn resource_updates(
+        &self,
+        uri: impl Into<String>,
+    ) -> impl futures::Stream<Item = ResourceUpdatedNotificationParam> + Send + 'static {
+        let uri = uri.into();
+        self.subscribe_notifications()
+            .filter_map(move |notification| {
+                let update = match notification {
+                    ServerNotification::ResourceUpdatedNotification(notification)
+                        if notification.params.uri == uri =>
+                    {
+                        Some(notification.params)
+                    }
+                    _ => None,
+                };
+                std::future::ready(update)
+            })
+    }
+
+    /// A stream of the `notifications/message` at `min_level` or more severe, see
+    /// [`Peer::subscribe_notifications`].
+    ///
+    /// This does not change the level the server logs at, use [`Peer<RoleClient>::set_level`].
+    pub fn log_messages(
+        &self,
+        min_level: LoggingLevel,
+    ) -> impl futures::Stream<Item = LoggingMessageNotificationParam> + Send + 'static {
+        self.subscribe_notifications()
+            .filter_map(move |notification| {
+                let message = match notification {
+                    ServerNotification::LoggingMessageNotification(notification)
+                        if notification.params.level >= min_level =>
+                    {
+                        Some(notification.params)
+                    }
+                    _ => None,
+                };
+                std::future::ready(message)
+            })
+    }
+
+    /// A stream yielding each time the server sends `notifications/tools/list_changed`, see
+    /// [`Peer::subscribe_notifications`].
+    pub fn tool_list_changes(&self) -> impl futures::Stream<Item = ()> + Send + 'static {
+        self.subscribe_notifications().filter_map(|notification| {
+            std::future::ready(
+                matches!(
+                    notification,
+                    ServerNotification::ToolListChangedNotification(_)
+                )
+                .then_some(()),
+            )
+        })
+    }
+}
+
+/// The content streamed by a tool call, returned by [`Peer::call_tool_streaming`].
+///
+/// The stream ends when the call completes, after which [`ToolCallStream::result`] is ready.
+/// Chunks are only missed if the stream falls far behind the notifications received.
+pub struct ToolCallStream {
+    notifications: futures::stream::Fuse<futures::stream::BoxStream<'static, ServerNotification>>,
+    progress_token: ProgressToken,
+    chunks: VecDeque<Content>,
+    response: futures::future::BoxFuture<'static, Result<CallToolResult, ServiceError>>,
+    result: Option<Result<CallToolResult, ServiceError>>,
+}
+
+impl std::fmt::Debug for ToolCallStream {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ToolCallStream")
+            .field("progress_token", &self.progress_token)
+            .field("chunks", &self.chunks)
+            .finish_non_exhaustive()
+    }
+}
+
+impl ToolCallStream {
+    /// The result of the call, which aggregates every chunk.
+    pub async fn result(mut self) -> Result<CallToolResult, ServiceError> {
+        match self.result.take() {
+            Some(result) => result,
+            None => self.response.await,
+        }
+    }
+
+    fn receive(&mut self, notification: ServerNotification) {
+        let ServerNotification::ProgressNotification(notification) = notification else {
+            return;
+        };
+        if notification.params.progress_token != self.progress_token {
+            return;
+        }
+        if let Some(chunks) = notification
+            .extensions
+            .get::<Meta>()
+            .and_then(Meta::get_partial_content)
+        {
+            self.chunks.extend(chunks);
+        }
+    }
+}
+
+impl futures::Stream for ToolCallStream {
+    type Item = Content;
+
+    fn poll_next(
+        self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Option<Content>> {
+        use std::task::Poll;
+        let this = self.get_mut();
+        loop {
+            if let Some(chunk) = this.chunks.pop_front() {
+                return Poll::Ready(Some(chunk));
+            }
+            if let Poll::Ready(Some(notification)) = this.notifications.poll_next_unpin(cx) {
+                this.receive(notification);
+                continue;
+            }
+            // the notifications sent before the response are received before it, so none
+            // is left once it is here
+            if this.result.is_some() {
+                return Poll::Ready(None);
+            }
+            match this.response.poll_unpin(cx) {
+                Poll::Ready(result) => this.result = Some(result),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}
+
+impl Peer<RoleClient> {
+    pub async fn list_tools(
+        &self,
+        params: Option<PaginatedRequestParams>,
+    ) -> Result<ListToolsResult, ServiceError> {
+        let result = self
+            .send_request(ClientRequest::ListToolsRequest(ListToolsRequest {
+                method: Default::default(),
+                params,
+                extensions: Default::default(),
+            }))
+            .await?;
+        match result {
+            ServerResult::ListToolsResult(result) => {
+                #[cfg(feature = "schema-validation")]
+                self.listed_tools.record(&result.tools);
+                Ok(result)
+            }
+            _ => Err(ServiceError::UnexpectedResponse),
+        }
+    }
+
+    /// Call a tool.
+    ///
+    /// With the `schema-validation` feature, the structured content of the result is checked
+    /// against the output schema of the tool, if it was listed by [`Peer::list_tools`]. A
+    /// violation is a [`ServiceError::InvalidToolResult`].
+    pub async fn call_tool(
+        &self,
+        params: CallToolRequestParams,
+    ) -> Result<CallToolResult, ServiceError> {
+        let name = params.name.clone();
+        let result = self
+            .send_request(ClientRequest::CallToolRequest(CallToolRequest {
+                method: Default::default(),
+                params,
+                extensions: Default::default(),
+            }))
+            .await?;
+        self.check_tool_result(&name, result)
+    }
+
+    /// Call a tool, receiving the content it streams while the call is pending, see
+    /// [`ContentStream`](crate::handler::server::wrapper::ContentStream).
+    ///
+    /// Unlike [`Peer::call_tool`], the request is never retried, since its chunks would be
+    /// received again.
+    pub async fn call_tool_streaming(
+        &self,
+        params: CallToolRequestParams,
+    ) -> Result<ToolCallStream, ServiceError> {
+        let name = params.name.clone();
+        // subscribe first, so that no chunk is missed
+        let notifications = self.subscribe_notifications().boxed().fuse();
+        let handle = self
+            .send_cancellable_request(
+                ClientRequest::CallToolRequest(CallToolRequest {
+                    method: Default::default(),
+                    params,
+                    extensions: Default::default(),
+                }),
+                PeerRequestOptions::no_options(),
+            )
+            .await?;
+        let progress_token = handle.progress_token.clone();
+        let peer = self.clone();
+        let response = async move {
+            let result = handle.await_response().await?;
+            peer.check_tool_result(&name, result)
+        }
+        .boxed();
+        Ok(ToolCallStream {
+            notifications,
+            progress_token,
+            chunks: VecDeque::new(),
+            response,
+            result: None,
+        })
+    }
+
+    fn check_tool_result(
+        &self,
+        name: &str,
+        result: ServerResult,
+    ) -> Result<CallToolResult, ServiceError> {
+        match result {
+            ServerResult::CallToolResult(result) => {
+                #[cfg(feature = "schema-validation")]
+                self.listed_tools
+                    .validate(name, &result)
+                    .map_err(ServiceError::InvalidToolResult)?;
+                #[cfg(not(feature = "schema-validation"))]
+                let _ = name;
+                Ok(result)
+            }
+            _ => Err(ServiceError::UnexpectedResponse),
+        }
+    }
+
+    /// Call a tool and decode its structured content, see [`Peer::call_tool`].
+    ///
+    /// An error result is a [`ServiceError::InvalidToolResult`] with the text of its content.
+    pub async fn call_tool_typed<T: serde::de::DeserializeOwned>(
+        &self,
+        params: CallToolRequestParams,
+    ) -> Result<T, ServiceError> {
+        let name = params.name.clone();
+        let result = self.call_tool(params).await?;
+        if result.is_error == Some(true) {
+            let text = result
+                .content
+                .iter()
+                .filter_map(|content| content.as_text())
+                .map(|text| text.text.as_str())
+                .collect::<Vec<_>>()
+                .join("\n");
+            return Err(ServiceError::InvalidToolResult(McpError::internal_error(
+                format!("tool '{name}' returned an error: {text}"),
+                None,
+            )));
+        }
+        result.into_typed().map_err(|error| {
+            ServiceError::InvalidToolResult(McpError::internal_error(
+                format!("failed to decode the result of tool '{name}': {error}"),
+                None,
+            ))
+        })
+    }
+
     /// A wrapper method for [`Peer<RoleClient>::list_tools`].
     ///
     /// This function will call [`Peer<RoleClient>::list_tools`] multiple times until all tools are listed.
@@ -488,6 +831,10 @@ impl Peer<RoleClient> {
     ///
     /// # Returns
     /// CompletionInfo with suggestions for the specified resource URI argument
+    ///
+    /// # Errors
+    /// If `uri_template` is a valid URI template without a variable named `argument_name`, an
+    /// invalid params error is returned without sending the request.
     pub async fn complete_resource_argument(
         &self,
         uri_template: impl Into<String>,
@@ -495,11 +842,24 @@ impl Peer<RoleClient> {
         current_value: impl Into<String>,
         context: Option<CompletionContext>,
     ) -> Result<CompletionInfo, ServiceError> {
+        let uri_template = uri_template.into();
+        let argument_name = argument_name.into();
+        if let Ok(template) = UriTemplate::parse(&uri_template) {
+            if !template.has_variable(&argument_name) {
+                return Err(ServiceError::McpError(ErrorData::invalid_params(
+                    format!(
+                        "uri template '{uri_template}' has no variable '{argument_name}', expected one of {:?}",
+                        template.variable_names()
+                    ),
+                    None,
+                )));
+            }
+        }
         let request = CompleteRequestParams {
             meta: None,
             r#ref: Reference::for_resource(uri_template),
             argument: ArgumentInfo {
-                name: argument_name.into(),
+                name: argument_name,
                 value: current_value.into(),
             },
             context,
@@ -0,0 +1,401 @@
+//! Forward `tracing` events to the client as `notifications/message`.
+//!
+//! With the `logging` feature enabled, install a [`LoggingLayer`] on the subscriber:
+//!
+//! ```rust,ignore
+//! use tracing_subscriber::prelude::*;
+//!
+//! tracing_subscriber::registry()
+//!     .with(tracing_subscriber::fmt::layer())
+//!     .with(LoggingLayer::new().with_redacted_fields(["password"]))
+//!     .init();
+//! ```
+//!
+//! Every server then declares the `logging` capability, and the events emitted while handling
+//! a request of a session are sent to the client of that session:
+//!
+//! - the tracing level is mapped to a [`LoggingLevel`], `TRACE` becoming `debug`
+//! - events below the level set by the client with `logging/setLevel`, or the
+//!   [default level](LoggingLayer::with_default_level) before that, are ignored
+//! - the event target is the `logger`, and its fields are the `data`, with the
+//!   [redacted fields](LoggingLayer::with_redacted_fields) replaced by `"[redacted]"`
+//! - past the [rate limit](LoggingLayer::with_rate_limit), events are dropped, and the
+//!   client is told how many were once the limit resets
+//!
+//! Sessions are found through a span, named `mcp_session`, which the service opens at the
+//! `ERROR` level around its handlers, so filters must let it through, and which is forgotten
+//! once the session closes. Events of rmcp itself are never forwarded, since sending a message
+//! emits events too.
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{
+        Arc, Mutex, RwLock, Weak,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::{Duration, Instant},
+};
+
+use futures::{FutureExt, future::BoxFuture};
+use serde_json::{Map, Value};
+use tokio::sync::mpsc;
+use tracing::{
+    Event, Subscriber,
+    field::{Field, Visit},
+    span,
+};
+use tracing_subscriber::{Layer, layer::Context, registry::LookupSpan};
+
+use super::{Peer, RoleServer, ServiceRole};
+use crate::model::{LoggingLevel, LoggingMessageNotificationParam};
+
+const SESSION_SPAN: &str = "mcp_session";
+const SESSION_FIELD: &str = "mcp.session";
+/// Messages waiting to be sent to a session, beyond which new ones are dropped
+const SESSION_BUFFER_SIZE: usize = 64;
+
+/// A [`Layer`] sending the events of each server session to its client, see the
+/// [module](self) documentation.
+#[derive(Debug)]
+pub struct LoggingLayer {
+    default_level: LoggingLevel,
+    redacted_fields: HashSet<String>,
+    rate_limit: Option<(u32, Duration)>,
+    /// Shared with the forwarding task of each session, which removes it when it ends
+    sessions: Arc<RwLock<HashMap<u64, Arc<Session>>>>,
+    next_session: AtomicU64,
+}
+
+#[derive(Debug)]
+struct Session {
+    peer: Peer<RoleServer>,
+    level: Mutex<LoggingLevel>,
+    messages: mpsc::Sender<LoggingMessageNotificationParam>,
+    window: Mutex<Window>,
+}
+
+#[derive(Debug)]
+struct Window {
+    start: Instant,
+    sent: u32,
+    dropped: u32,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct SessionId(u64);
+
+impl Default for LoggingLayer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LoggingLayer {
+    pub const DEFAULT_RATE_LIMIT: (u32, Duration) = (100, Duration::from_secs(1));
+
+    /// A layer sending `info` and above until the client sets a level, at most
+    /// [`DEFAULT_RATE_LIMIT`](Self::DEFAULT_RATE_LIMIT) messages per session.
+    pub fn new() -> Self {
+        Self {
+            default_level: LoggingLevel::Info,
+            redacted_fields: HashSet::new(),
+            rate_limit: Some(Self::DEFAULT_RATE_LIMIT),
+            sessions: Arc::default(),
+            next_session: AtomicU64::new(1),
+        }
+    }
+
+    /// The level of a session until its client sets one.
+    pub fn with_default_level(mut self, level: LoggingLevel) -> Self {
+        self.default_level = level;
+        self
+    }
+
+    /// Replace the value of the fields with these names by `"[redacted]"`.
+    pub fn with_redacted_fields(
+        mut self,
+        fields: impl IntoIterator<Item = impl Into<String>>,
+    ) -> Self {
+        self.redacted_fields
+            .extend(fields.into_iter().map(Into::into));
+        self
+    }
+
+    /// Send at most `max` messages to a session per `period`.
+    pub fn with_rate_limit(mut self, max: u32, period: Duration) -> Self {
+        self.rate_limit = Some((max, period));
+        self
+    }
+
+    pub fn without_rate_limit(mut self) -> Self {
+        self.rate_limit = None;
+        self
+    }
+
+    /// Register a session with `peer`, returning the span to run it in and the future sending
+    /// its events, which the service spawns.
+    fn register(&self, peer: &Peer<RoleServer>) -> (tracing::Span, BoxFuture<'static, ()>) {
+        let id = self.next_session.fetch_add(1, Ordering::Relaxed);
+        let (messages, mut receiver) = mpsc::channel(SESSION_BUFFER_SIZE);
+        let session = Session {
+            peer: peer.clone(),
+            level: Mutex::new(self.default_level),
+            messages,
+            window: Mutex::new(Window {
+                start: Instant::now(),
+                sent: 0,
+                dropped: 0,
+            }),
+        };
+        self.sessions
+            .write()
+            .expect("logging lock poisoned")
+            .insert(id, Arc::new(session));
+        let sessions = Arc::downgrade(&self.sessions);
+        let peer = peer.clone();
+        let forward = async move {
+            loop {
+                let message = tokio::select! {
+                    message = receiver.recv() => message,
+                    _ = peer.closed() => None,
+                };
+                let Some(message) = message else {
+                    break;
+                };
+                if peer.notify_logging_message(message).await.is_err() {
+                    break;
+                }
+            }
+            remove_session(&sessions, id);
+        };
+        (
+            tracing::error_span!("mcp_session", mcp.session = id),
+            forward.boxed(),
+        )
+    }
+
+    fn set_level(&self, peer: &Peer<RoleServer>, level: LoggingLevel) -> bool {
+        let sessions = self.sessions.read().expect("logging lock poisoned");
+        let Some(session) = sessions
+            .values()
+            .find(|session| session.peer.is_same_peer(peer))
+        else {
+            return false;
+        };
+        *session.level.lock().expect("logging lock poisoned") = level;
+        true
+    }
+
+    /// Whether the rate limit lets one more message through to `session`.
+    fn admit(&self, session: &Session) -> bool {
+        let Some((max, period)) = self.rate_limit else {
+            return true;
+        };
+        let mut window = session.window.lock().expect("logging lock poisoned");
+        if window.start.elapsed() >= period {
+            if window.dropped > 0 {
+                let _ = session.messages.try_send(LoggingMessageNotificationParam {
+                    level: LoggingLevel::Warning,
+                    logger: Some("rmcp".into()),
+                    data: serde_json::json!({
+                        "message": format!("{} log messages were dropped", window.dropped),
+                        "dropped": window.dropped,
+                    }),
+                });
+            }
+            *window = Window {
+                start: Instant::now(),
+                sent: 0,
+                dropped: 0,
+            };
+        }
+        if window.sent >= max {
+            window.dropped += 1;
+            return false;
+        }
+        window.sent += 1;
+        true
+    }
+}
+
+fn remove_session(sessions: &Weak<RwLock<HashMap<u64, Arc<Session>>>>, id: u64) {
+    if let Some(sessions) = sessions.upgrade() {
+        sessions.write().expect("logging lock poisoned").remove(&id);
+    }
+}
+
+/// Open the span of a new session with `peer`, if it is a server and a [`LoggingLayer`] is
+/// installed, along with the future sending its events, to spawn once the session runs.
+pub(crate) fn register_session<R: ServiceRole>(
+    peer: &Peer<R>,
+) -> Option<(tracing::Span, BoxFuture<'static, ()>)> {
+    let peer = (peer as &dyn std::any::Any).downcast_ref::<Peer<RoleServer>>()?;
+    // the span must be created outside of `get_default`, where no dispatcher is available
+    let dispatch = tracing::dispatcher::get_default(Clone::clone);
+    Some(dispatch.downcast_ref::<LoggingLayer>()?.register(peer))
+}
+
+/// Apply the level requested with `logging/setLevel` to the session with `peer`, returning
+/// whether it has one.
+pub(crate) fn set_level(peer: &Peer<RoleServer>, level: LoggingLevel) -> bool {
+    tracing::dispatcher::get_default(|dispatch| {
+        dispatch
+            .downcast_ref::<LoggingLayer>()
+            .is_some_and(|layer| layer.set_level(peer, level))
+    })
+}
+
+/// Whether a [`LoggingLayer`] is installed.
+pub(crate) fn is_installed() -> bool {
+    tracing::dispatcher::get_default(|dispatch| dispatch.is::<LoggingLayer>())
+}
+
+fn logging_level(level: &tracing::Level) -> LoggingLevel {
+    match *level {
+        tracing::Level::ERROR => LoggingLevel::Error,
+        tracing::Level::WARN => LoggingLevel::Warning,
+        tracing::Level::INFO => LoggingLevel::Info,
+        _ => LoggingLevel::Debug,
+    }
+}
+
+impl<S> Layer<S> for LoggingLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &span::Attributes<'_>, id: &span::Id, ctx: Context<'_, S>) {
+        if attrs.metadata().name() != SESSION_SPAN {
+            return;
+        }
+        let mut visitor = SessionVisitor(None);
+        attrs.record(&mut visitor);
+        if let (Some(session), Some(span)) = (visitor.0, ctx.span(id)) {
+            span.extensions_mut().insert(SessionId(session));
+        }
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        let metadata = event.metadata();
+        if metadata.target() == "rmcp" || metadata.target().starts_with("rmcp::") {
+            return;
+        }
+        let Some(SessionId(id)) = ctx.event_scope(event).and_then(|scope| {
+            scope
+                .into_iter()
+                .find_map(|span| span.extensions().get::<SessionId>().copied())
+        }) else {
+            return;
+        };
+        let Some(session) = self
+            .sessions
+            .read()
+            .expect("logging lock poisoned")
+            .get(&id)
+            .cloned()
+        else {
+            return;
+        };
+        let level = logging_level(metadata.level());
+        if level < *session.level.lock().expect("logging lock poisoned") || !self.admit(&session) {
+            return;
+        }
+        let mut visitor = DataVisitor {
+            data: Map::new(),
+            redacted_fields: &self.redacted_fields,
+        };
+        event.record(&mut visitor);
+        let _ = session.messages.try_send(LoggingMessageNotificationParam {
+            level,
+            logger: Some(metadata.target().into()),
+            data: Value::Object(visitor.data),
+        });
+    }
+}
+
+struct SessionVisitor(Option<u64>);
+
+impl Visit for SessionVisitor {
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        if field.name() == SESSION_FIELD {
+            self.0 = Some(value);
+        }
+    }
+
+    fn record_debug(&mut self, _field: &Field, _value: &dyn std::fmt::Debug) {}
+}
+
+struct DataVisitor<'a> {
+    data: Map<String, Value>,
+    redacted_fields: &'a HashSet<String>,
+}
+
+impl DataVisitor<'_> {
+    fn insert(&mut self, field: &Field, value: impl Into<Value>) {
+        let value = if self.redacted_fields.contains(field.name()) {
+            Value::from("[redacted]")
+        } else {
+            value.into()
+        };
+        self.data.insert(field.name().into(), value);
+    }
+}
+
+impl Visit for DataVisitor<'_> {
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.insert(field, value);
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.insert(field, value);
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.insert(field, value);
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.insert(field, value);
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.insert(field, value);
+    }
+
+    fn record_error(&mut self, field: &Field, value: &(dyn std::error::Error + 'static)) {
+        self.insert(field, value.to_string());
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        self.insert(field, format!("{value:?}"));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_logging_level() {
+        assert_eq!(logging_level(&tracing::Level::ERROR), LoggingLevel::Error);
+        assert_eq!(logging_level(&tracing::Level::WARN), LoggingLevel::Warning);
+        assert_eq!(logging_level(&tracing::Level::INFO), LoggingLevel::Info);
+        assert_eq!(logging_level(&tracing::Level::DEBUG), LoggingLevel::Debug);
+        assert_eq!(logging_level(&tracing::Level::TRACE), LoggingLevel::Debug);
+    }
+
+    #[tokio::test]
+    async fn test_closed_session_is_removed() {
+        let layer = LoggingLayer::new();
+        let (peer, outbound) = Peer::<RoleServer>::new(
+            Arc::new(crate::service::AtomicU32RequestIdProvider::default()),
+            None,
+            Default::default(),
+        );
+        let (_span, forward) = layer.register(&peer);
+        let forward = tokio::spawn(forward);
+        assert_eq!(layer.sessions.read().unwrap().len(), 1);
+
+        drop(outbound);
+        forward.await.unwrap();
+        assert!(layer.sessions.read().unwrap().is_empty());
+    }
+}
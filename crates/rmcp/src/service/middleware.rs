@@ -0,0 +1,174 @@
+//! Composable middleware around [`Service`].
+//!
+//! A [`Middleware`] sits between the service loop and the wrapped service, in the same spirit
+//! as a tower `Layer`. It receives every incoming request and notification together with its
+//! context and a [`Next`] handle to the inner service, so it can
+//!
+//! - inspect or rewrite the request and the [`RequestContext`] (including its extensions),
+//! - inspect or rewrite the result returned by the inner service,
+//! - or short-circuit by returning without calling [`Next`] at all.
+//!
+//! Middlewares are attached with [`ServiceExt::with_middleware`](super::ServiceExt::with_middleware),
+//! and can be stacked; the last one added is the outermost.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! struct Audit;
+//!
+//! impl Middleware<RoleServer> for Audit {
+//!     async fn handle_request<'a>(
+//!         &'a self,
+//!         request: ClientRequest,
+//!         context: RequestContext<RoleServer>,
+//!         next: Next<'a, RoleServer>,
+//!     ) -> Result<ServerResult, ErrorData> {
+//!         tracing::info!(method = request.method(), "incoming request");
+//!         next.handle_request(request, context).await
+//!     }
+//! }
+//!
+//! let service = MyServer::new().with_middleware(Audit);
+//! let running = service.serve(transport).await?;
+//! ```
+use futures::future::BoxFuture;
+
+use super::{DynService, NotificationContext, RequestContext, Service, ServiceRole};
+use crate::error::ErrorData as McpError;
+
+/// A handle to the rest of the middleware chain.
+///
+/// Calling [`Next::handle_request`] or [`Next::handle_notification`] forwards the message to the
+/// inner service (which may itself be another middleware layer).
+pub struct Next<'a, R: ServiceRole> {
+    inner: &'a dyn DynService<R>,
+}
+
+impl<R: ServiceRole> Clone for Next<'_, R> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<R: ServiceRole> Copy for Next<'_, R> {}
+
+impl<R: ServiceRole> std::fmt::Debug for Next<'_, R> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Next")
+            .field("is_client", &R::IS_CLIENT)
+            .finish()
+    }
+}
+
+impl<'a, R: ServiceRole> Next<'a, R> {
+    pub fn new(inner: &'a dyn DynService<R>) -> Self {
+        Self { inner }
+    }
+
+    /// Forward a request to the inner service.
+    pub fn handle_request(
+        self,
+        request: R::PeerReq,
+        context: RequestContext<R>,
+    ) -> BoxFuture<'a, Result<R::Resp, McpError>> {
+        self.inner.handle_request(request, context)
+    }
+
+    /// Forward a notification to the inner service.
+    pub fn handle_notification(
+        self,
+        notification: R::PeerNot,
+        context: NotificationContext<R>,
+    ) -> BoxFuture<'a, Result<(), McpError>> {
+        self.inner.handle_notification(notification, context)
+    }
+
+    /// Get the info of the inner service.
+    pub fn get_info(&self) -> R::Info {
+        self.inner.get_info()
+    }
+}
+
+/// An interceptor around [`Service::handle_request`] and [`Service::handle_notification`].
+///
+/// Both methods forward to [`Next`] by default, so a middleware only needs to implement the
+/// hooks it cares about.
+#[allow(unused_variables)]
+pub trait Middleware<R: ServiceRole>: Send + Sync + 'static {
+    fn handle_request<'a>(
+        &'a self,
+        request: R::PeerReq,
+        context: RequestContext<R>,
+        next: Next<'a, R>,
+    ) -> impl Future<Output = Result<R::Resp, McpError>> + Send + 'a {
+        next.handle_request(request, context)
+    }
+
+    fn handle_notification<'a>(
+        &'a self,
+        notification: R::PeerNot,
+        context: NotificationContext<R>,
+        next: Next<'a, R>,
+    ) -> impl Future<Output = Result<(), McpError>> + Send + 'a {
+        next.handle_notification(notification, context)
+    }
+}
+
+/// A [`Service`] wrapped by a [`Middleware`].
+///
+/// This is created by [`ServiceExt::with_middleware`](super::ServiceExt::with_middleware).
+#[derive(Debug, Clone)]
+pub struct MiddlewareService<S, M> {
+    inner: S,
+    middleware: M,
+}
+
+impl<S, M> MiddlewareService<S, M> {
+    pub fn new(inner: S, middleware: M) -> Self {
+        Self { inner, middleware }
+    }
+
+    /// Get a reference to the wrapped service.
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+
+    /// Get a reference to the middleware.
+    pub fn middleware(&self) -> &M {
+        &self.middleware
+    }
+
+    /// Consume the wrapper, returning the wrapped service.
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+}
+
+impl<R, S, M> Service<R> for MiddlewareService<S, M>
+where
+    R: ServiceRole,
+    S: Service<R>,
+    M: Middleware<R>,
+{
+    fn handle_request(
+        &self,
+        request: R::PeerReq,
+        context: RequestContext<R>,
+    ) -> impl Future<Output = Result<R::Resp, McpError>> + Send + '_ {
+        self.middleware
+            .handle_request(request, context, Next::new(&self.inner))
+    }
+
+    fn handle_notification(
+        &self,
+        notification: R::PeerNot,
+        context: NotificationContext<R>,
+    ) -> impl Future<Output = Result<(), McpError>> + Send + '_ {
+        self.middleware
+            .handle_notification(notification, context, Next::new(&self.inner))
+    }
+
+    fn get_info(&self) -> R::Info {
+        self.inner.get_info()
+    }
+}
@@ -0,0 +1,189 @@
+//! OpenTelemetry trace context propagation through `_meta`.
+//!
+//! With the `otel` feature enabled, every request sent by a [`Peer`](super::Peer) carries the
+//! [W3C trace context](https://www.w3.org/TR/trace-context/) of the current [`tracing::Span`] in
+//! its `_meta`, under the `traceparent` and `tracestate` keys. This works in both directions, so
+//! server-to-client requests such as `sampling/createMessage` or `elicitation/create` are covered
+//! as well. Requests without params, such as `ping` and `roots/list`, have no `_meta` on the
+//! wire and so start a new trace on the receiving side.
+//!
+//! On the receiving side, each request is handled inside a span whose OpenTelemetry parent is the
+//! remote context found in `_meta`. The span is named after the method and records:
+//!
+//! - `rpc.method`: the JSON-RPC method, e.g. `tools/call`
+//! - `rpc.jsonrpc.request_id`: the JSON-RPC request id
+//! - `mcp.tool.name`: the tool name, for `tools/call`
+//! - `rpc.jsonrpc.error_code`: the error code, if the handler returned an error
+//!
+//! The OpenTelemetry context is read from and written to `tracing` spans through
+//! [`tracing_opentelemetry`], so a [`tracing_opentelemetry::layer`] must be installed on the
+//! subscriber for anything to be propagated. If the current span has no valid span context,
+//! nothing is injected.
+use std::any::Any;
+
+use opentelemetry::{
+    Context,
+    trace::{SpanContext, SpanId, TraceContextExt, TraceFlags, TraceId, TraceState},
+};
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+
+use crate::{
+    error::ErrorData as McpError,
+    model::{GetMethod, Meta, RequestId},
+};
+
+/// The `_meta` key carrying the W3C `traceparent` header value.
+pub const TRACEPARENT: &str = "traceparent";
+/// The `_meta` key carrying the W3C `tracestate` header value.
+pub const TRACESTATE: &str = "tracestate";
+
+const SUPPORTED_VERSION: u8 = 0;
+
+/// Write the span context of `cx` into `meta` as `traceparent` / `tracestate`.
+///
+/// Does nothing if `cx` has no valid span context.
+pub fn inject_context(cx: &Context, meta: &mut Meta) {
+    let span = cx.span();
+    let span_context = span.span_context();
+    if !span_context.is_valid() {
+        return;
+    }
+    let traceparent = format!(
+        "{:02x}-{}-{}-{:02x}",
+        SUPPORTED_VERSION,
+        span_context.trace_id(),
+        span_context.span_id(),
+        span_context.trace_flags() & TraceFlags::SAMPLED
+    );
+    meta.insert(TRACEPARENT.to_string(), traceparent.into());
+    let tracestate = span_context.trace_state().header();
+    if !tracestate.is_empty() {
+        meta.insert(TRACESTATE.to_string(), tracestate.into());
+    }
+}
+
+/// Read a remote span context from the `traceparent` / `tracestate` entries of `meta`.
+///
+/// Returns `None` if there is no `traceparent`, or if it is malformed.
+pub fn extract_context(meta: &Meta) -> Option<Context> {
+    let traceparent = meta.get(TRACEPARENT)?.as_str()?;
+    let mut parts = traceparent.trim().split('-');
+    let version = parts.next()?;
+    let trace_id = parts.next()?;
+    let span_id = parts.next()?;
+    let flags = parts.next()?;
+    // future versions may append fields, version 00 must not
+    if version.len() != 2
+        || version == "ff"
+        || (version == "00" && parts.next().is_some())
+        || u8::from_str_radix(version, 16).is_err()
+    {
+        return None;
+    }
+    if trace_id.len() != 32 || span_id.len() != 16 || flags.len() != 2 {
+        return None;
+    }
+    let trace_id = TraceId::from_hex(trace_id).ok()?;
+    let span_id = SpanId::from_hex(span_id).ok()?;
+    let flags = TraceFlags::new(u8::from_str_radix(flags, 16).ok()?) & TraceFlags::SAMPLED;
+    let trace_state = meta
+        .get(TRACESTATE)
+        .and_then(|value| value.as_str())
+        .and_then(|value| value.parse::<TraceState>().ok())
+        .unwrap_or_default();
+    let span_context = SpanContext::new(trace_id, span_id, flags, true, trace_state);
+    span_context
+        .is_valid()
+        .then(|| Context::new().with_remote_span_context(span_context))
+}
+
+/// Inject the context of the current span, unless the caller already set a `traceparent`.
+pub(crate) fn inject_current_context(meta: &mut Meta) {
+    if meta.contains_key(TRACEPARENT) {
+        return;
+    }
+    inject_context(&tracing::Span::current().context(), meta);
+}
+
+/// Create the span an incoming request is handled in.
+pub(crate) fn request_span<Req>(request: &Req, id: &RequestId, meta: &Meta) -> tracing::Span
+where
+    Req: GetMethod + Any,
+{
+    let method = request.method();
+    let span = tracing::info_span!(
+        "mcp.request",
+        otel.name = method,
+        otel.kind = "server",
+        otel.status_code = tracing::field::Empty,
+        rpc.system = "jsonrpc",
+        rpc.method = method,
+        rpc.jsonrpc.request_id = %id,
+        rpc.jsonrpc.error_code = tracing::field::Empty,
+        mcp.tool.name = tracing::field::Empty,
+    );
+    if let Some(tool_name) = super::request_tool_name(request) {
+        span.record("mcp.tool.name", tool_name);
+    }
+    if let Some(cx) = extract_context(meta) {
+        if let Err(error) = span.set_parent(cx) {
+            tracing::debug!(%error, "fail to set remote parent of request span");
+        }
+    }
+    span
+}
+
+/// Record a handler error on a span created by [`request_span`].
+pub(crate) fn record_error(span: &tracing::Span, error: &McpError) {
+    span.record("rpc.jsonrpc.error_code", error.code.0);
+    span.record("otel.status_code", "ERROR");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_traceparent_round_trip() {
+        let mut meta = Meta::new();
+        meta.insert(
+            TRACEPARENT.to_string(),
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01".into(),
+        );
+        meta.insert(TRACESTATE.to_string(), "congo=t61rcWkgMzE".into());
+        let cx = extract_context(&meta).expect("valid traceparent");
+        let span = cx.span();
+        let span_context = span.span_context();
+        assert!(span_context.is_remote());
+        assert!(span_context.is_sampled());
+        assert_eq!(
+            span_context.trace_id(),
+            TraceId::from_hex("4bf92f3577b34da6a3ce929d0e0e4736").unwrap()
+        );
+
+        let mut injected = Meta::new();
+        inject_context(&cx, &mut injected);
+        assert_eq!(injected.get(TRACEPARENT), meta.get(TRACEPARENT));
+        assert_eq!(injected.get(TRACESTATE), meta.get(TRACESTATE));
+    }
+
+    #[test]
+    fn test_invalid_traceparent_is_ignored() {
+        for traceparent in [
+            "00-00000000000000000000000000000000-00f067aa0ba902b7-01",
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-0000000000000000-01",
+            "ff-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01",
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01-extra",
+            "00-4bf92f3577b34da6a3ce929d0e0e473-00f067aa0ba902b7-01",
+            "not a traceparent",
+        ] {
+            let mut meta = Meta::new();
+            meta.insert(TRACEPARENT.to_string(), traceparent.into());
+            assert!(extract_context(&meta).is_none(), "{traceparent}");
+        }
+
+        let mut meta = Meta::new();
+        inject_context(&Context::new(), &mut meta);
+        assert!(meta.is_empty());
+    }
+}
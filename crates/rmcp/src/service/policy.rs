@@ -0,0 +1,297 @@
+//! Default timeout, cancellation and retry behavior of the requests sent by a [`Peer`](super::Peer).
+//!
+//! A [`RequestPolicy`] is attached to a peer when the service is started, with
+//! [`ServiceExt::serve_with_policy`](super::ServiceExt::serve_with_policy), and applies to every
+//! request sent through it, including the convenience methods such as `call_tool` or
+//! `list_tools`. It can be replaced for a subset of calls with
+//! [`Peer::with_request_policy`](super::Peer::with_request_policy), and a timeout passed in
+//! [`PeerRequestOptions`](super::PeerRequestOptions) always takes precedence.
+//!
+//! ```rust,ignore
+//! let policy = RequestPolicy::new(
+//!     MethodPolicy::new()
+//!         .with_timeout(Duration::from_secs(30))
+//!         .with_retry(RetryPolicy::default()),
+//! )
+//! .with_method("tools/call", MethodPolicy::new().with_timeout(Duration::from_secs(300)));
+//! let client = client_handler.serve_with_policy(transport, policy).await?;
+//! ```
+use std::{borrow::Cow, collections::HashMap, time::Duration};
+
+use super::ServiceError;
+use crate::model::ProtocolVersion;
+
+/// Methods that have no side effects on the peer, and can be retried by the default
+/// [`MethodPolicy`] of a [`RequestPolicy`].
+pub const RETRY_SAFE_METHODS: &[&str] = &[
+    "ping",
+    "tools/list",
+    "prompts/list",
+    "prompts/get",
+    "resources/list",
+    "resources/templates/list",
+    "resources/read",
+    "completion/complete",
+    "roots/list",
+    "tasks/get",
+    "tasks/list",
+];
+
+/// How requests are retried after a timeout or a transport send error.
+///
+/// The delay before the `n`th retry is `initial_backoff * multiplier^(n - 1)`, capped at
+/// `max_backoff`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RetryPolicy {
+    /// Maximum number of retries, not counting the first attempt.
+    pub max_retries: u32,
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+    pub multiplier: f64,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(5),
+            multiplier: 2.0,
+        }
+    }
+}
+
+impl RetryPolicy {
+    pub fn new(max_retries: u32) -> Self {
+        Self {
+            max_retries,
+            ..Default::default()
+        }
+    }
+
+    pub fn with_initial_backoff(mut self, initial_backoff: Duration) -> Self {
+        self.initial_backoff = initial_backoff;
+        self
+    }
+
+    pub fn with_max_backoff(mut self, max_backoff: Duration) -> Self {
+        self.max_backoff = max_backoff;
+        self
+    }
+
+    pub fn with_multiplier(mut self, multiplier: f64) -> Self {
+        self.multiplier = multiplier;
+        self
+    }
+
+    /// The delay before retry number `retry`, starting from 1.
+    pub fn backoff(&self, retry: u32) -> Duration {
+        let exponent = retry.saturating_sub(1).min(i32::MAX as u32) as i32;
+        let backoff = self.initial_backoff.as_secs_f64() * self.multiplier.powi(exponent);
+        if backoff.is_finite() && backoff < self.max_backoff.as_secs_f64() {
+            Duration::from_secs_f64(backoff.max(0.0))
+        } else {
+            self.max_backoff
+        }
+    }
+
+    /// Whether a request that failed with `error` should be sent again.
+    pub fn is_retryable(error: &ServiceError) -> bool {
+        matches!(
+            error,
+            ServiceError::Timeout { .. } | ServiceError::TransportSend(_)
+        )
+    }
+}
+
+/// The timeout, cancellation and retry behavior of a request.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MethodPolicy {
+    /// How long to wait for a response, `None` waits forever.
+    pub timeout: Option<Duration>,
+    /// Send `notifications/cancelled` to the peer when the timeout elapses.
+    pub cancel_on_timeout: bool,
+    pub retry: Option<RetryPolicy>,
+}
+
+impl Default for MethodPolicy {
+    fn default() -> Self {
+        Self {
+            timeout: None,
+            cancel_on_timeout: true,
+            retry: None,
+        }
+    }
+}
+
+impl MethodPolicy {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    pub fn with_cancel_on_timeout(mut self, cancel_on_timeout: bool) -> Self {
+        self.cancel_on_timeout = cancel_on_timeout;
+        self
+    }
+
+    pub fn with_retry(mut self, retry: RetryPolicy) -> Self {
+        self.retry = Some(retry);
+        self
+    }
+}
+
+/// The request policy of a [`Peer`](super::Peer).
+///
+/// The default policy waits forever and never retries, which is how a peer behaves when no
+/// policy is given.
+///
+/// The retry policy of [`RequestPolicy::default_policy`] only applies to the
+/// [`RETRY_SAFE_METHODS`]. A policy set for a specific method with [`RequestPolicy::with_method`]
+/// applies as is, so retrying a method with side effects, such as `tools/call`, must be asked for
+/// explicitly.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RequestPolicy {
+    pub default_policy: MethodPolicy,
+    pub methods: HashMap<Cow<'static, str>, MethodPolicy>,
+    /// Validate the lifecycle and capabilities of the session, see [`RequestPolicy::with_strict`].
+    pub strict: bool,
+    /// The protocol versions accepted during initialization, see
+    /// [`RequestPolicy::with_protocol_versions`].
+    pub protocol_versions: Option<Vec<ProtocolVersion>>,
+}
+
+impl RequestPolicy {
+    pub fn new(default_policy: MethodPolicy) -> Self {
+        Self {
+            default_policy,
+            methods: HashMap::new(),
+            strict: false,
+            protocol_versions: None,
+        }
+    }
+
+    /// Only accept these protocol versions during initialization.
+    ///
+    /// A server answers with the newest accepted version that is not newer than the one the
+    /// client asked for, and fails the initialization with an `Unsupported protocol version`
+    /// error listing the accepted versions if there is none. A client fails the initialization
+    /// if the server answers with a version it does not accept.
+    ///
+    /// By default, every [known](ProtocolVersion::KNOWN) version up to the one declared in the
+    /// service's info is accepted.
+    pub fn with_protocol_versions(
+        mut self,
+        versions: impl IntoIterator<Item = ProtocolVersion>,
+    ) -> Self {
+        self.protocol_versions = Some(versions.into_iter().collect());
+        self
+    }
+
+    /// The protocol versions accepted by a service declaring `declared`, oldest first.
+    pub fn accepted_protocol_versions(&self, declared: &ProtocolVersion) -> Vec<ProtocolVersion> {
+        let mut versions = match &self.protocol_versions {
+            Some(versions) => versions.clone(),
+            None => ProtocolVersion::KNOWN
+                .iter()
+                .filter(|version| *version <= declared)
+                .chain(std::iter::once(declared))
+                .cloned()
+                .collect(),
+        };
+        versions.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+        versions.dedup();
+        versions
+    }
+
+    /// Enable or disable strict protocol-state validation.
+    ///
+    /// In strict mode, every request is checked against the lifecycle of the session and the
+    /// capabilities declared during initialization:
+    ///
+    /// - Before the session is initialized only `initialize` and `ping` are accepted, and
+    ///   `initialize` is accepted only once.
+    /// - A request is only sent if the peer declared the capability it belongs to, e.g. a server
+    ///   can only send `sampling/createMessage` to a client that declared `sampling`. Otherwise
+    ///   the call fails with [`ServiceError::ProtocolViolation`] and nothing is sent.
+    /// - A request is only handled if the local side declared the capability it belongs to.
+    ///   Otherwise the peer gets a JSON-RPC error and the handler is not called.
+    ///
+    /// Notifications received before the session is initialized, other than `initialized`,
+    /// `cancelled`, `progress` and `message`, are dropped. Every violation is logged with the
+    /// offending method.
+    pub fn with_strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    /// Override the policy of the requests of `method`.
+    pub fn with_method(
+        mut self,
+        method: impl Into<Cow<'static, str>>,
+        policy: MethodPolicy,
+    ) -> Self {
+        self.methods.insert(method.into(), policy);
+        self
+    }
+
+    /// The timeout of the requests of `method`.
+    pub fn timeout(&self, method: &str) -> Option<Duration> {
+        self.methods
+            .get(method)
+            .unwrap_or(&self.default_policy)
+            .timeout
+    }
+
+    /// Whether a timed out request of `method` is cancelled.
+    pub fn cancel_on_timeout(&self, method: &str) -> bool {
+        self.methods
+            .get(method)
+            .unwrap_or(&self.default_policy)
+            .cancel_on_timeout
+    }
+
+    /// The retry policy of the requests of `method`.
+    pub fn retry(&self, method: &str) -> Option<&RetryPolicy> {
+        match self.methods.get(method) {
+            Some(policy) => policy.retry.as_ref(),
+            None if RETRY_SAFE_METHODS.contains(&method) => self.default_policy.retry.as_ref(),
+            None => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff_is_exponential_and_capped() {
+        let retry = RetryPolicy::new(10)
+            .with_initial_backoff(Duration::from_millis(100))
+            .with_max_backoff(Duration::from_secs(1));
+        assert_eq!(retry.backoff(1), Duration::from_millis(100));
+        assert_eq!(retry.backoff(2), Duration::from_millis(200));
+        assert_eq!(retry.backoff(3), Duration::from_millis(400));
+        assert_eq!(retry.backoff(5), Duration::from_secs(1));
+        assert_eq!(retry.backoff(u32::MAX), Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_default_retry_only_applies_to_safe_methods() {
+        let policy = RequestPolicy::new(MethodPolicy::new().with_retry(RetryPolicy::default()))
+            .with_method(
+                "tools/call",
+                MethodPolicy::new().with_retry(RetryPolicy::new(1)),
+            )
+            .with_method("prompts/get", MethodPolicy::new());
+        assert!(policy.retry("tools/list").is_some());
+        assert!(policy.retry("sampling/createMessage").is_none());
+        assert_eq!(policy.retry("tools/call"), Some(&RetryPolicy::new(1)));
+        assert!(policy.retry("prompts/get").is_none());
+    }
+}
@@ -85,17 +85,18 @@ impl ServerInitializeError {
 pub type ClientSink = Peer<RoleServer>;
 
 impl<S: Service<RoleServer>> ServiceExt<RoleServer> for S {
-    fn serve_with_ct<T, E, A>(
+    fn serve_with_ct_and_policy<T, E, A>(
         self,
         transport: T,
         ct: CancellationToken,
+        policy: RequestPolicy,
     ) -> impl Future<Output = Result<RunningService<RoleServer, Self>, ServerInitializeError>> + Send
     where
         T: IntoTransport<RoleServer, E, A>,
         E: std::error::Error + Send + Sync + 'static,
         Self: Sized,
     {
-        serve_server_with_ct(self, transport, ct)
+        serve_server_with_ct_and_policy(self, transport, ct, policy)
     }
 }
 
@@ -126,35 +127,94 @@ where
 }
 
 /// Helper function to expect a request from the stream
+///
+/// In strict mode, other messages are rejected instead of failing the handshake.
 async fn expect_request<T>(
     transport: &mut T,
     context: &str,
+    strict: bool,
 ) -> Result<(ClientRequest, RequestId), ServerInitializeError>
 where
-    T: Transport<RoleServer>,
+    T: Transport<RoleServer> + 'static,
 {
-    let msg = expect_next_message(transport, context).await?;
-    let msg_clone = msg.clone();
-    msg.into_request()
-        .ok_or(ServerInitializeError::ExpectedInitializeRequest(Some(
-            msg_clone,
-        )))
+    loop {
+        let msg = expect_next_message(transport, context).await?;
+        if strict
+            && !matches!(
+                &msg,
+                ClientJsonRpcMessage::Request(request)
+                    if matches!(request.request, ClientRequest::InitializeRequest(_))
+            )
+        {
+            reject_early_message(transport, msg).await?;
+            continue;
+        }
+        let msg_clone = msg.clone();
+        return msg
+            .into_request()
+            .ok_or(ServerInitializeError::ExpectedInitializeRequest(Some(
+                msg_clone,
+            )));
+    }
 }
 
 /// Helper function to expect a notification from the stream
+///
+/// In strict mode, other messages are rejected instead of failing the handshake.
 async fn expect_notification<T>(
     transport: &mut T,
     context: &str,
+    strict: bool,
 ) -> Result<ClientNotification, ServerInitializeError>
 where
-    T: Transport<RoleServer>,
+    T: Transport<RoleServer> + 'static,
 {
-    let msg = expect_next_message(transport, context).await?;
-    let msg_clone = msg.clone();
-    msg.into_notification()
-        .ok_or(ServerInitializeError::ExpectedInitializedNotification(
-            Some(msg_clone),
-        ))
+    loop {
+        let msg = expect_next_message(transport, context).await?;
+        if strict
+            && !matches!(
+                &msg,
+                ClientJsonRpcMessage::Notification(notification)
+                    if matches!(notification.notification, ClientNotification::InitializedNotification(_))
+            )
+        {
+            reject_early_message(transport, msg).await?;
+            continue;
+        }
+        let msg_clone = msg.clone();
+        return msg.into_notification().ok_or(
+            ServerInitializeError::ExpectedInitializedNotification(Some(msg_clone)),
+        );
+    }
+}
+
+/// Answer a message received before the session is initialized, in strict mode
+///
+/// Pings are answered, other requests get an error, everything else is dropped.
+async fn reject_early_message<T>(
+    transport: &mut T,
+    message: ClientJsonRpcMessage,
+) -> Result<(), ServerInitializeError>
+where
+    T: Transport<RoleServer> + 'static,
+{
+    let ClientJsonRpcMessage::Request(JsonRpcRequest { id, request, .. }) = message else {
+        tracing::warn!(?message, "drop message received before initialization");
+        return Ok(());
+    };
+    let response = if let ClientRequest::PingRequest(_) = request {
+        ServerJsonRpcMessage::response(ServerResult::empty(()), id)
+    } else {
+        let violation = ProtocolViolation::NotInitialized {
+            method: request.method().to_owned(),
+        };
+        tracing::warn!(%id, method = request.method(), %violation, "reject request");
+        ServerJsonRpcMessage::error(violation.to_error_data(), id)
+    };
+    transport
+        .send(response)
+        .await
+        .map_err(|error| ServerInitializeError::transport::<T>(error, "sending early response"))
 }
 
 pub async fn serve_server_with_ct<S, T, E, A>(
@@ -162,13 +222,29 @@ pub async fn serve_server_with_ct<S, T, E, A>(
     transport: T,
     ct: CancellationToken,
 ) -> Result<RunningService<RoleServer, S>, ServerInitializeError>
+where
+    S: Service<RoleServer>,
+    T: IntoTransport<RoleServer, E, A>,
+    E: std::error::Error + Send + Sync + 'static,
+{
+    serve_server_with_ct_and_policy(service, transport, ct, RequestPolicy::default()).await
+}
+
+/// Like [`serve_server_with_ct`], with a [`RequestPolicy`] applied to the requests sent to the
+/// peer
+pub async fn serve_server_with_ct_and_policy<S, T, E, A>(
+    service: S,
+    transport: T,
+    ct: CancellationToken,
+    policy: RequestPolicy,
+) -> Result<RunningService<RoleServer, S>, ServerInitializeError>
 where
     S: Service<RoleServer>,
     T: IntoTransport<RoleServer, E, A>,
     E: std::error::Error + Send + Sync + 'static,
 {
     tokio::select! {
-        result = serve_server_with_ct_inner(service, transport.into_transport(), ct.clone()) => { result }
+        result = serve_server_with_ct_inner(service, transport.into_transport(), ct.clone(), policy) => { result }
         _ = ct.cancelled() => {
             Err(ServerInitializeError::Cancelled)
         }
@@ -179,6 +255,7 @@ async fn serve_server_with_ct_inner<S, T>(
     service: S,
     transport: T,
     ct: CancellationToken,
+    policy: RequestPolicy,
 ) -> Result<RunningService<RoleServer, S>, ServerInitializeError>
 where
     S: Service<RoleServer>,
@@ -188,14 +265,15 @@ where
     let id_provider = <Arc<AtomicU32RequestIdProvider>>::default();
 
     // Get initialize request
-    let (request, id) = expect_request(&mut transport, "initialized request").await?;
+    let strict = policy.strict;
+    let (request, id) = expect_request(&mut transport, "initialized request", strict).await?;
 
     let ClientRequest::InitializeRequest(peer_info) = &request else {
         return Err(ServerInitializeError::ExpectedInitializeRequest(Some(
             ClientJsonRpcMessage::request(request, id),
         )));
     };
-    let (peer, peer_rx) = Peer::new(id_provider, Some(peer_info.params.clone()));
+    let (peer, peer_rx) = Peer::new(id_provider, Some(peer_info.params.clone()), policy);
     let context = RequestContext {
         ct: ct.child_token(),
         id: id.clone(),
@@ -204,7 +282,19 @@ where
         peer: peer.clone(),
     };
     // Send initialize response
+    #[cfg(feature = "metrics")]
+    let observation = crate::metrics::RequestObservation::start(
+        false,
+        crate::metrics::Direction::Inbound,
+        request.method(),
+        None,
+    );
     let init_response = service.handle_request(request.clone(), context).await;
+    #[cfg(feature = "metrics")]
+    observation.finish(match &init_response {
+        Ok(_) => crate::metrics::RequestOutcome::Ok,
+        Err(error) => crate::metrics::RequestOutcome::Error(error.code),
+    });
     let mut init_response = match init_response {
         Ok(ServerResult::InitializeResult(init_response)) => init_response,
         Ok(result) => {
@@ -220,16 +310,50 @@ where
             return Err(ServerInitializeError::InitializeFailed(e));
         }
     };
-    let peer_protocol_version = peer_info.params.protocol_version.clone();
-    let protocol_version = match peer_protocol_version
-        .partial_cmp(&init_response.protocol_version)
-        .ok_or(ServerInitializeError::UnsupportedProtocolVersion(
-            peer_protocol_version,
-        ))? {
-        std::cmp::Ordering::Less => peer_info.params.protocol_version.clone(),
-        _ => init_response.protocol_version,
+    #[cfg(feature = "logging")]
+    if logging::is_installed() {
+        init_response
+            .capabilities
+            .logging
+            .get_or_insert_with(Default::default);
+    }
+    // answer with the newest accepted version the client can be expected to support
+    let requested = &peer_info.params.protocol_version;
+    let accepted = peer
+        .request_policy()
+        .accepted_protocol_versions(&init_response.protocol_version);
+    let Some(protocol_version) = accepted
+        .iter()
+        .rev()
+        .find(|version| *version <= requested)
+        .cloned()
+    else {
+        tracing::warn!(%requested, ?accepted, "unsupported protocol version");
+        let error = ErrorData::invalid_params(
+            "Unsupported protocol version",
+            Some(serde_json::json!({
+                "supported": accepted,
+                "requested": requested,
+            })),
+        );
+        transport
+            .send(ServerJsonRpcMessage::error(error, id))
+            .await
+            .map_err(|error| {
+                ServerInitializeError::transport::<T>(error, "sending error response")
+            })?;
+        return Err(ServerInitializeError::UnsupportedProtocolVersion(
+            requested.clone(),
+        ));
     };
-    init_response.protocol_version = protocol_version;
+    let declared = std::mem::replace(
+        &mut init_response.protocol_version,
+        protocol_version.clone(),
+    );
+    if protocol_version < declared {
+        init_response.downgrade(&protocol_version);
+    }
+    peer.set_protocol_version(protocol_version, &declared);
     transport
         .send(ServerJsonRpcMessage::response(
             ServerResult::InitializeResult(init_response),
@@ -241,7 +365,14 @@ where
         })?;
 
     // Wait for initialize notification
-    let notification = expect_notification(&mut transport, "initialize notification").await?;
+    let notification =
+        expect_notification(&mut transport, "initialize notification", strict).await?;
+    #[cfg(feature = "metrics")]
+    crate::metrics::record_notification(
+        false,
+        crate::metrics::Direction::Inbound,
+        notification.method(),
+    );
     let ClientNotification::InitializedNotification(_) = notification else {
         return Err(ServerInitializeError::ExpectedInitializedNotification(
             Some(ClientJsonRpcMessage::notification(notification)),
@@ -413,6 +544,21 @@ impl Peer<RoleServer> {
     method!(peer_not notify_resource_list_changed ResourceListChangedNotification);
     method!(peer_not notify_tool_list_changed ToolListChangedNotification);
     method!(peer_not notify_prompt_list_changed PromptListChangedNotification);
+
+    /// A stream yielding each time the client sends `notifications/roots/list_changed`, see
+    /// [`Peer::subscribe_notifications`].
+    pub fn roots_list_changes(&self) -> impl futures::Stream<Item = ()> + Send + 'static {
+        use futures::StreamExt;
+        self.subscribe_notifications().filter_map(|notification| {
+            std::future::ready(
+                matches!(
+                    notification,
+                    ClientNotification::RootsListChangedNotification(_)
+                )
+                .then_some(()),
+            )
+        })
+    }
 }
 
 // =============================================================================
@@ -0,0 +1,254 @@
+//! Strict protocol-state validation, enabled with
+//! [`RequestPolicy::with_strict`](super::RequestPolicy::with_strict).
+use thiserror::Error;
+
+use crate::{
+    error::ErrorData as McpError,
+    model::{ClientInfo, ServerInfo},
+};
+
+/// A message that is not allowed in the current state of the session.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[non_exhaustive]
+pub enum ProtocolViolation {
+    #[error("`{method}` is not allowed before the session is initialized")]
+    NotInitialized { method: String },
+    #[error("`{method}` is not allowed after the session is initialized")]
+    AlreadyInitialized { method: String },
+    #[error("`{method}` requires the `{capability}` capability, which the {role} did not declare")]
+    MissingCapability {
+        method: String,
+        capability: &'static str,
+        /// The side that should have declared the capability, `client` or `server`.
+        role: &'static str,
+    },
+}
+
+impl ProtocolViolation {
+    /// The JSON-RPC error the peer gets for a request violating the protocol.
+    pub fn to_error_data(&self) -> McpError {
+        match self {
+            ProtocolViolation::MissingCapability { .. } => McpError::new(
+                crate::model::ErrorCode::METHOD_NOT_FOUND,
+                self.to_string(),
+                None,
+            ),
+            _ => McpError::invalid_request(self.to_string(), None),
+        }
+    }
+}
+
+/// The capabilities declared by one side of a session during initialization.
+pub trait DeclaredCapabilities {
+    /// The capability needed to handle requests of `method`, if it was not declared.
+    fn missing_capability(&self, method: &str) -> Option<&'static str>;
+}
+
+impl DeclaredCapabilities for ServerInfo {
+    fn missing_capability(&self, method: &str) -> Option<&'static str> {
+        let capabilities = &self.capabilities;
+        let (declared, capability) = match method {
+            "tools/list" | "tools/call" => (capabilities.tools.is_some(), "tools"),
+            "prompts/list" | "prompts/get" => (capabilities.prompts.is_some(), "prompts"),
+            "resources/list" | "resources/templates/list" | "resources/read" => {
+                (capabilities.resources.is_some(), "resources")
+            }
+            "resources/subscribe" | "resources/unsubscribe" => (
+                capabilities
+                    .resources
+                    .as_ref()
+                    .and_then(|resources| resources.subscribe)
+                    .unwrap_or_default(),
+                "resources.subscribe",
+            ),
+            "logging/setLevel" => (capabilities.logging.is_some(), "logging"),
+            "completion/complete" => (capabilities.completions.is_some(), "completions"),
+            "tasks/get" | "tasks/list" | "tasks/result" | "tasks/cancel" => {
+                (capabilities.tasks.is_some(), "tasks")
+            }
+            _ => return None,
+        };
+        (!declared).then_some(capability)
+    }
+}
+
+impl DeclaredCapabilities for ClientInfo {
+    fn missing_capability(&self, method: &str) -> Option<&'static str> {
+        let capabilities = &self.capabilities;
+        let (declared, capability) = match method {
+            "sampling/createMessage" => (capabilities.sampling.is_some(), "sampling"),
+            "roots/list" => (capabilities.roots.is_some(), "roots"),
+            "elicitation/create" => (capabilities.elicitation.is_some(), "elicitation"),
+            "tasks/get" | "tasks/list" | "tasks/result" | "tasks/cancel" => {
+                (capabilities.tasks.is_some(), "tasks")
+            }
+            _ => return None,
+        };
+        (!declared).then_some(capability)
+    }
+}
+
+pub(crate) fn role(is_client: bool) -> &'static str {
+    if is_client { "client" } else { "server" }
+}
+
+/// Check a request before it is sent to a peer which declared `peer_info`.
+pub(crate) fn check_outbound_request<I: DeclaredCapabilities>(
+    method: &str,
+    peer_info: Option<&I>,
+    is_client: bool,
+) -> Result<(), ProtocolViolation> {
+    if matches!(method, "ping" | "initialize") {
+        return Ok(());
+    }
+    let Some(peer_info) = peer_info else {
+        return Err(ProtocolViolation::NotInitialized {
+            method: method.to_owned(),
+        });
+    };
+    match peer_info.missing_capability(method) {
+        Some(capability) => Err(ProtocolViolation::MissingCapability {
+            method: method.to_owned(),
+            capability,
+            role: role(!is_client),
+        }),
+        None => Ok(()),
+    }
+}
+
+/// The lifecycle of a session, as seen by the side receiving messages.
+#[derive(Debug)]
+pub(crate) struct ProtocolState {
+    initialize_received: bool,
+    initialized: bool,
+}
+
+impl ProtocolState {
+    pub(crate) fn new(initialized: bool) -> Self {
+        Self {
+            initialize_received: initialized,
+            initialized,
+        }
+    }
+
+    /// Check a request received from the peer, `local_info` being what the local side declared.
+    pub(crate) fn check_request<I: DeclaredCapabilities>(
+        &mut self,
+        method: &str,
+        local_info: &I,
+        is_client: bool,
+    ) -> Result<(), ProtocolViolation> {
+        match method {
+            "ping" => Ok(()),
+            "initialize" if self.initialize_received => {
+                Err(ProtocolViolation::AlreadyInitialized {
+                    method: method.to_owned(),
+                })
+            }
+            "initialize" => {
+                self.initialize_received = true;
+                Ok(())
+            }
+            _ if !self.initialized => Err(ProtocolViolation::NotInitialized {
+                method: method.to_owned(),
+            }),
+            _ => match local_info.missing_capability(method) {
+                Some(capability) => Err(ProtocolViolation::MissingCapability {
+                    method: method.to_owned(),
+                    capability,
+                    role: role(is_client),
+                }),
+                None => Ok(()),
+            },
+        }
+    }
+
+    /// Check a notification received from the peer.
+    pub(crate) fn check_notification(&mut self, method: &str) -> Result<(), ProtocolViolation> {
+        match method {
+            "notifications/initialized" if self.initialized => {
+                Err(ProtocolViolation::AlreadyInitialized {
+                    method: method.to_owned(),
+                })
+            }
+            "notifications/initialized" if self.initialize_received => {
+                self.initialized = true;
+                Ok(())
+            }
+            "notifications/cancelled" | "notifications/progress" | "notifications/message" => {
+                Ok(())
+            }
+            _ if !self.initialized => Err(ProtocolViolation::NotInitialized {
+                method: method.to_owned(),
+            }),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{ClientCapabilities, ServerCapabilities};
+
+    #[test]
+    fn test_lifecycle() {
+        let info = ServerInfo {
+            capabilities: ServerCapabilities::builder().enable_tools().build(),
+            ..Default::default()
+        };
+        let mut state = ProtocolState::new(false);
+        assert!(state.check_request("ping", &info, false).is_ok());
+        assert!(matches!(
+            state.check_request("tools/list", &info, false),
+            Err(ProtocolViolation::NotInitialized { .. })
+        ));
+        assert!(state.check_request("initialize", &info, false).is_ok());
+        assert!(state.check_notification("notifications/progress").is_ok());
+        assert!(
+            state
+                .check_notification("notifications/roots/list_changed")
+                .is_err()
+        );
+        assert!(
+            state
+                .check_notification("notifications/initialized")
+                .is_ok()
+        );
+        assert!(state.check_request("tools/list", &info, false).is_ok());
+        assert!(matches!(
+            state.check_request("initialize", &info, false),
+            Err(ProtocolViolation::AlreadyInitialized { .. })
+        ));
+        assert_eq!(
+            state.check_request("prompts/list", &info, false),
+            Err(ProtocolViolation::MissingCapability {
+                method: "prompts/list".to_owned(),
+                capability: "prompts",
+                role: "server",
+            })
+        );
+    }
+
+    #[test]
+    fn test_outbound_capabilities() {
+        let client = ClientInfo {
+            capabilities: ClientCapabilities::builder().enable_roots().build(),
+            ..Default::default()
+        };
+        assert!(check_outbound_request("roots/list", Some(&client), false).is_ok());
+        assert!(matches!(
+            check_outbound_request("sampling/createMessage", Some(&client), false),
+            Err(ProtocolViolation::MissingCapability {
+                capability: "sampling",
+                role: "client",
+                ..
+            })
+        ));
+        assert!(matches!(
+            check_outbound_request("roots/list", None::<&ClientInfo>, false),
+            Err(ProtocolViolation::NotInitialized { .. })
+        ));
+        assert!(check_outbound_request("ping", None::<&ClientInfo>, false).is_ok());
+    }
+}
@@ -132,6 +132,14 @@ impl Default for OperationProcessor {
     }
 }
 
+#[cfg(feature = "metrics")]
+impl Drop for OperationProcessor {
+    fn drop(&mut self) {
+        // tasks still running are detached, nobody will collect them anymore
+        crate::metrics::record_tasks_finished("abandoned", self.running_tasks.len());
+    }
+}
+
 impl OperationProcessor {
     pub fn new() -> Self {
         let (task_result_sender, task_result_receiver) = mpsc::unbounded_channel();
@@ -192,13 +200,27 @@ impl OperationProcessor {
             descriptor,
         };
         self.running_tasks.insert(task_id, running_task);
+        #[cfg(feature = "metrics")]
+        crate::metrics::record_task_submitted();
     }
 
     /// Collect completed results from running tasks and remove them from the running tasks map.
     pub fn collect_completed_results(&mut self) -> Vec<TaskResult> {
         if let Some(receiver) = &mut self.task_result_receiver {
             while let Ok(result) = receiver.try_recv() {
-                self.running_tasks.remove(&result.descriptor.operation_id);
+                let _was_running = self
+                    .running_tasks
+                    .remove(&result.descriptor.operation_id)
+                    .is_some();
+                #[cfg(feature = "metrics")]
+                if _was_running {
+                    let status = if result.result.is_ok() {
+                        "completed"
+                    } else {
+                        "failed"
+                    };
+                    crate::metrics::record_tasks_finished(status, 1);
+                }
                 self.completed_results.push(result);
             }
         }
@@ -219,6 +241,8 @@ impl OperationProcessor {
             }
         }
 
+        #[cfg(feature = "metrics")]
+        crate::metrics::record_tasks_finished("timed_out", timed_out_tasks.len());
         for task_id in timed_out_tasks {
             if let Some(task) = self.running_tasks.remove(&task_id) {
                 let timeout_result = TaskResult {
@@ -237,6 +261,8 @@ impl OperationProcessor {
 
     /// Cancel all running tasks.
     pub fn cancel_all_tasks(&mut self) {
+        #[cfg(feature = "metrics")]
+        crate::metrics::record_tasks_finished("cancelled", self.running_tasks.len());
         for (_, task) in self.running_tasks.drain() {
             task.task_handle.abort();
         }
@@ -268,6 +294,8 @@ impl OperationProcessor {
     pub fn cancel_task(&mut self, task_id: &str) -> bool {
         if let Some(task) = self.running_tasks.remove(task_id) {
             task.task_handle.abort();
+            #[cfg(feature = "metrics")]
+            crate::metrics::record_tasks_finished("cancelled", 1);
             // Insert a cancelled result so callers can observe the terminal state.
             let cancel_result = TaskResult {
                 descriptor: task.descriptor,
@@ -14,6 +14,8 @@ use tokio_util::{
 };
 
 use super::{IntoTransport, Transport};
+#[cfg(feature = "metrics")]
+use crate::metrics::Direction;
 use crate::service::{RxJsonRpcMessage, ServiceRole, TxJsonRpcMessage};
 
 pub enum TransportAdapterAsyncRW {}
@@ -342,6 +344,8 @@ impl<T: DeserializeOwned> Decoder for JsonRpcMessageCodec<T> {
                     let newline_index = offset + self.next_index;
                     self.next_index = 0;
                     let line = buf.split_to(newline_index + 1);
+                    #[cfg(feature = "metrics")]
+                    record_transport_bytes(Direction::Inbound, line.len());
                     let line = &line[..line.len() - 1];
                     let line = without_carriage_return(line);
 
@@ -379,6 +383,8 @@ impl<T: DeserializeOwned> Decoder for JsonRpcMessageCodec<T> {
                     None
                 } else {
                     let line = buf.split_to(buf.len());
+                    #[cfg(feature = "metrics")]
+                    record_transport_bytes(Direction::Inbound, line.len());
                     let line = without_carriage_return(&line);
 
                     // Use compatibility handling function
@@ -397,12 +403,21 @@ impl<T: Serialize> Encoder<T> for JsonRpcMessageCodec<T> {
     type Error = JsonRpcMessageCodecError;
 
     fn encode(&mut self, item: T, buf: &mut BytesMut) -> Result<(), JsonRpcMessageCodecError> {
+        #[cfg(feature = "metrics")]
+        let start = buf.len();
         serde_json::to_writer(buf.writer(), &item)?;
         buf.put_u8(b'\n');
+        #[cfg(feature = "metrics")]
+        record_transport_bytes(Direction::Outbound, buf.len() - start);
         Ok(())
     }
 }
 
+#[cfg(feature = "metrics")]
+fn record_transport_bytes(direction: Direction, bytes: usize) {
+    crate::metrics::record_transport_bytes("async_rw", direction, bytes);
+}
+
 #[cfg(test)]
 mod test {
     use futures::{Sink, Stream};
@@ -80,6 +80,12 @@ pub(crate) fn sse_stream_response(
         .map(|message| {
             let mut sse = if let Some(ref msg) = message.message {
                 let data = serde_json::to_string(msg.as_ref()).expect("valid message");
+                #[cfg(feature = "metrics")]
+                crate::metrics::record_transport_bytes(
+                    "streamable_http",
+                    crate::metrics::Direction::Outbound,
+                    data.len(),
+                );
                 Sse::default().data(data)
             } else {
                 // Priming event: empty data per SEP-1699 (just "data:\n")
@@ -145,7 +151,14 @@ where
 {
     match body.collect().await {
         Ok(bytes) => {
-            match serde_json::from_reader::<_, ClientJsonRpcMessage>(bytes.aggregate().reader()) {
+            let body = bytes.aggregate();
+            #[cfg(feature = "metrics")]
+            crate::metrics::record_transport_bytes(
+                "streamable_http",
+                crate::metrics::Direction::Inbound,
+                body.remaining(),
+            );
+            match serde_json::from_reader::<_, ClientJsonRpcMessage>(body.reader()) {
                 Ok(message) => Ok(message),
                 Err(e) => {
                     let response = Response::builder()
@@ -295,6 +295,8 @@ pub struct LocalSessionWorker {
     common: CachedTx,
     event_rx: Receiver<SessionEvent>,
     session_config: SessionConfig,
+    #[cfg(feature = "metrics")]
+    _active: crate::metrics::ActiveSessionGuard,
 }
 
 impl LocalSessionWorker {
@@ -1038,6 +1040,8 @@ pub fn create_local_session(
         common,
         event_rx,
         session_config: config.clone(),
+        #[cfg(feature = "metrics")]
+        _active: crate::metrics::ActiveSessionGuard::new(),
     };
     (handle, session_worker)
 }
@@ -0,0 +1,155 @@
+//cargo test --test test_argument_validation --features "client schema-validation"
+use std::sync::Arc;
+
+use rmcp::{
+    ServerHandler, ServiceExt,
+    handler::server::{
+        router::tool::{ToolRoute, ToolRouter},
+        wrapper::Parameters,
+    },
+    model::*,
+    schemars::{self, JsonSchema},
+    service::{RoleClient, RunningService, ServiceError},
+    tool, tool_handler, tool_router,
+};
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize, JsonSchema)]
+struct OrderArgs {
+    #[schemars(range(min = 1, max = 10))]
+    quantity: u32,
+    #[schemars(regex(pattern = r"^[A-Z]{3}$"))]
+    product: String,
+    #[schemars(length(max = 2))]
+    #[serde(default)]
+    notes: Vec<String>,
+}
+
+#[derive(Debug, Clone)]
+struct Shop {
+    tool_router: ToolRouter<Self>,
+}
+
+#[tool_router]
+impl Shop {
+    fn new() -> Self {
+        let mut tool_router = Self::tool_router().with_argument_validation();
+        tool_router.add_route(ToolRoute::new_dyn(
+            Tool::new(
+                "refund",
+                "Refund an order",
+                Arc::new(object(serde_json::json!({
+                    "type": "object",
+                    "properties": {
+                        "reason": { "enum": ["damaged", "late"] }
+                    },
+                    "required": ["reason"]
+                }))),
+            ),
+            |_| Box::pin(async { Ok(CallToolResult::success(vec![Content::text("refunded")])) }),
+        ));
+        Self { tool_router }
+    }
+
+    #[tool(description = "Order a product")]
+    fn order(&self, Parameters(args): Parameters<OrderArgs>) -> String {
+        format!("{} x {} {:?}", args.quantity, args.product, args.notes)
+    }
+}
+
+#[tool_handler]
+impl ServerHandler for Shop {}
+
+async fn serve() -> anyhow::Result<RunningService<RoleClient, ()>> {
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    tokio::spawn(async move {
+        Shop::new().serve(server_transport).await?.waiting().await?;
+        anyhow::Ok(())
+    });
+    Ok(().serve(client_transport).await?)
+}
+
+async fn call(
+    client: &RunningService<RoleClient, ()>,
+    name: &'static str,
+    arguments: serde_json::Value,
+) -> Result<CallToolResult, ServiceError> {
+    client
+        .call_tool(CallToolRequestParams {
+            meta: None,
+            name: name.into(),
+            arguments: Some(object(arguments)),
+            task: None,
+        })
+        .await
+}
+
+fn failing_pointers(error: ServiceError) -> Vec<String> {
+    let ServiceError::McpError(error) = error else {
+        panic!("unexpected error {error:?}");
+    };
+    assert_eq!(error.code, ErrorCode::INVALID_PARAMS);
+    let mut pointers = error.data.expect("error data")["errors"]
+        .as_array()
+        .expect("errors")
+        .iter()
+        .map(|error| error["pointer"].as_str().expect("pointer").to_owned())
+        .collect::<Vec<_>>();
+    pointers.sort();
+    pointers
+}
+
+#[tokio::test]
+async fn test_generated_schema_constraints() -> anyhow::Result<()> {
+    let client = serve().await?;
+
+    let result = call(
+        &client,
+        "order",
+        serde_json::json!({ "quantity": 2, "product": "ABC" }),
+    )
+    .await?;
+    assert_eq!(
+        result.content[0].as_text().map(|text| text.text.as_str()),
+        Some("2 x ABC []")
+    );
+
+    let error = call(
+        &client,
+        "order",
+        serde_json::json!({ "quantity": 0, "product": "abc", "notes": ["a", "b", "c"] }),
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(failing_pointers(error), ["/notes", "/product", "/quantity"]);
+
+    client.cancel().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_hand_written_schema() -> anyhow::Result<()> {
+    let client = serve().await?;
+
+    let result = call(&client, "refund", serde_json::json!({ "reason": "late" })).await?;
+    assert_eq!(
+        result.content[0].as_text().map(|text| text.text.as_str()),
+        Some("refunded")
+    );
+
+    let error = call(
+        &client,
+        "refund",
+        serde_json::json!({ "reason": "changed mind" }),
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(failing_pointers(error), ["/reason"]);
+    let error = call(&client, "refund", serde_json::json!({}))
+        .await
+        .unwrap_err();
+    assert_eq!(failing_pointers(error), [""]);
+
+    client.cancel().await?;
+    Ok(())
+}
@@ -0,0 +1,222 @@
+//cargo test --test test_completion_router --features "client server"
+use std::collections::HashMap;
+
+use rmcp::{
+    RoleServer, ServerHandler, ServiceExt, completion,
+    handler::server::{
+        completion::CompletionValue,
+        router::{
+            Router,
+            completion::CompletionAttr,
+            prompt::{PromptRoute, PromptRouter},
+        },
+        wrapper::Parameters,
+    },
+    model::*,
+    prompt, prompt_handler, prompt_router,
+    schemars::{self, JsonSchema},
+    service::RequestContext,
+};
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(rename_all = "UPPERCASE")]
+enum Operation {
+    Select,
+    Insert,
+    Update,
+    Delete,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+struct SqlQueryArgs {
+    operation: Operation,
+    table: String,
+    columns: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+struct SqlServer {
+    prompt_router: PromptRouter<Self>,
+}
+
+#[prompt_router]
+impl SqlServer {
+    fn new() -> Self {
+        Self {
+            prompt_router: Self::prompt_router(),
+        }
+    }
+
+    /// Build a SQL query
+    #[prompt]
+    async fn sql_query(&self, Parameters(args): Parameters<SqlQueryArgs>) -> Vec<PromptMessage> {
+        vec![PromptMessage::new_text(
+            PromptMessageRole::User,
+            format!("{:?} {} {:?}", args.operation, args.table, args.columns),
+        )]
+    }
+
+    #[completion(prompt = "sql_query", argument = "table")]
+    async fn tables(&self, CompletionValue(value): CompletionValue) -> Vec<String> {
+        (0..250)
+            .map(|i| format!("table_{i:03}"))
+            .filter(|table| table.starts_with(&value))
+            .collect()
+    }
+
+    #[completion(prompt = "sql_query", argument = "columns")]
+    fn columns(&self, context: CompletionContext) -> Vec<&'static str> {
+        match context.get_argument("table").map(String::as_str) {
+            Some("users") => vec!["id", "name", "email"],
+            Some(_) => vec!["id"],
+            None => vec![],
+        }
+    }
+}
+
+#[prompt_handler]
+impl ServerHandler for SqlServer {
+    fn get_info(&self) -> ServerInfo {
+        ServerInfo {
+            capabilities: ServerCapabilities::builder()
+                .enable_prompts()
+                .enable_completions()
+                .build(),
+            ..Default::default()
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_prompt_completions() -> anyhow::Result<()> {
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    tokio::spawn(async move {
+        SqlServer::new()
+            .serve(server_transport)
+            .await?
+            .waiting()
+            .await?;
+        anyhow::Ok(())
+    });
+    let client = ().serve(client_transport).await?;
+
+    // generated from the enum
+    let values = client
+        .complete_prompt_simple("sql_query", "operation", "")
+        .await?;
+    assert_eq!(values, ["SELECT", "INSERT", "UPDATE", "DELETE"]);
+    let values = client
+        .complete_prompt_simple("sql_query", "operation", "up")
+        .await?;
+    assert_eq!(values, ["UPDATE"]);
+
+    // truncated to the limit
+    let completion = client
+        .complete_prompt_argument("sql_query", "table", "table_", None)
+        .await?;
+    assert_eq!(completion.values.len(), 100);
+    assert_eq!(completion.total, Some(250));
+    assert!(completion.has_more_results());
+    let completion = client
+        .complete_prompt_argument("sql_query", "table", "table_24", None)
+        .await?;
+    assert_eq!(completion.values.len(), 10);
+    assert!(!completion.has_more_results());
+
+    // depends on the arguments already resolved
+    let context = CompletionContext::with_arguments(HashMap::from([(
+        "table".to_owned(),
+        "users".to_owned(),
+    )]));
+    let completion = client
+        .complete_prompt_argument("sql_query", "columns", "", Some(context))
+        .await?;
+    assert_eq!(completion.values, ["id", "name", "email"]);
+    let values = client
+        .complete_prompt_simple("sql_query", "columns", "")
+        .await?;
+    assert!(values.is_empty());
+
+    // unknown arguments and prompts complete to nothing
+    assert!(
+        client
+            .complete_prompt_simple("sql_query", "limit", "")
+            .await?
+            .is_empty()
+    );
+    assert!(
+        client
+            .complete_prompt_simple("unknown", "table", "")
+            .await?
+            .is_empty()
+    );
+
+    client.cancel().await?;
+    Ok(())
+}
+
+#[derive(Debug, Clone, Default)]
+struct EmptyServer;
+
+impl ServerHandler for EmptyServer {}
+
+#[tokio::test]
+async fn test_router_completions() -> anyhow::Result<()> {
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    let prompts = PromptRouter::new()
+        .with_route(PromptRoute::new_dyn(
+            Prompt::new("greet", None::<String>, None),
+            |_| {
+                Box::pin(async {
+                    Ok(GetPromptResult {
+                        description: None,
+                        messages: vec![],
+                    })
+                })
+            },
+        ))
+        .with_argument_values(
+            "greet",
+            [(
+                "language".to_owned(),
+                vec![
+                    "English".to_owned(),
+                    "Esperanto".to_owned(),
+                    "French".to_owned(),
+                ],
+            )],
+        )
+        .with_completion((
+            CompletionAttr::resource("users://{id}", "id"),
+            |_: &EmptyServer, CompletionValue(value): CompletionValue| {
+                vec![format!("{value}1"), format!("{value}2")]
+            },
+        ));
+    let mut router = Router::new(EmptyServer);
+    router.prompt_router = prompts;
+    tokio::spawn(async move {
+        router.serve(server_transport).await?.waiting().await?;
+        anyhow::Ok(())
+    });
+    let client = ().serve(client_transport).await?;
+
+    let values = client
+        .complete_prompt_simple("greet", "language", "e")
+        .await?;
+    assert_eq!(values, ["English", "Esperanto"]);
+    let values = client
+        .complete_resource_simple("users://{id}", "id", "4")
+        .await?;
+    assert_eq!(values, ["41", "42"]);
+    // falls back to the service
+    assert!(
+        client
+            .complete_prompt_simple("greet", "name", "")
+            .await?
+            .is_empty()
+    );
+
+    client.cancel().await?;
+    Ok(())
+}
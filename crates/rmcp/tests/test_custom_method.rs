@@ -0,0 +1,232 @@
+use std::time::Duration;
+
+use rmcp::{
+    ClientHandler, CustomMethod, ErrorData, RoleClient, RoleServer, ServerHandler, ServiceExt,
+    handler::custom::CustomRouter,
+    model::{
+        ClientCapabilities, ClientInfo, ClientRequest, CustomNotification, CustomRequest,
+        CustomResult, ErrorCode, ServerCapabilities, ServerInfo,
+    },
+    service::{NotificationContext, RequestContext, ServiceError},
+};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tokio::sync::mpsc;
+
+#[derive(Debug, Serialize, Deserialize, CustomMethod)]
+#[custom_method(method = "acme/search", result = SearchResult, capability = "acme")]
+struct Search {
+    query: String,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct SearchResult {
+    hits: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, CustomMethod)]
+#[custom_method(method = "acme/indexed", capability = "acme")]
+struct Indexed {
+    documents: u32,
+}
+
+#[derive(Debug, Serialize, Deserialize, CustomMethod)]
+#[custom_method(method = "acme/confirm", result = Confirmation)]
+struct Confirm {
+    question: String,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Confirmation {
+    confirmed: bool,
+}
+
+struct SearchServer {
+    documents: Vec<String>,
+    indexed: mpsc::UnboundedSender<u32>,
+    custom_router: CustomRouter<Self, RoleServer>,
+}
+
+impl SearchServer {
+    fn new(indexed: mpsc::UnboundedSender<u32>) -> Self {
+        Self {
+            documents: vec!["rmcp".into(), "rust".into(), "mcp".into()],
+            indexed,
+            custom_router: CustomRouter::new()
+                .with_request::<Search, _>(|server: &Self, search, _context| {
+                    Box::pin(async move {
+                        let hits = server
+                            .documents
+                            .iter()
+                            .filter(|document| document.contains(&search.query))
+                            .cloned()
+                            .collect();
+                        Ok(SearchResult { hits })
+                    })
+                })
+                .with_notification::<Indexed, _>(|server: &Self, indexed, _context| {
+                    Box::pin(async move {
+                        let _ = server.indexed.send(indexed.documents);
+                    })
+                }),
+        }
+    }
+}
+
+impl ServerHandler for SearchServer {
+    fn get_info(&self) -> ServerInfo {
+        ServerInfo {
+            capabilities: ServerCapabilities::builder()
+                .enable_experimental_with(self.custom_router.experimental_capabilities())
+                .build(),
+            ..Default::default()
+        }
+    }
+
+    async fn on_custom_request(
+        &self,
+        request: CustomRequest,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CustomResult, ErrorData> {
+        self.custom_router
+            .handle_request(self, request, context)
+            .await
+    }
+
+    async fn on_custom_notification(
+        &self,
+        notification: CustomNotification,
+        context: NotificationContext<RoleServer>,
+    ) {
+        self.custom_router
+            .handle_notification(self, notification, context)
+            .await
+    }
+}
+
+struct ConfirmingClient {
+    custom_router: CustomRouter<Self, RoleClient>,
+}
+
+impl ClientHandler for ConfirmingClient {
+    fn get_info(&self) -> ClientInfo {
+        ClientInfo {
+            capabilities: ClientCapabilities::builder()
+                .enable_experimental_with(self.custom_router.experimental_capabilities())
+                .build(),
+            ..Default::default()
+        }
+    }
+
+    async fn on_custom_request(
+        &self,
+        request: CustomRequest,
+        context: RequestContext<RoleClient>,
+    ) -> Result<CustomResult, ErrorData> {
+        self.custom_router
+            .handle_request(self, request, context)
+            .await
+    }
+}
+
+#[tokio::test]
+async fn test_typed_custom_methods() -> anyhow::Result<()> {
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    let (indexed_tx, mut indexed_rx) = mpsc::unbounded_channel();
+    let client = ConfirmingClient {
+        custom_router: CustomRouter::new().with_request::<Confirm, _>(|_, confirm, _| {
+            Box::pin(async move {
+                Ok(Confirmation {
+                    confirmed: confirm.question.ends_with('?'),
+                })
+            })
+        }),
+    };
+    let (server, client) = tokio::join!(
+        SearchServer::new(indexed_tx).serve(server_transport),
+        client.serve(client_transport)
+    );
+    let (server, client) = (server?, client?);
+
+    let result = client
+        .send_custom::<Search>(Search { query: "mc".into() })
+        .await?;
+    assert_eq!(
+        result,
+        SearchResult {
+            hits: vec!["rmcp".into(), "mcp".into()]
+        }
+    );
+
+    client
+        .notify_custom::<Indexed>(Indexed { documents: 3 })
+        .await?;
+    let documents = tokio::time::timeout(Duration::from_secs(5), indexed_rx.recv()).await?;
+    assert_eq!(documents, Some(3));
+
+    let confirmation = server
+        .send_custom::<Confirm>(Confirm {
+            question: "continue?".into(),
+        })
+        .await?;
+    assert!(confirmation.confirmed);
+
+    let experimental = client
+        .peer_info()
+        .and_then(|info| info.capabilities.experimental.clone())
+        .expect("experimental capabilities");
+    assert_eq!(
+        serde_json::to_value(experimental)?,
+        json!({ "acme": { "methods": ["acme/search"], "notifications": ["acme/indexed"] } })
+    );
+    let experimental = server
+        .peer_info()
+        .and_then(|info| info.capabilities.experimental.clone())
+        .expect("experimental capabilities");
+    assert!(experimental.contains_key("acme/confirm"));
+
+    client.cancel().await?;
+    server.cancel().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_custom_method_errors() -> anyhow::Result<()> {
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    let (indexed_tx, _indexed_rx) = mpsc::unbounded_channel();
+    tokio::spawn(async move {
+        SearchServer::new(indexed_tx)
+            .serve(server_transport)
+            .await?
+            .waiting()
+            .await?;
+        anyhow::Ok(())
+    });
+    let client = ().serve(client_transport).await?;
+
+    let error = client
+        .send_custom::<Confirm>(Confirm {
+            question: "continue?".into(),
+        })
+        .await
+        .unwrap_err();
+    let ServiceError::McpError(error) = error else {
+        panic!("unexpected error {error:?}");
+    };
+    assert_eq!(error.code, ErrorCode::METHOD_NOT_FOUND);
+
+    let error = client
+        .send_request(ClientRequest::CustomRequest(CustomRequest::new(
+            "acme/search",
+            Some(json!({ "query": 42 })),
+        )))
+        .await
+        .unwrap_err();
+    let ServiceError::McpError(error) = error else {
+        panic!("unexpected error {error:?}");
+    };
+    assert_eq!(error.code, ErrorCode::INVALID_PARAMS);
+
+    client.cancel().await?;
+    Ok(())
+}
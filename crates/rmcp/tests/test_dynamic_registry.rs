@@ -0,0 +1,198 @@
+//cargo test --test test_dynamic_registry --features "client server"
+use std::{sync::Arc, time::Duration};
+
+use futures::StreamExt;
+use rmcp::{
+    ServerHandler, ServiceExt,
+    handler::server::{
+        registry::DynamicRegistry,
+        router::{Router, prompt::PromptRoute, tool::ToolRoute},
+    },
+    model::{
+        CallToolRequestParams, CallToolResult, Content, GetPromptRequestParams, GetPromptResult,
+        JsonObject, Prompt, PromptMessage, PromptMessageRole, ServerNotification, Tool,
+    },
+    service::{RoleClient, RunningService},
+};
+
+#[derive(Debug, Clone)]
+struct Server;
+
+impl ServerHandler for Server {}
+
+fn echo_tool(name: &'static str) -> ToolRoute<Server> {
+    ToolRoute::new_dyn(
+        Tool::new(name, "Echo the tool name", Arc::new(JsonObject::new())),
+        move |_| Box::pin(async move { Ok(CallToolResult::success(vec![Content::text(name)])) }),
+    )
+}
+
+fn greeting_prompt() -> PromptRoute<Server> {
+    PromptRoute::new_dyn(Prompt::new("greeting", None::<String>, None), |_| {
+        Box::pin(async {
+            Ok(GetPromptResult {
+                description: None,
+                messages: vec![PromptMessage::new_text(PromptMessageRole::User, "hello")],
+            })
+        })
+    })
+}
+
+async fn connect(
+    registry: &DynamicRegistry<Server>,
+) -> anyhow::Result<RunningService<RoleClient, ()>> {
+    let connected = registry.peer_count();
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    let router = Router::new(Server).with_registry(registry.clone());
+    tokio::spawn(async move {
+        router.serve(server_transport).await?.waiting().await?;
+        anyhow::Ok(())
+    });
+    let client = ().serve(client_transport).await?;
+    // the server connects the peer once initialized
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while registry.peer_count() == connected {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await?;
+    Ok(client)
+}
+
+#[tokio::test]
+async fn test_registry_changes_are_notified() -> anyhow::Result<()> {
+    let registry = DynamicRegistry::new();
+    registry.add_tool(echo_tool("first")).await;
+    let client = connect(&registry).await?;
+
+    let capabilities = &client.peer_info().expect("server info").capabilities;
+    assert_eq!(
+        capabilities
+            .tools
+            .as_ref()
+            .and_then(|tools| tools.list_changed),
+        Some(true)
+    );
+    assert_eq!(
+        capabilities
+            .prompts
+            .as_ref()
+            .and_then(|prompts| prompts.list_changed),
+        Some(true)
+    );
+    assert_eq!(client.list_all_tools().await?.len(), 1);
+
+    let mut notifications = Box::pin(client.subscribe_notifications());
+    let mut next = async || {
+        tokio::time::timeout(Duration::from_secs(5), notifications.next())
+            .await
+            .expect("notification")
+            .expect("notification stream")
+    };
+
+    registry.add_tool(echo_tool("second")).await;
+    assert!(matches!(
+        next().await,
+        ServerNotification::ToolListChangedNotification(_)
+    ));
+    let mut tools = client
+        .list_all_tools()
+        .await?
+        .into_iter()
+        .map(|tool| tool.name)
+        .collect::<Vec<_>>();
+    tools.sort();
+    assert_eq!(tools, ["first", "second"]);
+    let result = client
+        .call_tool(CallToolRequestParams {
+            meta: None,
+            name: "second".into(),
+            arguments: None,
+            task: None,
+        })
+        .await?;
+    assert_eq!(
+        result.content[0].as_text().map(|text| text.text.as_str()),
+        Some("second")
+    );
+
+    registry.add_prompt(greeting_prompt()).await;
+    assert!(matches!(
+        next().await,
+        ServerNotification::PromptListChangedNotification(_)
+    ));
+    let result = client
+        .get_prompt(GetPromptRequestParams {
+            meta: None,
+            name: "greeting".into(),
+            arguments: None,
+        })
+        .await?;
+    assert_eq!(result.messages.len(), 1);
+
+    assert!(registry.remove_tool("first").await);
+    assert!(matches!(
+        next().await,
+        ServerNotification::ToolListChangedNotification(_)
+    ));
+    assert!(!registry.remove_tool("first").await);
+    assert!(
+        client
+            .call_tool(CallToolRequestParams {
+                meta: None,
+                name: "first".into(),
+                arguments: None,
+                task: None,
+            })
+            .await
+            .is_err()
+    );
+
+    assert!(registry.remove_prompt("greeting").await);
+    assert!(matches!(
+        next().await,
+        ServerNotification::PromptListChangedNotification(_)
+    ));
+    assert!(client.list_all_prompts().await?.is_empty());
+
+    client.cancel().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_registry_notifies_every_session() -> anyhow::Result<()> {
+    let registry = DynamicRegistry::new();
+    let first = connect(&registry).await?;
+    let second = connect(&registry).await?;
+    assert_eq!(registry.peer_count(), 2);
+
+    let mut first_notifications = Box::pin(first.subscribe_notifications());
+    let mut second_notifications = Box::pin(second.subscribe_notifications());
+    registry
+        .update_tools(|tools| {
+            tools.add_route(echo_tool("a"));
+            tools.add_route(echo_tool("b"));
+        })
+        .await;
+    for notifications in [&mut first_notifications, &mut second_notifications] {
+        let notification = tokio::time::timeout(Duration::from_secs(5), notifications.next())
+            .await?
+            .expect("notification stream");
+        assert!(matches!(
+            notification,
+            ServerNotification::ToolListChangedNotification(_)
+        ));
+    }
+
+    first.cancel().await?;
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while registry.peer_count() != 1 {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await?;
+    assert_eq!(registry.notify_tool_list_changed().await, 1);
+
+    second.cancel().await?;
+    Ok(())
+}
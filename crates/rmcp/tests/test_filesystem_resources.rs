@@ -0,0 +1,269 @@
+//cargo test --test test_filesystem_resources --features "client resource-fs"
+use std::{path::Path, time::Duration};
+
+use futures::StreamExt;
+use rmcp::{
+    ErrorData, RoleServer, ServerHandler, ServiceExt,
+    handler::server::{filesystem::FileSystemResources, subscription::ResourceSubscriptions},
+    model::{
+        ErrorCode, ListResourcesResult, PaginatedRequestParams, ReadResourceRequestParams,
+        ReadResourceResult, ResourceContents, ServerCapabilities, ServerInfo, ServerNotification,
+        SubscribeRequestParams,
+    },
+    service::{RequestContext, RoleClient, RunningService, ServiceError},
+};
+
+#[derive(Debug, Clone)]
+struct FileServer {
+    files: FileSystemResources,
+    subscriptions: ResourceSubscriptions,
+}
+
+impl ServerHandler for FileServer {
+    fn get_info(&self) -> ServerInfo {
+        ServerInfo {
+            capabilities: self.subscriptions.enable_capability(
+                ServerCapabilities::builder()
+                    .enable_resources()
+                    .enable_resources_list_changed()
+                    .build(),
+            ),
+            ..Default::default()
+        }
+    }
+
+    async fn list_resources(
+        &self,
+        request: Option<PaginatedRequestParams>,
+        context: RequestContext<RoleServer>,
+    ) -> Result<ListResourcesResult, ErrorData> {
+        self.files.list_resources(request, &context.peer).await
+    }
+
+    async fn read_resource(
+        &self,
+        request: ReadResourceRequestParams,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ReadResourceResult, ErrorData> {
+        self.files.read_resource(&request.uri).await
+    }
+
+    async fn subscribe(
+        &self,
+        request: SubscribeRequestParams,
+        context: RequestContext<RoleServer>,
+    ) -> Result<(), ErrorData> {
+        self.subscriptions.on_subscribe(request, &context.peer);
+        Ok(())
+    }
+}
+
+fn write(root: &Path, relative: &str, content: impl AsRef<[u8]>) {
+    let path = root.join(relative);
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    std::fs::write(path, content).unwrap();
+}
+
+fn project() -> tempfile::TempDir {
+    let project = tempfile::tempdir().unwrap();
+    let root = project.path().join("root");
+    write(&root, "README.md", "# hello");
+    write(&root, "src/lib.rs", "pub fn answer() -> u32 { 42 }");
+    write(&root, "logo.png", [0x89, b'P', b'N', b'G', 0xff, 0x00]);
+    write(&root, ".env", "SECRET=1");
+    write(&root, ".gitignore", "*.log\nbuild/\n");
+    write(&root, "app.log", "log");
+    write(&root, "build/out.txt", "out");
+    write(&root, "docs/.ignore", "draft.md\n");
+    write(&root, "docs/draft.md", "draft");
+    write(&root, "docs/guide.md", "guide");
+    write(project.path(), "secret.txt", "outside");
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::symlink;
+        symlink(project.path().join("secret.txt"), root.join("escape.txt")).unwrap();
+        symlink(root.join("README.md"), root.join("readme-link.md")).unwrap();
+        symlink(root.join(".env"), root.join("env-link")).unwrap();
+        symlink(project.path(), root.join("parent")).unwrap();
+    }
+    project
+}
+
+async fn serve(
+    files: FileSystemResources,
+    subscriptions: ResourceSubscriptions,
+) -> anyhow::Result<RunningService<RoleClient, ()>> {
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    tokio::spawn(async move {
+        FileServer {
+            files,
+            subscriptions,
+        }
+        .serve(server_transport)
+        .await?
+        .waiting()
+        .await?;
+        anyhow::Ok(())
+    });
+    Ok(().serve(client_transport).await?)
+}
+
+async fn read(
+    client: &RunningService<RoleClient, ()>,
+    uri: &str,
+) -> Result<ResourceContents, ServiceError> {
+    let mut result = client
+        .read_resource(ReadResourceRequestParams {
+            meta: None,
+            uri: uri.to_owned(),
+        })
+        .await?;
+    Ok(result.contents.remove(0))
+}
+
+#[tokio::test]
+async fn test_list_and_read_files() -> anyhow::Result<()> {
+    let project = project();
+    let files = FileSystemResources::builder(project.path().join("root"))
+        .page_size(2)
+        .max_text_size(16)
+        .build()?;
+    let client = serve(files.clone(), ResourceSubscriptions::new()).await?;
+
+    let first_page = client.list_resources(None).await?;
+    assert_eq!(first_page.resources.len(), 2);
+    assert!(first_page.next_cursor.is_some());
+    let error = client
+        .list_resources(Some(PaginatedRequestParams {
+            meta: None,
+            cursor: Some("docs/guide.md".into()),
+        }))
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        error,
+        ServiceError::McpError(ErrorData {
+            code: ErrorCode::INVALID_PARAMS,
+            ..
+        })
+    ));
+
+    let resources = client.list_all_resources().await?;
+    let mut listed = resources
+        .iter()
+        .map(|resource| resource.title.clone().unwrap())
+        .collect::<Vec<_>>();
+    let mut expected = vec!["README.md", "docs/guide.md", "logo.png", "src/lib.rs"];
+    if cfg!(unix) {
+        expected.push("readme-link.md");
+    }
+    listed.sort();
+    expected.sort();
+    assert_eq!(listed, expected);
+
+    let readme = resources
+        .iter()
+        .find(|resource| resource.name == "README.md")
+        .unwrap();
+    assert_eq!(readme.mime_type.as_deref(), Some("text/markdown"));
+    assert_eq!(readme.size, Some(7));
+
+    let ResourceContents::TextResourceContents { text, .. } = read(&client, &readme.uri).await?
+    else {
+        panic!("README.md is text");
+    };
+    assert_eq!(text, "# hello");
+
+    let logo = files.uri_of("logo.png").unwrap();
+    let ResourceContents::BlobResourceContents {
+        blob, mime_type, ..
+    } = read(&client, &logo).await?
+    else {
+        panic!("logo.png is binary");
+    };
+    assert_eq!(blob, "iVBOR/8A");
+    assert_eq!(mime_type.as_deref(), Some("image/png"));
+
+    // larger than the max text size
+    let lib = files.uri_of("src/lib.rs").unwrap();
+    assert!(matches!(
+        read(&client, &lib).await?,
+        ResourceContents::BlobResourceContents { .. }
+    ));
+
+    let root_uri = files.uri_of("").unwrap();
+    let mut denied = vec![
+        format!("{root_uri}/../secret.txt"),
+        format!("{root_uri}/%2E%2E/secret.txt"),
+        format!("{root_uri}/src%2F..%2F..%2Fsecret.txt"),
+        files.uri_of(".env").unwrap(),
+        files.uri_of("app.log").unwrap(),
+        files.uri_of("build/out.txt").unwrap(),
+        files.uri_of("docs/draft.md").unwrap(),
+        files.uri_of("src").unwrap(),
+        files.uri_of("missing.md").unwrap(),
+        "https://example.com/README.md".to_owned(),
+    ];
+    if cfg!(unix) {
+        denied.extend([
+            files.uri_of("escape.txt").unwrap(),
+            files.uri_of("env-link").unwrap(),
+            files.uri_of("parent/secret.txt").unwrap(),
+        ]);
+        let link = files.uri_of("readme-link.md").unwrap();
+        assert!(read(&client, &link).await.is_ok());
+    }
+    for uri in denied {
+        let error = read(&client, &uri).await.unwrap_err();
+        let ServiceError::McpError(error) = error else {
+            panic!("unexpected error {error:?}");
+        };
+        assert_eq!(error.code, ErrorCode::RESOURCE_NOT_FOUND, "{uri}");
+    }
+
+    client.cancel().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_watch_files() -> anyhow::Result<()> {
+    let project = project();
+    let root = project.path().join("root");
+    let files = FileSystemResources::new(&root)?;
+    let subscriptions = ResourceSubscriptions::new();
+    let _watcher = files.watch(Duration::from_millis(20), subscriptions.clone());
+    let client = serve(files.clone(), subscriptions).await?;
+
+    let mut notifications = Box::pin(client.subscribe_notifications());
+    client.list_resources(None).await?;
+    let readme = files.uri_of("README.md").unwrap();
+    client
+        .subscribe(SubscribeRequestParams {
+            meta: None,
+            uri: readme.clone(),
+        })
+        .await?;
+
+    write(&root, "README.md", "# hello, world");
+    let notification = tokio::time::timeout(Duration::from_secs(5), notifications.next()).await?;
+    let Some(ServerNotification::ResourceUpdatedNotification(notification)) = notification else {
+        panic!("unexpected notification {notification:?}");
+    };
+    assert_eq!(notification.params.uri, readme);
+
+    // ignored files don't change the list
+    write(&root, "debug.log", "log");
+    write(&root, "notes.md", "notes");
+    let notification = tokio::time::timeout(Duration::from_secs(5), notifications.next()).await?;
+    assert!(matches!(
+        notification,
+        Some(ServerNotification::ResourceListChangedNotification(_))
+    ));
+    assert_eq!(
+        client.list_all_resources().await?.len(),
+        if cfg!(unix) { 6 } else { 5 }
+    );
+
+    client.cancel().await?;
+    Ok(())
+}
@@ -0,0 +1,161 @@
+//cargo test --test test_logging_layer --features "client server macros logging"
+use std::time::Duration;
+
+use rmcp::{
+    ClientHandler, ServerHandler, ServiceExt,
+    handler::server::router::tool::ToolRouter,
+    model::*,
+    service::{NotificationContext, RoleClient, RunningService, logging::LoggingLayer},
+    tool, tool_handler, tool_router,
+};
+use tokio::sync::mpsc;
+use tracing_subscriber::prelude::*;
+
+#[derive(Debug, Clone)]
+struct Chatty {
+    tool_router: ToolRouter<Self>,
+}
+
+#[tool_router]
+impl Chatty {
+    fn new() -> Self {
+        Self {
+            tool_router: Self::tool_router(),
+        }
+    }
+
+    #[tool(description = "Log in")]
+    fn login(&self) -> String {
+        tracing::info!(user = "alice", password = "hunter2", "logged in");
+        tracing::debug!("checked the password");
+        "welcome".into()
+    }
+
+    #[tool(description = "Log a lot")]
+    fn flood(&self) -> String {
+        for line in 0..20 {
+            tracing::warn!(line, "flooding");
+        }
+        "flooded".into()
+    }
+}
+
+#[tool_handler]
+impl ServerHandler for Chatty {}
+
+struct Collector(mpsc::UnboundedSender<LoggingMessageNotificationParam>);
+
+impl ClientHandler for Collector {
+    async fn on_logging_message(
+        &self,
+        params: LoggingMessageNotificationParam,
+        _context: NotificationContext<RoleClient>,
+    ) {
+        let _ = self.0.send(params);
+    }
+}
+
+async fn connect() -> anyhow::Result<(
+    RunningService<RoleClient, Collector>,
+    mpsc::UnboundedReceiver<LoggingMessageNotificationParam>,
+)> {
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    tokio::spawn(async move {
+        Chatty::new()
+            .serve(server_transport)
+            .await?
+            .waiting()
+            .await?;
+        anyhow::Ok(())
+    });
+    let (sender, messages) = mpsc::unbounded_channel();
+    Ok((Collector(sender).serve(client_transport).await?, messages))
+}
+
+async fn call(client: &RunningService<RoleClient, Collector>, name: &'static str) {
+    client
+        .call_tool(CallToolRequestParams {
+            meta: None,
+            name: name.into(),
+            arguments: None,
+            task: None,
+        })
+        .await
+        .expect("call tool");
+}
+
+async fn next_message(
+    messages: &mut mpsc::UnboundedReceiver<LoggingMessageNotificationParam>,
+) -> LoggingMessageNotificationParam {
+    tokio::time::timeout(Duration::from_secs(5), messages.recv())
+        .await
+        .expect("log message")
+        .expect("open channel")
+}
+
+// the current thread runtime keeps every task under the subscriber set for the test
+#[tokio::test]
+async fn test_logging_layer() -> anyhow::Result<()> {
+    let _guard = tracing_subscriber::registry()
+        .with(
+            LoggingLayer::new()
+                .with_redacted_fields(["password"])
+                .with_rate_limit(5, Duration::from_millis(200)),
+        )
+        .set_default();
+    let (client, mut messages) = connect().await?;
+    let capabilities = &client.peer_info().expect("server info").capabilities;
+    assert!(capabilities.logging.is_some());
+
+    call(&client, "login").await;
+    let message = next_message(&mut messages).await;
+    assert_eq!(message.level, LoggingLevel::Info);
+    assert_eq!(message.logger.as_deref(), Some("test_logging_layer"));
+    assert_eq!(
+        message.data,
+        serde_json::json!({
+            "message": "logged in",
+            "user": "alice",
+            "password": "[redacted]",
+        })
+    );
+
+    client
+        .set_level(SetLevelRequestParams {
+            meta: None,
+            level: LoggingLevel::Debug,
+        })
+        .await?;
+    call(&client, "login").await;
+    assert_eq!(next_message(&mut messages).await.level, LoggingLevel::Info);
+    let message = next_message(&mut messages).await;
+    assert_eq!(message.level, LoggingLevel::Debug);
+    assert_eq!(message.data["message"], "checked the password");
+
+    // a new window: the first 5 warnings of the flood get through
+    tokio::time::sleep(Duration::from_millis(250)).await;
+    call(&client, "flood").await;
+    for line in 0..5 {
+        let message = next_message(&mut messages).await;
+        assert_eq!(message.level, LoggingLevel::Warning);
+        assert_eq!(message.data["line"], line);
+    }
+    tokio::time::sleep(Duration::from_millis(250)).await;
+    call(&client, "login").await;
+    let message = next_message(&mut messages).await;
+    assert_eq!(message.logger.as_deref(), Some("rmcp"));
+    assert_eq!(message.data["dropped"], 15);
+    assert_eq!(next_message(&mut messages).await.level, LoggingLevel::Info);
+
+    client.cancel().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_logging_capability_requires_the_layer() -> anyhow::Result<()> {
+    let (client, _messages) = connect().await?;
+    let capabilities = &client.peer_info().expect("server info").capabilities;
+    assert!(capabilities.logging.is_none());
+    client.cancel().await?;
+    Ok(())
+}
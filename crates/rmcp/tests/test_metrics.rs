@@ -0,0 +1,236 @@
+use std::{any::Any, sync::OnceLock, time::Duration};
+
+use rmcp::{
+    ServerHandler, ServiceExt,
+    handler::server::{router::tool::ToolRouter, wrapper::Parameters},
+    metrics::PrometheusMetricsService,
+    model::{CallToolRequestParams, ServerCapabilities, ServerInfo},
+    object, schemars,
+    task_manager::{
+        OperationDescriptor, OperationMessage, OperationProcessor, OperationResultTransport,
+    },
+    tool, tool_handler, tool_router,
+    transport::{
+        StreamableHttpClientTransport,
+        streamable_http_server::{StreamableHttpService, session::local::LocalSessionManager},
+    },
+};
+use tokio_util::sync::CancellationToken;
+
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+struct Operands {
+    a: i32,
+    b: i32,
+}
+
+#[derive(Debug, Clone)]
+struct Calculator {
+    tool_router: ToolRouter<Self>,
+}
+
+#[tool_router]
+impl Calculator {
+    fn new() -> Self {
+        Self {
+            tool_router: Self::tool_router(),
+        }
+    }
+
+    #[tool(description = "Calculate the sum of two numbers")]
+    fn sum(&self, Parameters(Operands { a, b }): Parameters<Operands>) -> String {
+        (a + b).to_string()
+    }
+}
+
+#[tool_handler]
+impl ServerHandler for Calculator {
+    fn get_info(&self) -> ServerInfo {
+        ServerInfo {
+            capabilities: ServerCapabilities::builder().enable_tools().build(),
+            ..Default::default()
+        }
+    }
+}
+
+/// The global recorder can only be installed once per process.
+fn metrics_service() -> PrometheusMetricsService {
+    static SERVICE: OnceLock<PrometheusMetricsService> = OnceLock::new();
+    SERVICE
+        .get_or_init(|| PrometheusMetricsService::install().expect("install recorder"))
+        .clone()
+}
+
+/// Find the value of the sample of `name` whose labels include all of `labels`.
+fn sample(body: &str, name: &str, labels: &[(&str, &str)]) -> Option<f64> {
+    body.lines()
+        .filter(|line| line.starts_with(&format!("{name}{{")))
+        .find(|line| {
+            labels
+                .iter()
+                .all(|(key, value)| line.contains(&format!("{key}=\"{value}\"")))
+        })
+        .and_then(|line| line.rsplit(' ').next())
+        .and_then(|value| value.parse().ok())
+}
+
+#[tokio::test]
+async fn test_metrics_endpoint() -> anyhow::Result<()> {
+    let metrics = metrics_service();
+    let ct = CancellationToken::new();
+    let service: StreamableHttpService<Calculator, LocalSessionManager> =
+        StreamableHttpService::new(
+            || Ok(Calculator::new()),
+            Default::default(),
+            Default::default(),
+        );
+    let router = axum::Router::new()
+        .nest_service("/mcp", service)
+        .route_service("/metrics", metrics);
+    let tcp_listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+    let addr = tcp_listener.local_addr()?;
+    let handle = tokio::spawn({
+        let ct = ct.clone();
+        async move {
+            let _ = axum::serve(tcp_listener, router)
+                .with_graceful_shutdown(async move { ct.cancelled_owned().await })
+                .await;
+        }
+    });
+
+    let client = ()
+        .serve(StreamableHttpClientTransport::from_uri(format!(
+            "http://{addr}/mcp"
+        )))
+        .await?;
+    let call = |name: &'static str| CallToolRequestParams {
+        meta: None,
+        name: name.into(),
+        arguments: Some(object!({ "a": 1, "b": 2 })),
+        task: None,
+    };
+    client.call_tool(call("sum")).await?;
+    client.call_tool(call("sum")).await?;
+    client
+        .call_tool(call("missing"))
+        .await
+        .expect_err("unknown tool");
+
+    let http = reqwest::Client::new();
+    let response = http.get(format!("http://{addr}/metrics")).send().await?;
+    assert_eq!(response.status(), 200);
+    assert!(
+        response.headers()["content-type"]
+            .to_str()?
+            .starts_with("text/plain")
+    );
+    let body = response.text().await?;
+
+    let server_sum = [
+        ("role", "server"),
+        ("direction", "inbound"),
+        ("method", "tools/call"),
+        ("tool", "sum"),
+        ("status", "ok"),
+    ];
+    assert_eq!(sample(&body, "mcp_requests_total", &server_sum), Some(2.0));
+    let client_sum = [
+        ("role", "client"),
+        ("direction", "outbound"),
+        ("method", "tools/call"),
+        ("tool", "sum"),
+        ("status", "ok"),
+    ];
+    assert_eq!(sample(&body, "mcp_requests_total", &client_sum), Some(2.0));
+    for role in ["server", "client"] {
+        assert_eq!(
+            sample(
+                &body,
+                "mcp_request_errors_total",
+                &[("role", role), ("tool", "missing"), ("code", "-32602")]
+            ),
+            Some(1.0)
+        );
+    }
+    assert_eq!(
+        sample(
+            &body,
+            "mcp_request_duration_seconds_count",
+            &[("role", "server"), ("tool", "sum")]
+        ),
+        Some(2.0)
+    );
+    assert!(body.contains("mcp_request_duration_seconds_bucket{"));
+    assert_eq!(
+        sample(
+            &body,
+            "mcp_notifications_total",
+            &[
+                ("role", "server"),
+                ("direction", "inbound"),
+                ("method", "notifications/initialized")
+            ]
+        ),
+        Some(1.0)
+    );
+    for direction in ["in", "out"] {
+        let bytes = sample(
+            &body,
+            "mcp_transport_bytes_total",
+            &[("transport", "streamable_http"), ("direction", direction)],
+        );
+        assert!(
+            bytes.is_some_and(|bytes| bytes > 0.0),
+            "{direction}: {bytes:?}"
+        );
+    }
+    assert!(body.contains("mcp_sessions_active 1"), "{body}");
+
+    let response = http.post(format!("http://{addr}/metrics")).send().await?;
+    assert_eq!(response.status(), 405);
+
+    client.cancel().await?;
+    ct.cancel();
+    handle.await?;
+    Ok(())
+}
+
+struct Done(String);
+
+impl OperationResultTransport for Done {
+    fn operation_id(&self) -> &String {
+        &self.0
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+#[tokio::test]
+async fn test_task_metrics() -> anyhow::Result<()> {
+    let metrics = metrics_service();
+    let mut processor = OperationProcessor::new();
+    processor.submit_operation(OperationMessage::new(
+        OperationDescriptor::new("metrics-done", "dummy"),
+        Box::pin(async {
+            Ok(Box::new(Done("metrics-done".to_string())) as Box<dyn OperationResultTransport>)
+        }),
+    ))?;
+    processor.submit_operation(OperationMessage::new(
+        OperationDescriptor::new("metrics-cancelled", "dummy"),
+        Box::pin(std::future::pending()),
+    ))?;
+    tokio::time::sleep(Duration::from_millis(20)).await;
+    assert_eq!(processor.collect_completed_results().len(), 1);
+    assert!(processor.cancel_task("metrics-cancelled"));
+
+    let body = metrics.render();
+    for status in ["submitted", "completed", "cancelled"] {
+        assert!(
+            sample(&body, "mcp_tasks_total", &[("status", status)]).is_some_and(|n| n >= 1.0),
+            "{status}"
+        );
+    }
+    assert!(body.contains("mcp_tasks_running "));
+    Ok(())
+}
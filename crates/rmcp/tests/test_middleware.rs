@@ -0,0 +1,214 @@
+use std::sync::{
+    Arc, Mutex,
+    atomic::{AtomicUsize, Ordering},
+};
+
+use rmcp::{
+    ErrorData, RoleServer, ServerHandler, ServiceExt,
+    handler::server::{router::tool::ToolRouter, wrapper::Parameters},
+    model::{
+        CallToolRequestParams, ClientNotification, ClientRequest, ErrorCode, ServerCapabilities,
+        ServerInfo, ServerResult,
+    },
+    object, schemars,
+    service::{Middleware, Next, NotificationContext, RequestContext},
+    tool, tool_handler, tool_router,
+};
+
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+struct Operands {
+    a: i32,
+    b: i32,
+}
+
+#[derive(Debug, Clone)]
+struct Calculator {
+    tool_router: ToolRouter<Self>,
+}
+
+#[tool_router]
+impl Calculator {
+    fn new() -> Self {
+        Self {
+            tool_router: Self::tool_router(),
+        }
+    }
+
+    #[tool(description = "Calculate the sum of two numbers")]
+    fn sum(&self, Parameters(Operands { a, b }): Parameters<Operands>) -> String {
+        (a + b).to_string()
+    }
+
+    #[tool(description = "Calculate the difference of two numbers")]
+    fn sub(&self, Parameters(Operands { a, b }): Parameters<Operands>) -> String {
+        (a - b).to_string()
+    }
+}
+
+#[tool_handler]
+impl ServerHandler for Calculator {
+    fn get_info(&self) -> ServerInfo {
+        ServerInfo {
+            capabilities: ServerCapabilities::builder().enable_tools().build(),
+            ..Default::default()
+        }
+    }
+}
+
+/// Records every method it sees, and counts notifications.
+#[derive(Clone, Default)]
+struct Audit {
+    methods: Arc<Mutex<Vec<String>>>,
+    notifications: Arc<AtomicUsize>,
+}
+
+impl Middleware<RoleServer> for Audit {
+    async fn handle_request<'a>(
+        &'a self,
+        request: ClientRequest,
+        context: RequestContext<RoleServer>,
+        next: Next<'a, RoleServer>,
+    ) -> Result<ServerResult, ErrorData> {
+        self.methods
+            .lock()
+            .unwrap()
+            .push(request.method().to_owned());
+        next.handle_request(request, context).await
+    }
+
+    async fn handle_notification<'a>(
+        &'a self,
+        notification: ClientNotification,
+        context: NotificationContext<RoleServer>,
+        next: Next<'a, RoleServer>,
+    ) -> Result<(), ErrorData> {
+        self.notifications.fetch_add(1, Ordering::SeqCst);
+        next.handle_notification(notification, context).await
+    }
+}
+
+/// Rejects calls to the `sub` tool without reaching the handler.
+struct DenySub;
+
+impl Middleware<RoleServer> for DenySub {
+    async fn handle_request<'a>(
+        &'a self,
+        request: ClientRequest,
+        context: RequestContext<RoleServer>,
+        next: Next<'a, RoleServer>,
+    ) -> Result<ServerResult, ErrorData> {
+        if let ClientRequest::CallToolRequest(call) = &request {
+            if call.params.name == "sub" {
+                return Err(ErrorData::invalid_request(
+                    "tool `sub` is not allowed",
+                    None,
+                ));
+            }
+        }
+        next.handle_request(request, context).await
+    }
+}
+
+/// Rewrites the arguments of `sum` and marks the result as coming from the middleware.
+struct RewriteSum;
+
+impl Middleware<RoleServer> for RewriteSum {
+    async fn handle_request<'a>(
+        &'a self,
+        mut request: ClientRequest,
+        context: RequestContext<RoleServer>,
+        next: Next<'a, RoleServer>,
+    ) -> Result<ServerResult, ErrorData> {
+        if let ClientRequest::CallToolRequest(call) = &mut request {
+            call.params.arguments = Some(object!({ "a": 40, "b": 2 }));
+        }
+        let mut result = next.handle_request(request, context).await?;
+        if let ServerResult::CallToolResult(result) = &mut result {
+            result.meta = Some(rmcp::model::Meta(object!({ "rewritten": true })));
+        }
+        Ok(result)
+    }
+}
+
+#[tokio::test]
+async fn test_middleware_inspects_and_short_circuits() -> anyhow::Result<()> {
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    let audit = Audit::default();
+    let server = Calculator::new()
+        .with_middleware(DenySub)
+        .with_middleware(audit.clone());
+    tokio::spawn(async move {
+        server.serve(server_transport).await?.waiting().await?;
+        anyhow::Ok(())
+    });
+
+    let client = ().serve(client_transport).await?;
+    let result = client
+        .call_tool(CallToolRequestParams {
+            meta: None,
+            name: "sum".into(),
+            arguments: Some(object!({ "a": 1, "b": 2 })),
+            task: None,
+        })
+        .await?;
+    assert_eq!(
+        result.content[0].as_text().map(|t| t.text.as_str()),
+        Some("3")
+    );
+
+    let error = client
+        .call_tool(CallToolRequestParams {
+            meta: None,
+            name: "sub".into(),
+            arguments: Some(object!({ "a": 1, "b": 2 })),
+            task: None,
+        })
+        .await
+        .expect_err("sub should be denied");
+    match error {
+        rmcp::ServiceError::McpError(error) => assert_eq!(error.code, ErrorCode::INVALID_REQUEST),
+        other => panic!("unexpected error: {other:?}"),
+    }
+
+    let methods = audit.methods.lock().unwrap().clone();
+    assert_eq!(methods, ["initialize", "tools/call", "tools/call"]);
+    assert_eq!(audit.notifications.load(Ordering::SeqCst), 1);
+
+    client.cancel().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_middleware_rewrites_request_and_result() -> anyhow::Result<()> {
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    tokio::spawn(async move {
+        Calculator::new()
+            .with_middleware(RewriteSum)
+            .serve(server_transport)
+            .await?
+            .waiting()
+            .await?;
+        anyhow::Ok(())
+    });
+
+    let client = ().serve(client_transport).await?;
+    let result = client
+        .call_tool(CallToolRequestParams {
+            meta: None,
+            name: "sum".into(),
+            arguments: Some(object!({ "a": 1, "b": 2 })),
+            task: None,
+        })
+        .await?;
+    assert_eq!(
+        result.content[0].as_text().map(|t| t.text.as_str()),
+        Some("42")
+    );
+    assert_eq!(
+        result.meta.and_then(|meta| meta.get("rewritten").cloned()),
+        Some(serde_json::Value::Bool(true))
+    );
+
+    client.cancel().await?;
+    Ok(())
+}
@@ -0,0 +1,187 @@
+use std::time::Duration;
+
+use futures::StreamExt;
+use rmcp::{
+    ClientHandler, ErrorData, RoleClient, RoleServer, ServerHandler, ServiceExt,
+    model::{
+        CallToolRequestParams, CallToolResult, Content, LoggingLevel,
+        LoggingMessageNotificationParam, ResourceUpdatedNotificationParam, ServerCapabilities,
+        ServerInfo, ServerNotification,
+    },
+    service::{NotificationContext, RequestContext, RunningService},
+};
+use serde_json::json;
+use tokio::sync::mpsc;
+
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Sends a few notifications before answering any tool call.
+#[derive(Clone, Default)]
+struct NotifyingServer;
+
+impl ServerHandler for NotifyingServer {
+    fn get_info(&self) -> ServerInfo {
+        ServerInfo {
+            capabilities: ServerCapabilities::builder()
+                .enable_tools()
+                .enable_tool_list_changed()
+                .enable_logging()
+                .enable_resources()
+                .enable_resources_subscribe()
+                .build(),
+            ..Default::default()
+        }
+    }
+
+    async fn call_tool(
+        &self,
+        _request: CallToolRequestParams,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let peer = context.peer;
+        for (level, data) in [
+            (LoggingLevel::Debug, "debug"),
+            (LoggingLevel::Warning, "warning"),
+            (LoggingLevel::Critical, "critical"),
+        ] {
+            peer.notify_logging_message(LoggingMessageNotificationParam {
+                level,
+                logger: None,
+                data: json!(data),
+            })
+            .await
+            .map_err(|error| ErrorData::internal_error(error.to_string(), None))?;
+        }
+        for uri in ["file:///a.txt", "file:///b.txt"] {
+            peer.notify_resource_updated(ResourceUpdatedNotificationParam { uri: uri.into() })
+                .await
+                .map_err(|error| ErrorData::internal_error(error.to_string(), None))?;
+        }
+        peer.notify_tool_list_changed()
+            .await
+            .map_err(|error| ErrorData::internal_error(error.to_string(), None))?;
+        Ok(CallToolResult::success(vec![Content::text("done")]))
+    }
+}
+
+async fn connect<C: ClientHandler>(client: C) -> anyhow::Result<RunningService<RoleClient, C>> {
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    tokio::spawn(async move {
+        NotifyingServer
+            .serve(server_transport)
+            .await?
+            .waiting()
+            .await?;
+        anyhow::Ok(())
+    });
+    Ok(client.serve(client_transport).await?)
+}
+
+async fn next<S: futures::Stream + Unpin>(stream: &mut S) -> Option<S::Item> {
+    tokio::time::timeout(TIMEOUT, stream.next())
+        .await
+        .expect("notification not received")
+}
+
+#[tokio::test]
+async fn test_handlerless_client_streams() -> anyhow::Result<()> {
+    let client = connect(()).await?;
+    let mut all = Box::pin(client.subscribe_notifications());
+    let mut logs = Box::pin(client.log_messages(LoggingLevel::Warning));
+    let mut updates = Box::pin(client.resource_updates("file:///b.txt"));
+    let mut tool_changes = Box::pin(client.tool_list_changes());
+
+    client
+        .call_tool(CallToolRequestParams {
+            meta: None,
+            name: "notify".into(),
+            arguments: None,
+            task: None,
+        })
+        .await?;
+
+    let log = next(&mut logs).await.unwrap();
+    assert_eq!(log.level, LoggingLevel::Warning);
+    let log = next(&mut logs).await.unwrap();
+    assert_eq!(log.level, LoggingLevel::Critical);
+    let update = next(&mut updates).await.unwrap();
+    assert_eq!(update.uri, "file:///b.txt");
+    assert_eq!(next(&mut tool_changes).await, Some(()));
+
+    let mut methods = Vec::new();
+    for _ in 0..6 {
+        let notification = next(&mut all).await.unwrap();
+        methods.push(match notification {
+            ServerNotification::LoggingMessageNotification(_) => "log",
+            ServerNotification::ResourceUpdatedNotification(_) => "update",
+            ServerNotification::ToolListChangedNotification(_) => "tools",
+            _ => "other",
+        });
+    }
+    assert_eq!(methods, ["log", "log", "log", "update", "update", "tools"]);
+
+    client.cancel().await?;
+    assert!(next(&mut all).await.is_none());
+    assert!(next(&mut logs).await.is_none());
+    Ok(())
+}
+
+/// Forwards the logging messages it handles.
+struct LoggingClient(mpsc::UnboundedSender<LoggingLevel>);
+
+impl ClientHandler for LoggingClient {
+    async fn on_logging_message(
+        &self,
+        params: LoggingMessageNotificationParam,
+        _context: NotificationContext<RoleClient>,
+    ) {
+        let _ = self.0.send(params.level);
+    }
+}
+
+#[tokio::test]
+async fn test_streams_alongside_handler() -> anyhow::Result<()> {
+    let (tx, mut handled) = mpsc::unbounded_channel();
+    let client = connect(LoggingClient(tx)).await?;
+    let mut logs = Box::pin(client.log_messages(LoggingLevel::Critical));
+
+    client
+        .call_tool(CallToolRequestParams {
+            meta: None,
+            name: "notify".into(),
+            arguments: None,
+            task: None,
+        })
+        .await?;
+
+    assert_eq!(next(&mut logs).await.unwrap().level, LoggingLevel::Critical);
+    for level in [
+        LoggingLevel::Debug,
+        LoggingLevel::Warning,
+        LoggingLevel::Critical,
+    ] {
+        let received = tokio::time::timeout(TIMEOUT, handled.recv()).await?;
+        assert_eq!(received, Some(level));
+    }
+    client.cancel().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_server_roots_list_changes() -> anyhow::Result<()> {
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    let (server, client) = tokio::join!(
+        NotifyingServer.serve(server_transport),
+        ().serve(client_transport)
+    );
+    let (server, client) = (server?, client?);
+    let mut roots_changes = Box::pin(server.roots_list_changes());
+
+    client.notify_roots_list_changed().await?;
+    assert_eq!(next(&mut roots_changes).await, Some(()));
+
+    client.cancel().await?;
+    server.cancel().await?;
+    assert!(next(&mut roots_changes).await.is_none());
+    Ok(())
+}
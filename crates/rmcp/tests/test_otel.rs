@@ -0,0 +1,198 @@
+use opentelemetry::{
+    KeyValue, Value,
+    trace::{SpanId, TracerProvider as _},
+};
+use opentelemetry_sdk::trace::{InMemorySpanExporter, SdkTracerProvider, SpanData};
+use rmcp::{
+    ClientHandler, ErrorData, Peer, RoleClient, RoleServer, ServerHandler, ServiceExt,
+    handler::server::{router::tool::ToolRouter, wrapper::Parameters},
+    model::{
+        CallToolRequestParams, Content, CreateMessageRequestParams, CreateMessageResult, Role,
+        SamplingMessage, ServerCapabilities, ServerInfo,
+    },
+    object, schemars,
+    service::RequestContext,
+    tool, tool_handler, tool_router,
+};
+use tracing::Instrument;
+use tracing_subscriber::layer::SubscriberExt;
+
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+struct Operands {
+    a: i32,
+    b: i32,
+}
+
+#[derive(Debug, Clone)]
+struct Calculator {
+    tool_router: ToolRouter<Self>,
+}
+
+#[tool_router]
+impl Calculator {
+    fn new() -> Self {
+        Self {
+            tool_router: Self::tool_router(),
+        }
+    }
+
+    #[tool(description = "Calculate the sum of two numbers")]
+    fn sum(&self, Parameters(Operands { a, b }): Parameters<Operands>) -> String {
+        (a + b).to_string()
+    }
+
+    #[tool(description = "Ask the client to complete a prompt")]
+    async fn sample(&self, peer: Peer<RoleServer>) -> Result<String, ErrorData> {
+        let result = peer
+            .create_message(CreateMessageRequestParams {
+                meta: None,
+                task: None,
+                messages: vec![SamplingMessage {
+                    role: Role::User,
+                    content: Content::text("ping"),
+                }],
+                model_preferences: None,
+                system_prompt: None,
+                include_context: None,
+                temperature: None,
+                max_tokens: 16,
+                stop_sequences: None,
+                metadata: None,
+            })
+            .await
+            .map_err(|e| ErrorData::internal_error(e.to_string(), None))?;
+        Ok(result.model)
+    }
+}
+
+#[tool_handler]
+impl ServerHandler for Calculator {
+    fn get_info(&self) -> ServerInfo {
+        ServerInfo {
+            capabilities: ServerCapabilities::builder().enable_tools().build(),
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+struct Sampler;
+
+impl ClientHandler for Sampler {
+    async fn create_message(
+        &self,
+        _params: CreateMessageRequestParams,
+        _context: RequestContext<RoleClient>,
+    ) -> Result<CreateMessageResult, ErrorData> {
+        Ok(CreateMessageResult {
+            message: SamplingMessage {
+                role: Role::Assistant,
+                content: Content::text("pong"),
+            },
+            model: "test-model".to_string(),
+            stop_reason: Some(CreateMessageResult::STOP_REASON_END_TURN.to_string()),
+        })
+    }
+}
+
+fn attribute<'a>(span: &'a SpanData, key: &str) -> Option<&'a Value> {
+    span.attributes
+        .iter()
+        .find(|KeyValue { key: k, .. }| k.as_str() == key)
+        .map(|kv| &kv.value)
+}
+
+fn find_span<'a>(spans: &'a [SpanData], name: &str) -> &'a SpanData {
+    spans
+        .iter()
+        .find(|span| span.name == name)
+        .unwrap_or_else(|| panic!("no span named {name}"))
+}
+
+#[tokio::test]
+async fn test_trace_context_propagation() -> anyhow::Result<()> {
+    let exporter = InMemorySpanExporter::default();
+    let provider = SdkTracerProvider::builder()
+        .with_simple_exporter(exporter.clone())
+        .build();
+    let subscriber = tracing_subscriber::registry()
+        .with(tracing_opentelemetry::layer().with_tracer(provider.tracer("test_otel")));
+    let _guard = tracing::subscriber::set_default(subscriber);
+
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    tokio::spawn(async move {
+        Calculator::new()
+            .serve(server_transport)
+            .await?
+            .waiting()
+            .await?;
+        anyhow::Ok(())
+    });
+    let client = Sampler.serve(client_transport).await?;
+
+    let call = |name: &'static str| CallToolRequestParams {
+        meta: None,
+        name: name.into(),
+        arguments: Some(object!({ "a": 1, "b": 2 })),
+        task: None,
+    };
+    async {
+        client.call_tool(call("sum")).await?;
+        client.call_tool(call("sample")).await?;
+        client
+            .call_tool(call("missing"))
+            .await
+            .expect_err("unknown tool");
+        anyhow::Ok(())
+    }
+    .instrument(tracing::info_span!("client.operation"))
+    .await?;
+    client.cancel().await?;
+    provider.force_flush()?;
+
+    let spans = exporter.get_finished_spans()?;
+    let root = find_span(&spans, "client.operation");
+    let tool_calls: Vec<_> = spans
+        .iter()
+        .filter(|span| span.name == "tools/call")
+        .collect();
+    assert_eq!(tool_calls.len(), 3);
+    for span in &tool_calls {
+        assert_eq!(span.span_context.trace_id(), root.span_context.trace_id());
+        assert_eq!(span.parent_span_id, root.span_context.span_id());
+        assert_eq!(
+            attribute(span, "rpc.method"),
+            Some(&Value::from("tools/call"))
+        );
+        assert!(attribute(span, "rpc.jsonrpc.request_id").is_some());
+    }
+    let tool_names: Vec<_> = tool_calls
+        .iter()
+        .filter_map(|span| attribute(span, "mcp.tool.name"))
+        .map(|value| value.as_str().into_owned())
+        .collect();
+    assert_eq!(tool_names, ["sum", "sample", "missing"]);
+
+    let failed = tool_calls
+        .iter()
+        .find(|span| attribute(span, "mcp.tool.name") == Some(&Value::from("missing")))
+        .unwrap();
+    assert_eq!(
+        attribute(failed, "rpc.jsonrpc.error_code"),
+        Some(&Value::I64(-32602))
+    );
+
+    // the sampling request sent by the tool is handled in a span parented by the tool call span
+    let sample = tool_calls
+        .iter()
+        .find(|span| attribute(span, "mcp.tool.name") == Some(&Value::from("sample")))
+        .unwrap();
+    let create_message = find_span(&spans, "sampling/createMessage");
+    assert_eq!(
+        create_message.span_context.trace_id(),
+        root.span_context.trace_id()
+    );
+    assert_eq!(create_message.parent_span_id, sample.span_context.span_id());
+    assert_ne!(create_message.parent_span_id, SpanId::INVALID);
+    Ok(())
+}
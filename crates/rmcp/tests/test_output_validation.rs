@@ -0,0 +1,154 @@
+//cargo test --test test_output_validation --features "client schema-validation"
+use std::sync::Arc;
+
+use rmcp::{
+    ServerHandler, ServiceExt,
+    handler::server::{
+        router::tool::{ToolRoute, ToolRouter},
+        validation::OutputValidation,
+        wrapper::{Json, Parameters},
+    },
+    model::*,
+    schemars::{self, JsonSchema},
+    service::{RoleClient, RunningService, ServiceError},
+    tool, tool_handler, tool_router,
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Deserialize, JsonSchema)]
+struct SumArgs {
+    a: i64,
+    b: i64,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+struct Sum {
+    sum: i64,
+}
+
+fn declaring_sum(name: &'static str) -> Tool {
+    Tool::new(name, "Declares a sum", Arc::new(JsonObject::new())).with_output_schema::<Sum>()
+}
+
+#[derive(Debug, Clone)]
+struct Calculator {
+    tool_router: ToolRouter<Self>,
+}
+
+#[tool_router]
+impl Calculator {
+    fn new(validation: Option<OutputValidation>) -> Self {
+        let mut tool_router = Self::tool_router();
+        if let Some(mode) = validation {
+            tool_router = tool_router.with_output_validation(mode);
+        }
+        tool_router.add_route(ToolRoute::new_dyn(declaring_sum("wrong_type"), |_| {
+            Box::pin(async {
+                Ok(CallToolResult::structured(
+                    serde_json::json!({ "sum": "three" }),
+                ))
+            })
+        }));
+        tool_router.add_route(ToolRoute::new_dyn(declaring_sum("text_only"), |_| {
+            Box::pin(async { Ok(CallToolResult::success(vec![Content::text("3")])) })
+        }));
+        tool_router.add_route(ToolRoute::new_dyn(declaring_sum("failing"), |_| {
+            Box::pin(async { Ok(CallToolResult::error(vec![Content::text("overflow")])) })
+        }));
+        Self { tool_router }
+    }
+
+    #[tool(description = "Add two numbers")]
+    fn sum(&self, Parameters(SumArgs { a, b }): Parameters<SumArgs>) -> Json<Sum> {
+        Json(Sum { sum: a + b })
+    }
+}
+
+#[tool_handler]
+impl ServerHandler for Calculator {}
+
+async fn serve(
+    validation: Option<OutputValidation>,
+) -> anyhow::Result<RunningService<RoleClient, ()>> {
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    tokio::spawn(async move {
+        Calculator::new(validation)
+            .serve(server_transport)
+            .await?
+            .waiting()
+            .await?;
+        anyhow::Ok(())
+    });
+    Ok(().serve(client_transport).await?)
+}
+
+fn params(name: &'static str) -> CallToolRequestParams {
+    CallToolRequestParams {
+        meta: None,
+        name: name.into(),
+        arguments: Some(object(serde_json::json!({ "a": 1, "b": 2 }))),
+        task: None,
+    }
+}
+
+#[tokio::test]
+async fn test_server_output_validation() -> anyhow::Result<()> {
+    let client = serve(Some(OutputValidation::Always)).await?;
+
+    let result = client.call_tool(params("sum")).await?;
+    assert_eq!(
+        result.structured_content,
+        Some(serde_json::json!({ "sum": 3 }))
+    );
+    // error results need no structured content
+    let result = client.call_tool(params("failing")).await?;
+    assert_eq!(result.is_error, Some(true));
+
+    for (name, expected) in [
+        ("wrong_type", "doesn't match its output schema"),
+        ("text_only", "returned no structured content"),
+    ] {
+        let error = client.call_tool(params(name)).await.unwrap_err();
+        let ServiceError::McpError(error) = error else {
+            panic!("unexpected error {error:?}");
+        };
+        assert_eq!(error.code, ErrorCode::INTERNAL_ERROR);
+        assert!(error.message.contains(expected), "{}", error.message);
+    }
+
+    client.cancel().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_client_output_validation() -> anyhow::Result<()> {
+    let client = serve(None).await?;
+
+    // unknown until listed
+    client.call_tool(params("wrong_type")).await?;
+
+    client.list_all_tools().await?;
+    let error = client.call_tool(params("wrong_type")).await.unwrap_err();
+    let ServiceError::InvalidToolResult(error) = error else {
+        panic!("unexpected error {error:?}");
+    };
+    assert_eq!(
+        error.data.expect("error data")["errors"][0]["pointer"],
+        "/sum"
+    );
+    assert!(matches!(
+        client.call_tool(params("text_only")).await,
+        Err(ServiceError::InvalidToolResult(_))
+    ));
+
+    let sum: Sum = client.call_tool_typed(params("sum")).await?;
+    assert_eq!(sum, Sum { sum: 3 });
+    let error = client
+        .call_tool_typed::<Sum>(params("failing"))
+        .await
+        .unwrap_err();
+    assert!(error.to_string().contains("overflow"), "{error}");
+
+    client.cancel().await?;
+    Ok(())
+}
@@ -0,0 +1,165 @@
+//cargo test --test test_pagination --features "client server"
+use std::sync::Arc;
+
+use rmcp::{
+    ServerHandler, ServiceExt,
+    handler::server::router::{
+        Router,
+        prompt::{PromptRoute, PromptRouter},
+        resource::{ResourceRoute, ResourceRouter},
+        tool::{ToolRoute, ToolRouter},
+    },
+    model::*,
+    service::{RoleClient, RunningService, ServiceError},
+    tool, tool_handler, tool_router,
+};
+
+#[derive(Debug, Clone)]
+struct Server;
+
+impl ServerHandler for Server {}
+
+fn tools(names: &[&'static str]) -> ToolRouter<Server> {
+    names.iter().fold(ToolRouter::new(), |router, name| {
+        router.with_route(ToolRoute::new_dyn(
+            Tool::new(*name, "A tool", Arc::new(JsonObject::new())),
+            |_| Box::pin(async { Ok(CallToolResult::success(vec![])) }),
+        ))
+    })
+}
+
+fn prompts(names: &[&'static str]) -> PromptRouter<Server> {
+    names.iter().fold(PromptRouter::new(), |router, name| {
+        router.with_route(PromptRoute::new_dyn(
+            Prompt::new(*name, None::<String>, None),
+            |_| {
+                Box::pin(async {
+                    Ok(GetPromptResult {
+                        description: None,
+                        messages: vec![],
+                    })
+                })
+            },
+        ))
+    })
+}
+
+fn resources(count: usize) -> ResourceRouter<Server> {
+    (0..count).fold(ResourceRouter::new(), |router, i| {
+        router
+            .with_route(ResourceRoute::new_dyn(
+                RawResource::new(format!("file:///{i}.txt"), format!("{i}.txt")),
+                |_| Box::pin(async { Ok(ReadResourceResult { contents: vec![] }) }),
+            ))
+            .with_route(ResourceRoute::new_dyn(
+                RawResourceTemplate {
+                    uri_template: format!("file:///{i}/{{name}}"),
+                    name: format!("{i}"),
+                    title: None,
+                    description: None,
+                    mime_type: None,
+                    icons: None,
+                },
+                |_| Box::pin(async { Ok(ReadResourceResult { contents: vec![] }) }),
+            ))
+    })
+}
+
+fn page(cursor: Option<String>) -> Option<PaginatedRequestParams> {
+    Some(PaginatedRequestParams { meta: None, cursor })
+}
+
+async fn serve<S: rmcp::Service<rmcp::RoleServer>>(
+    service: S,
+) -> anyhow::Result<RunningService<RoleClient, ()>> {
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    tokio::spawn(async move {
+        service.serve(server_transport).await?.waiting().await?;
+        anyhow::Ok(())
+    });
+    Ok(().serve(client_transport).await?)
+}
+
+#[tokio::test]
+async fn test_router_pagination() -> anyhow::Result<()> {
+    let mut router = Router::new(Server);
+    router.tool_router = tools(&["e", "d", "c", "b", "a"]).with_page_size(2);
+    router.prompt_router = prompts(&["z", "y", "x"]).with_page_size(2);
+    router.resource_router = resources(3).with_page_size(2);
+    let client = serve(router).await?;
+
+    let first = client.list_tools(None).await?;
+    let names = |tools: &[Tool]| tools.iter().map(|t| t.name.to_string()).collect::<Vec<_>>();
+    assert_eq!(names(&first.tools), ["a", "b"]);
+    let second = client.list_tools(page(first.next_cursor.clone())).await?;
+    assert_eq!(names(&second.tools), ["c", "d"]);
+    let last = client.list_tools(page(second.next_cursor)).await?;
+    assert_eq!(names(&last.tools), ["e"]);
+    assert_eq!(last.next_cursor, None);
+    assert_eq!(client.list_all_tools().await?.len(), 5);
+
+    let result = client.list_prompts(None).await?;
+    assert_eq!(result.prompts.len(), 2);
+    assert!(result.next_cursor.is_some());
+    assert_eq!(client.list_all_prompts().await?.len(), 3);
+
+    let result = client.list_resources(None).await?;
+    assert_eq!(result.resources.len(), 2);
+    assert_eq!(client.list_all_resources().await?.len(), 3);
+    let result = client.list_resource_templates(None).await?;
+    assert_eq!(result.resource_templates.len(), 2);
+    assert_eq!(client.list_all_resource_templates().await?.len(), 3);
+
+    let error = client
+        .list_tools(page(Some("not a cursor".into())))
+        .await
+        .unwrap_err();
+    let ServiceError::McpError(error) = error else {
+        panic!("unexpected error {error:?}");
+    };
+    assert_eq!(error.code, ErrorCode::INVALID_PARAMS);
+
+    client.cancel().await?;
+    Ok(())
+}
+
+#[derive(Debug, Clone)]
+struct Calculator {
+    tool_router: ToolRouter<Self>,
+}
+
+#[tool_router]
+impl Calculator {
+    fn new() -> Self {
+        Self {
+            tool_router: Self::tool_router().with_page_size(1),
+        }
+    }
+
+    #[tool]
+    fn add(&self) -> String {
+        "add".into()
+    }
+
+    #[tool]
+    fn sub(&self) -> String {
+        "sub".into()
+    }
+}
+
+#[tool_handler]
+impl ServerHandler for Calculator {}
+
+#[tokio::test]
+async fn test_tool_handler_pagination() -> anyhow::Result<()> {
+    let client = serve(Calculator::new()).await?;
+
+    let first = client.list_tools(None).await?;
+    assert_eq!(first.tools[0].name, "add");
+    let second = client.list_tools(page(first.next_cursor)).await?;
+    assert_eq!(second.tools[0].name, "sub");
+    assert_eq!(second.next_cursor, None);
+
+    client.cancel().await?;
+    Ok(())
+}
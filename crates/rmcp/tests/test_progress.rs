@@ -0,0 +1,109 @@
+//cargo test --test test_progress --features "client server macros"
+use std::time::Duration;
+
+use rmcp::{
+    ClientHandler, ServerHandler, ServiceExt,
+    handler::server::{progress::Progress, router::tool::ToolRouter},
+    model::*,
+    service::{NotificationContext, RoleClient},
+    tool, tool_handler, tool_router,
+};
+use tokio::sync::mpsc;
+
+#[derive(Debug, Clone)]
+struct Worker {
+    tool_router: ToolRouter<Self>,
+}
+
+#[tool_router]
+impl Worker {
+    fn new() -> Self {
+        Self {
+            tool_router: Self::tool_router(),
+        }
+    }
+
+    #[tool(description = "Work in two steps")]
+    async fn work(&self, progress: Progress) -> String {
+        // reported at once, so only the last one is sent
+        for step in 1..=50 {
+            progress.report(step as f64, Some(100.0), Some("loading"));
+        }
+        // going back is ignored
+        progress.report(10.0, None, Some("oops"));
+
+        let step = progress.scope(50.0, 100.0);
+        for item in 1..=4 {
+            tokio::time::sleep(Duration::from_millis(150)).await;
+            step.report(item as f64, Some(4.0), Some("processing"));
+        }
+        "done".into()
+    }
+}
+
+#[tool_handler]
+impl ServerHandler for Worker {}
+
+struct Collector(mpsc::UnboundedSender<ProgressNotificationParam>);
+
+impl ClientHandler for Collector {
+    async fn on_progress(
+        &self,
+        params: ProgressNotificationParam,
+        _context: NotificationContext<RoleClient>,
+    ) {
+        let _ = self.0.send(params);
+    }
+}
+
+#[tokio::test]
+async fn test_progress_extractor() -> anyhow::Result<()> {
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    tokio::spawn(async move {
+        Worker::new()
+            .serve(server_transport)
+            .await?
+            .waiting()
+            .await?;
+        anyhow::Ok(())
+    });
+    let (sender, mut notifications) = mpsc::unbounded_channel();
+    let client = Collector(sender).serve(client_transport).await?;
+
+    let result = client
+        .call_tool(CallToolRequestParams {
+            meta: None,
+            name: "work".into(),
+            arguments: None,
+            task: None,
+        })
+        .await?;
+    assert_eq!(result.content[0].as_text().expect("text").text, "done");
+
+    let mut received = Vec::new();
+    while received
+        .last()
+        .is_none_or(|last: &ProgressNotificationParam| last.progress < 100.0)
+    {
+        let notification = tokio::time::timeout(Duration::from_secs(5), notifications.recv())
+            .await?
+            .expect("progress notification");
+        received.push(notification);
+    }
+    assert_eq!(received[0].progress, 50.0);
+    assert_eq!(received[0].message.as_deref(), Some("loading"));
+    assert!(received.len() <= 5, "{received:?}");
+    assert!(
+        received
+            .windows(2)
+            .all(|pair| pair[0].progress < pair[1].progress)
+    );
+    assert!(received.iter().all(|update| update.total == Some(100.0)));
+    assert_eq!(
+        received.last().unwrap().message.as_deref(),
+        Some("processing")
+    );
+
+    client.cancel().await?;
+    Ok(())
+}
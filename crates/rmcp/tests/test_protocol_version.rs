@@ -0,0 +1,225 @@
+use rmcp::{
+    ClientHandler, Json, Peer, RoleServer, ServerHandler, ServiceExt,
+    handler::server::{router::tool::ToolRouter, wrapper::Parameters},
+    model::{
+        CallToolRequestParams, ClientInfo, ErrorCode, ProtocolVersion, ServerCapabilities,
+        ServerInfo,
+    },
+    object, schemars,
+    service::{ClientInitializeError, RequestPolicy, ServerInitializeError},
+    tool, tool_handler, tool_router,
+};
+
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+struct Operands {
+    a: i32,
+    b: i32,
+}
+
+#[derive(Debug, serde::Serialize, schemars::JsonSchema)]
+struct Sum {
+    sum: i32,
+}
+
+#[derive(Debug, Clone)]
+struct Calculator {
+    tool_router: ToolRouter<Self>,
+    protocol_version: ProtocolVersion,
+}
+
+#[tool_router]
+impl Calculator {
+    fn new() -> Self {
+        Self::declaring(ProtocolVersion::LATEST)
+    }
+
+    fn declaring(protocol_version: ProtocolVersion) -> Self {
+        Self {
+            tool_router: Self::tool_router(),
+            protocol_version,
+        }
+    }
+
+    #[tool(title = "Sum", description = "Calculate the sum of two numbers")]
+    fn sum(&self, Parameters(Operands { a, b }): Parameters<Operands>) -> Json<Sum> {
+        Json(Sum { sum: a + b })
+    }
+
+    #[tool(description = "The negotiated protocol version")]
+    fn version(&self, peer: Peer<RoleServer>) -> String {
+        peer.protocol_version()
+            .map(ToString::to_string)
+            .unwrap_or_default()
+    }
+}
+
+#[tool_handler]
+impl ServerHandler for Calculator {
+    fn get_info(&self) -> ServerInfo {
+        ServerInfo {
+            protocol_version: self.protocol_version.clone(),
+            capabilities: ServerCapabilities::builder().enable_tools().build(),
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Client(ProtocolVersion);
+
+impl ClientHandler for Client {
+    fn get_info(&self) -> ClientInfo {
+        ClientInfo {
+            protocol_version: self.0.clone(),
+            ..Default::default()
+        }
+    }
+}
+
+fn call(name: &'static str) -> CallToolRequestParams {
+    CallToolRequestParams {
+        meta: None,
+        name: name.into(),
+        arguments: Some(object!({ "a": 1, "b": 2 })),
+        task: None,
+    }
+}
+
+#[tokio::test]
+async fn test_latest_version_keeps_new_fields() -> anyhow::Result<()> {
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    let (server, client) = tokio::join!(
+        Calculator::new().serve(server_transport),
+        Client(ProtocolVersion::LATEST).serve(client_transport),
+    );
+    let (server, client) = (server?, client?);
+    assert_eq!(ProtocolVersion::LATEST, ProtocolVersion::V_2025_03_26);
+    assert_eq!(server.protocol_version(), Some(&ProtocolVersion::LATEST));
+    assert_eq!(client.protocol_version(), Some(&ProtocolVersion::LATEST));
+
+    let tools = client.list_all_tools().await?;
+    let sum = tools.iter().find(|tool| tool.name == "sum").unwrap();
+    assert_eq!(sum.title.as_deref(), Some("Sum"));
+    assert!(sum.output_schema.is_some());
+    let result = client.call_tool(call("sum")).await?;
+    assert_eq!(
+        result.structured_content,
+        Some(serde_json::json!({ "sum": 3 }))
+    );
+
+    client.cancel().await?;
+    server.cancel().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_older_version_is_downgraded() -> anyhow::Result<()> {
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    let (server, client) = tokio::join!(
+        Calculator::declaring(ProtocolVersion::V_2025_11_25).serve(server_transport),
+        Client(ProtocolVersion::V_2025_03_26).serve(client_transport),
+    );
+    let (server, client) = (server?, client?);
+    assert_eq!(
+        client.protocol_version(),
+        Some(&ProtocolVersion::V_2025_03_26)
+    );
+    assert_eq!(
+        client.peer_info().map(|info| &info.protocol_version),
+        Some(&ProtocolVersion::V_2025_03_26)
+    );
+
+    let tools = client.list_all_tools().await?;
+    let sum = tools.iter().find(|tool| tool.name == "sum").unwrap();
+    assert!(sum.title.is_none());
+    assert!(sum.output_schema.is_none());
+
+    let result = client.call_tool(call("sum")).await?;
+    assert!(result.structured_content.is_none());
+    assert_eq!(
+        result.content[0].as_text().map(|text| text.text.as_str()),
+        Some(r#"{"sum":3}"#)
+    );
+
+    let result = client.call_tool(call("version")).await?;
+    assert_eq!(
+        result.content[0].as_text().map(|text| text.text.as_str()),
+        Some("2025-03-26")
+    );
+
+    client.cancel().await?;
+    server.cancel().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_no_overlapping_version() -> anyhow::Result<()> {
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    let (server, client) = tokio::join!(
+        Calculator::new().serve_with_policy(
+            server_transport,
+            RequestPolicy::default().with_protocol_versions([
+                ProtocolVersion::V_2025_06_18,
+                ProtocolVersion::V_2025_11_25,
+            ]),
+        ),
+        Client(ProtocolVersion::V_2025_03_26).serve(client_transport),
+    );
+    let Err(ServerInitializeError::UnsupportedProtocolVersion(requested)) = server else {
+        panic!("expected unsupported protocol version");
+    };
+    assert_eq!(requested, ProtocolVersion::V_2025_03_26);
+    let Err(ClientInitializeError::JsonRpcError(error)) = client else {
+        panic!("expected a JSON-RPC error");
+    };
+    assert_eq!(error.code, ErrorCode::INVALID_PARAMS);
+    assert_eq!(
+        error.data,
+        Some(serde_json::json!({
+            "supported": ["2025-06-18", "2025-11-25"],
+            "requested": "2025-03-26",
+        }))
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_negotiated_version() -> anyhow::Result<()> {
+    // a client newer than the server gets the server's newest version
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    let (server, client) = tokio::join!(
+        Calculator::new().serve_with_policy(
+            server_transport,
+            RequestPolicy::default().with_protocol_versions([
+                ProtocolVersion::V_2025_03_26,
+                ProtocolVersion::V_2025_06_18,
+            ]),
+        ),
+        Client(ProtocolVersion::V_2025_11_25).serve(client_transport),
+    );
+    let (server, client) = (server?, client?);
+    assert_eq!(
+        client.protocol_version(),
+        Some(&ProtocolVersion::V_2025_06_18)
+    );
+    client.cancel().await?;
+    server.cancel().await?;
+
+    // the client refuses a version it does not accept
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    let (_server, client) = tokio::join!(
+        Calculator::new().serve_with_policy(
+            server_transport,
+            RequestPolicy::default().with_protocol_versions([ProtocolVersion::V_2025_06_18]),
+        ),
+        Client(ProtocolVersion::V_2025_11_25).serve_with_policy(
+            client_transport,
+            RequestPolicy::default().with_protocol_versions([ProtocolVersion::V_2025_11_25]),
+        ),
+    );
+    let Err(ClientInitializeError::UnsupportedProtocolVersion(version)) = client else {
+        panic!("expected unsupported protocol version");
+    };
+    assert_eq!(version, ProtocolVersion::V_2025_06_18);
+    Ok(())
+}
@@ -0,0 +1,256 @@
+use std::{
+    sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    },
+    time::Duration,
+};
+
+use rmcp::{
+    ErrorData, RoleClient, RoleServer, ServerHandler, ServiceExt,
+    model::{
+        CallToolRequest, CallToolRequestParams, CallToolResult, ClientRequest, Content,
+        ListToolsResult, PaginatedRequestParams, ServerCapabilities, ServerInfo,
+    },
+    service::{
+        MethodPolicy, PeerRequestOptions, RequestContext, RequestPolicy, RetryPolicy,
+        RunningService, ServiceError,
+    },
+};
+use tokio::sync::Notify;
+
+/// Answers the first `slow_responses` requests too late.
+#[derive(Clone, Default)]
+struct SlowServer {
+    slow_responses: usize,
+    list_tools_calls: Arc<AtomicUsize>,
+    call_tool_calls: Arc<AtomicUsize>,
+    cancelled: Arc<Notify>,
+}
+
+impl SlowServer {
+    async fn respond(&self, calls: &AtomicUsize, context: &RequestContext<RoleServer>) {
+        if calls.fetch_add(1, Ordering::SeqCst) < self.slow_responses {
+            tokio::select! {
+                _ = context.ct.cancelled() => self.cancelled.notify_one(),
+                _ = tokio::time::sleep(Duration::from_secs(5)) => {}
+            }
+        }
+    }
+}
+
+impl ServerHandler for SlowServer {
+    fn get_info(&self) -> ServerInfo {
+        ServerInfo {
+            capabilities: ServerCapabilities::builder().enable_tools().build(),
+            ..Default::default()
+        }
+    }
+
+    async fn list_tools(
+        &self,
+        _request: Option<PaginatedRequestParams>,
+        context: RequestContext<RoleServer>,
+    ) -> Result<ListToolsResult, ErrorData> {
+        self.respond(&self.list_tools_calls, &context).await;
+        Ok(ListToolsResult::default())
+    }
+
+    async fn call_tool(
+        &self,
+        _request: CallToolRequestParams,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.respond(&self.call_tool_calls, &context).await;
+        Ok(CallToolResult::success(vec![Content::text("done")]))
+    }
+}
+
+async fn connect(
+    server: SlowServer,
+    policy: RequestPolicy,
+) -> anyhow::Result<RunningService<RoleClient, ()>> {
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    tokio::spawn(async move {
+        server.serve(server_transport).await?.waiting().await?;
+        anyhow::Ok(())
+    });
+    Ok(().serve_with_policy(client_transport, policy).await?)
+}
+
+fn call() -> CallToolRequestParams {
+    CallToolRequestParams {
+        meta: None,
+        name: "slow".into(),
+        arguments: None,
+        task: None,
+    }
+}
+
+const TIMEOUT: Duration = Duration::from_millis(100);
+
+fn fast_retry(max_retries: u32) -> RetryPolicy {
+    RetryPolicy::new(max_retries).with_initial_backoff(Duration::from_millis(10))
+}
+
+#[tokio::test]
+async fn test_default_timeout_cancels_request() -> anyhow::Result<()> {
+    let server = SlowServer {
+        slow_responses: 1,
+        ..Default::default()
+    };
+    let cancelled = server.cancelled.clone();
+    let client = connect(
+        server,
+        RequestPolicy::new(MethodPolicy::new().with_timeout(TIMEOUT)),
+    )
+    .await?;
+
+    let error = client.call_tool(call()).await.expect_err("timeout");
+    assert!(
+        matches!(error, ServiceError::Timeout { timeout } if timeout == TIMEOUT),
+        "{error}"
+    );
+    tokio::time::timeout(Duration::from_secs(1), cancelled.notified()).await?;
+
+    client.call_tool(call()).await?;
+    client.cancel().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_timeout_without_cancellation() -> anyhow::Result<()> {
+    let server = SlowServer {
+        slow_responses: 1,
+        ..Default::default()
+    };
+    let cancelled = server.cancelled.clone();
+    let client = connect(
+        server,
+        RequestPolicy::new(
+            MethodPolicy::new()
+                .with_timeout(TIMEOUT)
+                .with_cancel_on_timeout(false),
+        ),
+    )
+    .await?;
+
+    let error = client.call_tool(call()).await.expect_err("timeout");
+    assert!(matches!(error, ServiceError::Timeout { .. }), "{error}");
+    assert!(
+        tokio::time::timeout(Duration::from_millis(200), cancelled.notified())
+            .await
+            .is_err()
+    );
+    client.cancel().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_safe_method_is_retried() -> anyhow::Result<()> {
+    let server = SlowServer {
+        slow_responses: 2,
+        ..Default::default()
+    };
+    let list_tools_calls = server.list_tools_calls.clone();
+    let call_tool_calls = server.call_tool_calls.clone();
+    let client = connect(
+        server,
+        RequestPolicy::new(
+            MethodPolicy::new()
+                .with_timeout(TIMEOUT)
+                .with_retry(fast_retry(3)),
+        ),
+    )
+    .await?;
+
+    client.list_tools(None).await?;
+    assert_eq!(list_tools_calls.load(Ordering::SeqCst), 3);
+
+    // tools/call has side effects, so it is not retried by the default policy
+    let error = client.call_tool(call()).await.expect_err("timeout");
+    assert!(matches!(error, ServiceError::Timeout { .. }), "{error}");
+    assert_eq!(call_tool_calls.load(Ordering::SeqCst), 1);
+    client.cancel().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_retries_exhausted() -> anyhow::Result<()> {
+    let server = SlowServer {
+        slow_responses: usize::MAX,
+        ..Default::default()
+    };
+    let call_tool_calls = server.call_tool_calls.clone();
+    let client = connect(
+        server,
+        RequestPolicy::default().with_method(
+            "tools/call",
+            MethodPolicy::new()
+                .with_timeout(TIMEOUT)
+                .with_retry(fast_retry(2)),
+        ),
+    )
+    .await?;
+
+    let error = client
+        .call_tool(call())
+        .await
+        .expect_err("retries exhausted");
+    let ServiceError::RetriesExhausted { attempts, last } = error else {
+        panic!("unexpected error: {error}");
+    };
+    assert_eq!(attempts, 3);
+    assert!(matches!(*last, ServiceError::Timeout { .. }));
+    assert_eq!(call_tool_calls.load(Ordering::SeqCst), 3);
+    client.cancel().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_policy_overrides() -> anyhow::Result<()> {
+    let server = SlowServer {
+        slow_responses: 2,
+        ..Default::default()
+    };
+    let client = connect(
+        server,
+        RequestPolicy::new(MethodPolicy::new().with_timeout(Duration::from_secs(30))),
+    )
+    .await?;
+
+    let impatient = client.peer().with_request_policy(RequestPolicy::new(
+        MethodPolicy::new().with_timeout(TIMEOUT),
+    ));
+    let error = impatient.call_tool(call()).await.expect_err("timeout");
+    assert!(matches!(error, ServiceError::Timeout { .. }), "{error}");
+    assert_eq!(
+        client.peer().request_policy().default_policy.timeout,
+        Some(Duration::from_secs(30))
+    );
+
+    // an explicit timeout takes precedence over the policy
+    let request = ClientRequest::CallToolRequest(CallToolRequest {
+        method: Default::default(),
+        params: call(),
+        extensions: Default::default(),
+    });
+    let error = client
+        .send_request_with_option(
+            request,
+            PeerRequestOptions {
+                timeout: Some(TIMEOUT),
+                meta: None,
+            },
+        )
+        .await?
+        .await_response()
+        .await
+        .expect_err("timeout");
+    assert!(
+        matches!(error, ServiceError::Timeout { timeout } if timeout == TIMEOUT),
+        "{error}"
+    );
+    client.cancel().await?;
+    Ok(())
+}
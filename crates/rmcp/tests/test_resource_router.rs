@@ -0,0 +1,246 @@
+//cargo test --test test_resource_router --features "client server"
+use rmcp::{
+    ErrorData, RoleServer, ServerHandler, ServiceExt,
+    handler::server::{
+        resource::ResourceUri,
+        router::{Router, resource::ResourceRouter},
+        wrapper::Parameters,
+    },
+    model::{
+        CompleteRequestParams, CompleteResult, CompletionInfo, ErrorCode,
+        ReadResourceRequestParams, ResourceContents, ServerCapabilities, ServerInfo,
+    },
+    resource, resource_handler, resource_router,
+    service::{RequestContext, ServiceError},
+};
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize)]
+struct UserId {
+    id: u32,
+}
+
+#[derive(Debug, Deserialize)]
+struct FilePath {
+    path: String,
+}
+
+#[derive(Debug, Clone)]
+struct ResourceServer {
+    resource_router: ResourceRouter<Self>,
+}
+
+#[resource_router]
+impl ResourceServer {
+    fn new() -> Self {
+        Self {
+            resource_router: Self::resource_router(),
+        }
+    }
+
+    /// The application configuration
+    #[resource(uri = "config://app", mime_type = "application/json")]
+    async fn config(&self) -> String {
+        r#"{"debug":true}"#.to_string()
+    }
+
+    #[resource(uri = "users://{id}", name = "user")]
+    fn user(&self, Parameters(user): Parameters<UserId>) -> Result<String, ErrorData> {
+        if user.id == 0 {
+            return Err(ErrorData::invalid_params("user 0 is reserved", None));
+        }
+        Ok(format!("user {}", user.id))
+    }
+
+    #[resource(uri = "file:///{+path}")]
+    async fn file(
+        &self,
+        ResourceUri(uri): ResourceUri,
+        Parameters(file): Parameters<FilePath>,
+    ) -> Vec<ResourceContents> {
+        vec![ResourceContents::text(file.path, uri)]
+    }
+}
+
+#[resource_handler]
+impl ServerHandler for ResourceServer {
+    fn get_info(&self) -> ServerInfo {
+        ServerInfo {
+            capabilities: ServerCapabilities::builder()
+                .enable_resources()
+                .enable_completions()
+                .build(),
+            ..Default::default()
+        }
+    }
+
+    async fn complete(
+        &self,
+        request: CompleteRequestParams,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<CompleteResult, ErrorData> {
+        let values = ["42", "7"]
+            .into_iter()
+            .filter(|id| id.starts_with(&request.argument.value))
+            .map(str::to_owned)
+            .collect();
+        Ok(CompleteResult {
+            completion: CompletionInfo::with_all_values(values)
+                .map_err(|error| ErrorData::internal_error(error, None))?,
+        })
+    }
+}
+
+fn text(contents: &ResourceContents) -> (&str, &str) {
+    match contents {
+        ResourceContents::TextResourceContents { uri, text, .. } => (uri, text),
+        _ => panic!("unexpected contents {contents:?}"),
+    }
+}
+
+#[test]
+fn test_resource_router_lists() {
+    let router = ResourceServer::resource_router();
+    let resources = router.list_resources();
+    assert_eq!(resources.len(), 1);
+    assert_eq!(resources[0].uri, "config://app");
+    assert_eq!(
+        resources[0].description.as_deref(),
+        Some("The application configuration")
+    );
+    assert_eq!(resources[0].mime_type.as_deref(), Some("application/json"));
+
+    let templates = router.list_resource_templates();
+    let uri_templates: Vec<_> = templates
+        .iter()
+        .map(|template| template.uri_template.as_str())
+        .collect();
+    assert_eq!(uri_templates, ["users://{id}", "file:///{+path}"]);
+    assert_eq!(templates[0].name, "user");
+
+    assert!(router.has_route("users://7"));
+    assert!(router.has_route("file:///etc/hosts"));
+    assert!(!router.has_route("users://7/profile"));
+}
+
+#[tokio::test]
+async fn test_resource_handler() -> anyhow::Result<()> {
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    tokio::spawn(async move {
+        ResourceServer::new()
+            .serve(server_transport)
+            .await?
+            .waiting()
+            .await?;
+        anyhow::Ok(())
+    });
+    let client = ().serve(client_transport).await?;
+
+    let resources = client.list_all_resources().await?;
+    assert_eq!(resources.len(), 1);
+    let templates = client.list_all_resource_templates().await?;
+    assert_eq!(templates.len(), 2);
+
+    let read = |uri: &str| {
+        client.read_resource(ReadResourceRequestParams {
+            uri: uri.into(),
+            meta: None,
+        })
+    };
+    let result = read("config://app").await?;
+    assert_eq!(
+        text(&result.contents[0]),
+        ("config://app", r#"{"debug":true}"#)
+    );
+    let result = read("users://42").await?;
+    assert_eq!(text(&result.contents[0]), ("users://42", "user 42"));
+    let result = read("file:///docs/read%20me.md").await?;
+    assert_eq!(
+        text(&result.contents[0]),
+        ("file:///docs/read%20me.md", "docs/read me.md")
+    );
+
+    for (uri, code) in [
+        ("users://zero", ErrorCode::INVALID_PARAMS),
+        ("users://0", ErrorCode::INVALID_PARAMS),
+        ("unknown://resource", ErrorCode::RESOURCE_NOT_FOUND),
+    ] {
+        let error = read(uri).await.unwrap_err();
+        let ServiceError::McpError(error) = error else {
+            panic!("unexpected error {error:?}");
+        };
+        assert_eq!(error.code, code, "{uri}");
+    }
+
+    client.cancel().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_router_with_resources() -> anyhow::Result<()> {
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    let router =
+        Router::new(ResourceServer::new()).with_resources(ResourceServer::resource_router());
+    tokio::spawn(async move {
+        router.serve(server_transport).await?.waiting().await?;
+        anyhow::Ok(())
+    });
+    let client = ().serve(client_transport).await?;
+
+    let result = client
+        .read_resource(ReadResourceRequestParams {
+            uri: "users://7".into(),
+            meta: None,
+        })
+        .await?;
+    assert_eq!(text(&result.contents[0]), ("users://7", "user 7"));
+    assert_eq!(client.list_all_resource_templates().await?.len(), 2);
+
+    client.cancel().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_complete_resource_argument() -> anyhow::Result<()> {
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    tokio::spawn(async move {
+        ResourceServer::new()
+            .serve(server_transport)
+            .await?
+            .waiting()
+            .await?;
+        anyhow::Ok(())
+    });
+    let client = ().serve(client_transport).await?;
+
+    let templates = client.list_all_resource_templates().await?;
+    // listed in the order of the pagination cursors
+    let template = &templates[1];
+    assert_eq!(template.uri_template, "users://{id}");
+    assert_eq!(template.variable_names()?, ["id"]);
+    let values = client
+        .complete_resource_simple(template.uri_template.clone(), "id", "4")
+        .await?;
+    assert_eq!(values, ["42"]);
+
+    let error = client
+        .complete_resource_simple(template.uri_template.clone(), "name", "4")
+        .await
+        .unwrap_err();
+    let ServiceError::McpError(error) = error else {
+        panic!("unexpected error {error:?}");
+    };
+    assert_eq!(error.code, ErrorCode::INVALID_PARAMS);
+
+    let uri = template.expand(&[("id".to_owned(), "42".into())].into())?;
+    let result = client
+        .read_resource(ReadResourceRequestParams {
+            uri: uri.clone(),
+            meta: None,
+        })
+        .await?;
+    assert_eq!(text(&result.contents[0]), (uri.as_str(), "user 42"));
+
+    client.cancel().await?;
+    Ok(())
+}
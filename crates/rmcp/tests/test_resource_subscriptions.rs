@@ -0,0 +1,121 @@
+//cargo test --test test_resource_subscriptions --features "client server"
+use std::time::Duration;
+
+use futures::StreamExt;
+use rmcp::{
+    ServerHandler, ServiceExt,
+    handler::server::{router::Router, subscription::ResourceSubscriptions},
+    model::{ServerCapabilities, ServerInfo, SubscribeRequestParams, UnsubscribeRequestParams},
+    service::{RoleClient, RunningService},
+};
+
+#[derive(Debug, Clone)]
+struct Server;
+
+impl ServerHandler for Server {
+    fn get_info(&self) -> ServerInfo {
+        ServerInfo {
+            capabilities: ServerCapabilities::builder().enable_resources().build(),
+            ..Default::default()
+        }
+    }
+}
+
+async fn connect(
+    subscriptions: &ResourceSubscriptions,
+) -> anyhow::Result<RunningService<RoleClient, ()>> {
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    let router = Router::new(Server).with_subscriptions(subscriptions.clone());
+    tokio::spawn(async move {
+        router.serve(server_transport).await?.waiting().await?;
+        anyhow::Ok(())
+    });
+    Ok(().serve(client_transport).await?)
+}
+
+async fn subscribe(client: &RunningService<RoleClient, ()>, uri: &str) -> anyhow::Result<()> {
+    client
+        .subscribe(SubscribeRequestParams {
+            meta: None,
+            uri: uri.into(),
+        })
+        .await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_notify_subscribed_peers() -> anyhow::Result<()> {
+    let subscriptions = ResourceSubscriptions::new();
+    let notes = connect(&subscriptions).await?;
+    let users = connect(&subscriptions).await?;
+
+    let capability = notes
+        .peer_info()
+        .and_then(|info| info.capabilities.resources.clone())
+        .expect("resources capability");
+    assert_eq!(capability.subscribe, Some(true));
+
+    let mut note_updates = Box::pin(notes.resource_updates("file:///notes.md"));
+    let mut user_updates = Box::pin(users.resource_updates("users://42"));
+    subscribe(&notes, "file:///notes.md").await?;
+    subscribe(&users, "users://{id}").await?;
+    subscribe(&users, "file:///logs/*").await?;
+    assert_eq!(subscriptions.session_count(), 2);
+
+    assert_eq!(subscriptions.notify_updated("file:///notes.md").await, 1);
+    assert_eq!(subscriptions.notify_updated("users://42").await, 1);
+    // `*` is not a wildcard
+    assert_eq!(subscriptions.notify_updated("file:///logs/*").await, 1);
+    assert_eq!(
+        subscriptions.notify_updated("file:///logs/today.log").await,
+        0
+    );
+    let users_peer = subscriptions.subscribers("users://42").remove(0);
+    subscriptions.subscribe_prefix(&users_peer, "file:///logs/");
+    assert_eq!(
+        subscriptions.notify_updated("file:///logs/today.log").await,
+        1
+    );
+    assert_eq!(subscriptions.notify_updated("file:///other.md").await, 0);
+
+    let update = tokio::time::timeout(Duration::from_secs(5), note_updates.next()).await?;
+    assert_eq!(
+        update.map(|update| update.uri).as_deref(),
+        Some("file:///notes.md")
+    );
+    let update = tokio::time::timeout(Duration::from_secs(5), user_updates.next()).await?;
+    assert_eq!(
+        update.map(|update| update.uri).as_deref(),
+        Some("users://42")
+    );
+
+    notes
+        .unsubscribe(UnsubscribeRequestParams {
+            meta: None,
+            uri: "file:///notes.md".into(),
+        })
+        .await?;
+    assert_eq!(subscriptions.notify_updated("file:///notes.md").await, 0);
+
+    users.cancel().await?;
+    notes.cancel().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_drop_subscriptions_of_closed_sessions() -> anyhow::Result<()> {
+    let subscriptions = ResourceSubscriptions::new();
+    let client = connect(&subscriptions).await?;
+    subscribe(&client, "file:///notes.md").await?;
+    assert_eq!(subscriptions.subscribers("file:///notes.md").len(), 1);
+
+    client.cancel().await?;
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while subscriptions.session_count() > 0 {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await?;
+    assert_eq!(subscriptions.notify_updated("file:///notes.md").await, 0);
+    Ok(())
+}
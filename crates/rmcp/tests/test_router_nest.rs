@@ -0,0 +1,202 @@
+//cargo test --test test_router_nest --features "client server macros"
+use rmcp::{
+    RoleServer, ServerHandler, ServiceExt, completion,
+    handler::server::{
+        completion::CompletionValue,
+        router::{ComposeError, prompt::PromptRouter, tool::ToolRouter},
+    },
+    model::*,
+    prompt, prompt_handler, prompt_router,
+    service::RequestContext,
+    tool, tool_handler, tool_router,
+};
+
+#[derive(Debug, Clone)]
+struct Workspace {
+    tool_router: ToolRouter<Self>,
+    prompt_router: PromptRouter<Self>,
+}
+
+#[tool_router(router = git_tools)]
+impl Workspace {
+    #[tool(description = "Status of the repository")]
+    fn status(&self) -> String {
+        "git: clean".into()
+    }
+
+    #[tool(description = "Commit the changes")]
+    fn commit(&self) -> String {
+        "git: committed".into()
+    }
+}
+
+#[tool_router(router = fs_tools)]
+impl Workspace {
+    #[tool(name = "status", description = "Status of the disk")]
+    fn disk_status(&self) -> String {
+        "fs: 42% used".into()
+    }
+}
+
+#[prompt_router(router = "git_prompts")]
+impl Workspace {
+    /// Write a commit message
+    #[prompt]
+    fn message(&self) -> Vec<PromptMessage> {
+        vec![PromptMessage::new_text(
+            PromptMessageRole::User,
+            "Write a commit message",
+        )]
+    }
+
+    #[completion(prompt = "message", argument = "kind")]
+    fn kinds(&self, CompletionValue(value): CompletionValue) -> Vec<&'static str> {
+        ["feat", "fix", "chore"]
+            .into_iter()
+            .filter(|kind| kind.starts_with(&value))
+            .collect()
+    }
+}
+
+impl Workspace {
+    fn new() -> Result<Self, ComposeError> {
+        let mut tool_router = ToolRouter::new();
+        tool_router.nest("git.", Self::git_tools())?;
+        tool_router.nest("fs_", Self::fs_tools())?;
+        let mut prompt_router = PromptRouter::new();
+        prompt_router.nest("git.", Self::git_prompts())?;
+        Ok(Self {
+            tool_router,
+            prompt_router,
+        })
+    }
+}
+
+#[tool_handler]
+#[prompt_handler]
+impl ServerHandler for Workspace {
+    fn get_info(&self) -> ServerInfo {
+        ServerInfo {
+            capabilities: ServerCapabilities::builder()
+                .enable_tools()
+                .enable_prompts()
+                .enable_completions()
+                .build(),
+            ..Default::default()
+        }
+    }
+}
+
+#[test]
+fn test_collisions() {
+    let mut tools = Workspace::git_tools();
+    assert_eq!(
+        tools.try_merge(Workspace::fs_tools()),
+        Err(ComposeError::Duplicate("status".into()))
+    );
+    // nothing was added
+    assert_eq!(tools.list_all().len(), 2);
+
+    let mut tools = ToolRouter::new();
+    tools.nest("git.", Workspace::git_tools()).unwrap();
+    assert_eq!(
+        tools.nest("git.", Workspace::fs_tools()),
+        Err(ComposeError::Duplicate("git.status".into()))
+    );
+    assert_eq!(
+        tools.nest("fs/", Workspace::fs_tools()),
+        Err(ComposeError::InvalidToolName("fs/status".into()))
+    );
+    assert_eq!(tools.list_all().len(), 2);
+
+    // settings which the nested routes would lose
+    let hidden = Workspace::fs_tools().with_filter(|_, _| false);
+    assert_eq!(
+        tools.nest("fs_", hidden),
+        Err(ComposeError::NestedSetting("visibility filters"))
+    );
+    let resolved = Workspace::fs_tools().with_identity_resolver(|_| None);
+    assert_eq!(
+        tools.nest("fs_", resolved),
+        Err(ComposeError::NestedSetting("an identity resolver"))
+    );
+    assert_eq!(tools.list_all().len(), 2);
+
+    let mut prompts = Workspace::git_prompts();
+    assert_eq!(
+        prompts.try_merge(Workspace::git_prompts()),
+        Err(ComposeError::Duplicate("message".into()))
+    );
+    prompts.nest("git.", Workspace::git_prompts()).unwrap();
+    assert!(prompts.has_route("git.message"));
+    assert_eq!(
+        prompts.nest(
+            "hidden.",
+            Workspace::git_prompts().with_filter(|_, _| false)
+        ),
+        Err(ComposeError::NestedSetting("visibility filters"))
+    );
+}
+
+#[tokio::test]
+async fn test_nested_dispatch() -> anyhow::Result<()> {
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    tokio::spawn(async move {
+        Workspace::new()?
+            .serve(server_transport)
+            .await?
+            .waiting()
+            .await?;
+        anyhow::Ok(())
+    });
+    let client = ().serve(client_transport).await?;
+
+    let mut tools = client
+        .list_all_tools()
+        .await?
+        .into_iter()
+        .map(|tool| tool.name.into_owned())
+        .collect::<Vec<_>>();
+    tools.sort();
+    assert_eq!(tools, ["fs_status", "git.commit", "git.status"]);
+    for (name, expected) in [("git.status", "git: clean"), ("fs_status", "fs: 42% used")] {
+        let result = client
+            .call_tool(CallToolRequestParams {
+                meta: None,
+                name: name.into(),
+                arguments: None,
+                task: None,
+            })
+            .await?;
+        assert_eq!(result.content[0].as_text().expect("text").text, expected);
+    }
+    assert!(
+        client
+            .call_tool(CallToolRequestParams {
+                meta: None,
+                name: "status".into(),
+                arguments: None,
+                task: None,
+            })
+            .await
+            .is_err()
+    );
+
+    let prompts = client.list_all_prompts().await?;
+    assert_eq!(prompts.len(), 1);
+    assert_eq!(prompts[0].name, "git.message");
+    client
+        .get_prompt(GetPromptRequestParams {
+            meta: None,
+            name: "git.message".into(),
+            arguments: None,
+        })
+        .await?;
+    let kinds = client
+        .complete_prompt_simple("git.message", "kind", "f")
+        .await?;
+    assert_eq!(kinds, ["feat", "fix"]);
+
+    client.cancel().await?;
+    Ok(())
+}
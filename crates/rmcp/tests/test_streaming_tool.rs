@@ -0,0 +1,107 @@
+//cargo test --test test_streaming_tool --features "client server macros"
+use std::time::Duration;
+
+use futures::{StreamExt, stream::BoxStream};
+use rmcp::{
+    ServerHandler, ServiceExt,
+    handler::server::{router::tool::ToolRouter, wrapper::ContentStream},
+    model::*,
+    service::{RoleClient, RunningService},
+    tool, tool_handler, tool_router,
+};
+
+#[derive(Debug, Clone)]
+struct Builder {
+    tool_router: ToolRouter<Self>,
+}
+
+#[tool_router]
+impl Builder {
+    fn new() -> Self {
+        Self {
+            tool_router: Self::tool_router(),
+        }
+    }
+
+    #[tool(description = "Build, streaming the log")]
+    async fn build(&self) -> ContentStream<BoxStream<'static, Content>> {
+        ContentStream(
+            futures::stream::iter(["compiling", "linking", "done"])
+                .then(|line| async move {
+                    tokio::time::sleep(Duration::from_millis(10)).await;
+                    Content::text(line)
+                })
+                .boxed(),
+        )
+    }
+
+    #[tool(description = "Build, or fail before starting")]
+    fn try_build(&self) -> Result<ContentStream<futures::stream::Empty<Content>>, ErrorData> {
+        Err(ErrorData::invalid_request("nothing to build", None))
+    }
+}
+
+#[tool_handler]
+impl ServerHandler for Builder {}
+
+async fn connect() -> anyhow::Result<RunningService<RoleClient, ()>> {
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    tokio::spawn(async move {
+        Builder::new()
+            .serve(server_transport)
+            .await?
+            .waiting()
+            .await?;
+        anyhow::Ok(())
+    });
+    Ok(().serve(client_transport).await?)
+}
+
+fn call(name: &'static str) -> CallToolRequestParams {
+    CallToolRequestParams {
+        meta: None,
+        name: name.into(),
+        arguments: None,
+        task: None,
+    }
+}
+
+fn texts(content: &[Content]) -> Vec<String> {
+    content
+        .iter()
+        .map(|content| content.as_text().expect("text").text.clone())
+        .collect()
+}
+
+#[tokio::test]
+async fn test_streamed_chunks() -> anyhow::Result<()> {
+    let client = connect().await?;
+
+    let mut stream = client.call_tool_streaming(call("build")).await?;
+    let mut chunks = Vec::new();
+    while let Some(chunk) = stream.next().await {
+        chunks.push(chunk);
+    }
+    assert_eq!(texts(&chunks), ["compiling", "linking", "done"]);
+    let result = stream.result().await?;
+    assert_eq!(texts(&result.content), ["compiling", "linking", "done"]);
+
+    // a plain call gets the aggregated result
+    let result = client.call_tool(call("build")).await?;
+    assert_eq!(texts(&result.content), ["compiling", "linking", "done"]);
+
+    client.cancel().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_stream_error() -> anyhow::Result<()> {
+    let client = connect().await?;
+
+    let mut stream = client.call_tool_streaming(call("try_build")).await?;
+    assert!(stream.next().await.is_none());
+    assert!(stream.result().await.is_err());
+
+    client.cancel().await?;
+    Ok(())
+}
@@ -0,0 +1,204 @@
+use std::sync::{
+    Arc,
+    atomic::{AtomicUsize, Ordering},
+};
+
+use rmcp::{
+    ErrorData, RoleClient, RoleServer, ServerHandler, ServiceExt,
+    model::{
+        ClientJsonRpcMessage, Content, CreateMessageRequestParams, ErrorCode, ListPromptsResult,
+        PaginatedRequestParams, Role, SamplingMessage, ServerCapabilities, ServerInfo,
+        ServerJsonRpcMessage,
+    },
+    service::{ProtocolViolation, RequestContext, RequestPolicy, ServiceError},
+    transport::{IntoTransport, Transport},
+};
+use serde_json::json;
+
+/// Declares tools only, and counts the prompts requests that reach it.
+#[derive(Clone, Default)]
+struct ToolsOnly {
+    list_prompts_calls: Arc<AtomicUsize>,
+}
+
+impl ServerHandler for ToolsOnly {
+    fn get_info(&self) -> ServerInfo {
+        ServerInfo {
+            capabilities: ServerCapabilities::builder().enable_tools().build(),
+            ..Default::default()
+        }
+    }
+
+    async fn list_prompts(
+        &self,
+        _request: Option<PaginatedRequestParams>,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ListPromptsResult, ErrorData> {
+        self.list_prompts_calls.fetch_add(1, Ordering::SeqCst);
+        Ok(ListPromptsResult::default())
+    }
+}
+
+fn strict() -> RequestPolicy {
+    RequestPolicy::default().with_strict(true)
+}
+
+#[tokio::test]
+async fn test_undeclared_capability_is_rejected_by_receiver() -> anyhow::Result<()> {
+    let server = ToolsOnly::default();
+    let list_prompts_calls = server.list_prompts_calls.clone();
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    tokio::spawn(async move {
+        server
+            .serve_with_policy(server_transport, strict())
+            .await?
+            .waiting()
+            .await?;
+        anyhow::Ok(())
+    });
+    let client = ().serve(client_transport).await?;
+
+    client.list_tools(None).await?;
+    let error = client.list_prompts(None).await.expect_err("no prompts");
+    let ServiceError::McpError(error) = error else {
+        panic!("unexpected error: {error}");
+    };
+    assert_eq!(error.code, ErrorCode::METHOD_NOT_FOUND);
+    assert!(error.message.contains("prompts/list"), "{}", error.message);
+    assert_eq!(list_prompts_calls.load(Ordering::SeqCst), 0);
+    client.cancel().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_undeclared_capability_is_not_sent() -> anyhow::Result<()> {
+    let server = ToolsOnly::default();
+    let list_prompts_calls = server.list_prompts_calls.clone();
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    let (server, client) = tokio::join!(
+        server.serve_with_policy(server_transport, strict()),
+        ().serve_with_policy(client_transport, strict()),
+    );
+    let (server, client) = (server?, client?);
+
+    // the client did not declare sampling
+    let error = server
+        .create_message(CreateMessageRequestParams {
+            meta: None,
+            task: None,
+            messages: vec![SamplingMessage {
+                role: Role::User,
+                content: Content::text("ping"),
+            }],
+            model_preferences: None,
+            system_prompt: None,
+            include_context: None,
+            temperature: None,
+            max_tokens: 16,
+            stop_sequences: None,
+            metadata: None,
+        })
+        .await
+        .expect_err("no sampling");
+    assert!(
+        matches!(
+            &error,
+            ServiceError::ProtocolViolation(ProtocolViolation::MissingCapability {
+                capability: "sampling",
+                role: "client",
+                ..
+            })
+        ),
+        "{error}"
+    );
+
+    // the server did not declare prompts
+    let error = client.list_prompts(None).await.expect_err("no prompts");
+    assert!(
+        matches!(
+            &error,
+            ServiceError::ProtocolViolation(ProtocolViolation::MissingCapability {
+                capability: "prompts",
+                role: "server",
+                ..
+            })
+        ),
+        "{error}"
+    );
+    assert_eq!(list_prompts_calls.load(Ordering::SeqCst), 0);
+    client.list_tools(None).await?;
+    client.cancel().await?;
+    server.cancel().await?;
+    Ok(())
+}
+
+async fn exchange(
+    transport: &mut impl Transport<RoleClient>,
+    message: serde_json::Value,
+) -> anyhow::Result<Result<serde_json::Value, ErrorCode>> {
+    let message: ClientJsonRpcMessage = serde_json::from_value(message)?;
+    transport.send(message).await?;
+    match transport.receive().await {
+        Some(ServerJsonRpcMessage::Response(response)) => {
+            Ok(Ok(serde_json::to_value(response.result)?))
+        }
+        Some(ServerJsonRpcMessage::Error(error)) => Ok(Err(error.error.code)),
+        message => anyhow::bail!("unexpected message: {message:?}"),
+    }
+}
+
+#[tokio::test]
+async fn test_lifecycle_is_enforced() -> anyhow::Result<()> {
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    tokio::spawn(async move {
+        ToolsOnly::default()
+            .serve_with_policy(server_transport, strict())
+            .await?
+            .waiting()
+            .await?;
+        anyhow::Ok(())
+    });
+    let mut client = IntoTransport::<RoleClient, _, _>::into_transport(client_transport);
+    let list_tools = |id: u32| json!({ "jsonrpc": "2.0", "id": id, "method": "tools/list" });
+    let initialize = |id: u32| {
+        json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "method": "initialize",
+            "params": {
+                "protocolVersion": "2025-06-18",
+                "capabilities": {},
+                "clientInfo": { "name": "raw", "version": "0.0.0" }
+            }
+        })
+    };
+
+    assert_eq!(
+        exchange(&mut client, list_tools(1)).await?,
+        Err(ErrorCode::INVALID_REQUEST)
+    );
+    assert!(
+        exchange(
+            &mut client,
+            json!({ "jsonrpc": "2.0", "id": 2, "method": "ping" })
+        )
+        .await?
+        .is_ok()
+    );
+    assert!(exchange(&mut client, initialize(3)).await?.is_ok());
+    assert_eq!(
+        exchange(&mut client, list_tools(4)).await?,
+        Err(ErrorCode::INVALID_REQUEST)
+    );
+
+    let initialized: ClientJsonRpcMessage =
+        serde_json::from_value(json!({ "jsonrpc": "2.0", "method": "notifications/initialized" }))?;
+    client.send(initialized).await?;
+    assert!(exchange(&mut client, list_tools(5)).await?.is_ok());
+    assert_eq!(
+        exchange(&mut client, initialize(6)).await?,
+        Err(ErrorCode::INVALID_REQUEST)
+    );
+    client.close().await?;
+    Ok(())
+}
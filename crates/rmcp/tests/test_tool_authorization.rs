@@ -0,0 +1,174 @@
+//cargo test --test test_tool_authorization --features "client server"
+use rmcp::{
+    RoleServer, ServerHandler, ServiceExt,
+    handler::server::{
+        authorization::{Identity, ToolPolicy},
+        router::tool::{ToolRoute, ToolRouter},
+    },
+    model::*,
+    service::{RequestContext, RoleClient, RunningService, ServiceError},
+    tool, tool_router,
+};
+
+#[derive(Debug, Clone)]
+struct Repo {
+    tool_router: ToolRouter<Self>,
+}
+
+#[tool_router]
+impl Repo {
+    fn new() -> Self {
+        let mut tool_router = Self::tool_router();
+        tool_router.add_route(
+            ToolRoute::new_dyn(
+                Tool::new(
+                    "admin",
+                    "Administer",
+                    std::sync::Arc::new(JsonObject::new()),
+                ),
+                |_| Box::pin(async { Ok(CallToolResult::success(vec![Content::text("done")])) }),
+            )
+            .with_policy(ToolPolicy::custom(|identity| {
+                identity.subject.as_deref() == Some("root")
+            })),
+        );
+        Self { tool_router }
+    }
+
+    #[tool(description = "Read the repository")]
+    fn status(&self) -> String {
+        "clean".into()
+    }
+
+    #[tool(description = "Push commits", scopes = ["repo:write"])]
+    fn push(&self) -> String {
+        "pushed".into()
+    }
+}
+
+/// Authenticates each request with the identity of the session, as a transport middleware
+/// would.
+#[derive(Debug, Clone)]
+struct Session {
+    repo: Repo,
+    identity: Option<Identity>,
+}
+
+impl ServerHandler for Session {
+    async fn call_tool(
+        &self,
+        request: CallToolRequestParams,
+        mut context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, ErrorData> {
+        if let Some(identity) = &self.identity {
+            context.extensions.insert(identity.clone());
+        }
+        let context =
+            rmcp::handler::server::tool::ToolCallContext::new(&self.repo, request, context);
+        self.repo.tool_router.call(context).await
+    }
+
+    async fn list_tools(
+        &self,
+        request: Option<PaginatedRequestParams>,
+        mut context: RequestContext<RoleServer>,
+    ) -> Result<ListToolsResult, ErrorData> {
+        if let Some(identity) = &self.identity {
+            context.extensions.insert(identity.clone());
+        }
+        let page = self
+            .repo
+            .tool_router
+            .list_authorized_page(request.as_ref(), &context.extensions)?;
+        Ok(ListToolsResult {
+            tools: page.items,
+            next_cursor: page.next_cursor,
+            meta: None,
+        })
+    }
+}
+
+async fn connect(identity: Option<Identity>) -> anyhow::Result<RunningService<RoleClient, ()>> {
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    let session = Session {
+        repo: Repo::new(),
+        identity,
+    };
+    tokio::spawn(async move {
+        session.serve(server_transport).await?.waiting().await?;
+        anyhow::Ok(())
+    });
+    Ok(().serve(client_transport).await?)
+}
+
+async fn tool_names(client: &RunningService<RoleClient, ()>) -> anyhow::Result<Vec<String>> {
+    let mut names = client
+        .list_all_tools()
+        .await?
+        .into_iter()
+        .map(|tool| tool.name.into_owned())
+        .collect::<Vec<_>>();
+    names.sort();
+    Ok(names)
+}
+
+async fn call(
+    client: &RunningService<RoleClient, ()>,
+    name: &'static str,
+) -> Result<CallToolResult, ServiceError> {
+    client
+        .call_tool(CallToolRequestParams {
+            meta: None,
+            name: name.into(),
+            arguments: None,
+            task: None,
+        })
+        .await
+}
+
+#[tokio::test]
+async fn test_scopes() -> anyhow::Result<()> {
+    let reader = connect(Some(Identity::new("alice").with_scopes(["repo:read"]))).await?;
+    assert_eq!(tool_names(&reader).await?, ["status"]);
+    call(&reader, "status").await?;
+    // as unknown as a tool which doesn't exist
+    let error = call(&reader, "push").await.unwrap_err();
+    let ServiceError::McpError(error) = error else {
+        panic!("unexpected error {error:?}");
+    };
+    let ServiceError::McpError(unknown) = call(&reader, "pull").await.unwrap_err() else {
+        panic!("unexpected error");
+    };
+    assert_eq!(error, unknown);
+    assert_eq!(error.code, ErrorCode::INVALID_PARAMS);
+    assert_eq!(error.data, None);
+    reader.cancel().await?;
+
+    let writer = connect(Some(
+        Identity::new("bob").with_scopes(["repo:read", "repo:write"]),
+    ))
+    .await?;
+    assert_eq!(tool_names(&writer).await?, ["push", "status"]);
+    call(&writer, "push").await?;
+    writer.cancel().await?;
+
+    // anonymous callers only get the tools without a policy
+    let anonymous = connect(None).await?;
+    assert_eq!(tool_names(&anonymous).await?, ["status"]);
+    assert!(call(&anonymous, "push").await.is_err());
+    anonymous.cancel().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_custom_policy() -> anyhow::Result<()> {
+    let root = connect(Some(Identity::new("root"))).await?;
+    assert_eq!(tool_names(&root).await?, ["admin", "status"]);
+    call(&root, "admin").await?;
+    root.cancel().await?;
+
+    let alice = connect(Some(Identity::new("alice"))).await?;
+    assert!(call(&alice, "admin").await.is_err());
+    alice.cancel().await?;
+    Ok(())
+}
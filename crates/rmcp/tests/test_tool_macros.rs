@@ -10,7 +10,7 @@ use rmcp::{
     ClientHandler, ServerHandler, ServiceExt,
     handler::server::{router::tool::ToolRouter, wrapper::Parameters},
     model::{CallToolRequestParams, ClientInfo},
-    tool, tool_handler, tool_router,
+    tool, tool_handler, tool_parameters, tool_router,
 };
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
@@ -371,3 +371,53 @@ async fn test_optional_i64_field_with_null_input() -> anyhow::Result<()> {
     server_handle.await??;
     Ok(())
 }
+
+#[derive(Debug, Clone, Default)]
+struct Files;
+
+#[tool_parameters(lints = "deny")]
+#[derive(Deserialize, JsonSchema)]
+struct DeleteFileRequest {
+    /// The path of the file
+    path: String,
+    #[schemars(description = "Delete the file even if it is read-only")]
+    #[serde(default)]
+    force: bool,
+}
+
+#[tool_router(router = files_router, lints = "deny")]
+impl Files {
+    /// Delete a file
+    #[tool(infer_annotations)]
+    fn delete_file(&self, Parameters(_request): Parameters<DeleteFileRequest>) {}
+
+    /// List the files, which may change
+    #[tool(infer_annotations, annotations(idempotent_hint = false))]
+    fn list_files(&self) {}
+
+    /// Tell the status of the disk
+    #[tool(infer_annotations)]
+    fn status(&self) {}
+}
+
+#[test]
+fn test_infer_annotations() {
+    let tool = Files::delete_file_tool_attr();
+    let properties = tool.input_schema["properties"].as_object().unwrap();
+    assert!(
+        properties
+            .values()
+            .all(|property| property.get("description").is_some())
+    );
+    let annotations = tool.annotations.unwrap();
+    assert_eq!(annotations.read_only_hint, Some(false));
+    assert_eq!(annotations.destructive_hint, Some(true));
+
+    let annotations = Files::list_files_tool_attr().annotations.unwrap();
+    assert_eq!(annotations.read_only_hint, Some(true));
+    assert_eq!(annotations.idempotent_hint, Some(false));
+
+    // no verb to infer from
+    assert!(Files::status_tool_attr().annotations.is_none());
+    assert_eq!(Files::files_router().list_all().len(), 3);
+}
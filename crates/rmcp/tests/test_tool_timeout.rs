@@ -0,0 +1,182 @@
+//cargo test --test test_tool_timeout --features "client server"
+use std::{sync::Arc, time::Duration};
+
+use rmcp::{
+    ServerHandler, ServiceExt,
+    handler::server::{
+        router::tool::{TimeoutResponse, ToolRoute, ToolRouter},
+        tool::ToolCallContext,
+    },
+    model::*,
+    service::{PeerRequestOptions, RequestContext, RoleClient, RunningService, ServiceError},
+    tool, tool_handler, tool_router,
+};
+use tokio::sync::mpsc;
+use tokio_util::sync::CancellationToken;
+
+/// Reports when it is dropped, i.e. when the tool future holding it is dropped.
+struct DropSignal(mpsc::UnboundedSender<&'static str>);
+
+impl Drop for DropSignal {
+    fn drop(&mut self) {
+        let _ = self.0.send("dropped");
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Worker {
+    tool_router: ToolRouter<Self>,
+    events: mpsc::UnboundedSender<&'static str>,
+}
+
+#[tool_router]
+impl Worker {
+    fn new(tool_router: ToolRouter<Self>, events: mpsc::UnboundedSender<&'static str>) -> Self {
+        Self {
+            tool_router: tool_router.with_route(ToolRoute::new_dyn(
+                Tool::new("forever", "Never returns", Arc::new(JsonObject::new())),
+                |_| Box::pin(std::future::pending()),
+            )),
+            events,
+        }
+    }
+
+    #[tool(description = "Sleep for a minute", timeout = "50ms")]
+    async fn sleep(&self) -> String {
+        let _signal = DropSignal(self.events.clone());
+        tokio::time::sleep(Duration::from_secs(60)).await;
+        "awake".into()
+    }
+
+    #[tool(description = "Wait until cancelled")]
+    async fn wait(&self, ct: CancellationToken) -> String {
+        let _signal = DropSignal(self.events.clone());
+        let _ = self.events.send("started");
+        ct.cancelled().await;
+        // never reached, the future is dropped first
+        tokio::time::sleep(Duration::from_secs(60)).await;
+        "cancelled".into()
+    }
+}
+
+#[tool_handler]
+impl ServerHandler for Worker {}
+
+async fn serve(
+    tool_router: ToolRouter<Worker>,
+) -> anyhow::Result<(
+    RunningService<RoleClient, ()>,
+    mpsc::UnboundedReceiver<&'static str>,
+)> {
+    let (events, received) = mpsc::unbounded_channel();
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    tokio::spawn(async move {
+        Worker::new(tool_router, events)
+            .serve(server_transport)
+            .await?
+            .waiting()
+            .await?;
+        anyhow::Ok(())
+    });
+    Ok((().serve(client_transport).await?, received))
+}
+
+fn call(name: &'static str) -> CallToolRequestParams {
+    CallToolRequestParams {
+        meta: None,
+        name: name.into(),
+        arguments: None,
+        task: None,
+    }
+}
+
+#[tokio::test]
+async fn test_timeout_error() -> anyhow::Result<()> {
+    let (client, mut events) = serve(Worker::tool_router()).await?;
+
+    let error = client.call_tool(call("sleep")).await.unwrap_err();
+    let ServiceError::McpError(error) = error else {
+        panic!("unexpected error {error:?}");
+    };
+    assert_eq!(error.code, ErrorCode::INTERNAL_ERROR);
+    assert!(error.message.contains("timed out"), "{}", error.message);
+    assert_eq!(events.recv().await, Some("dropped"));
+
+    // no timeout by default
+    let result = tokio::time::timeout(
+        Duration::from_millis(100),
+        client.call_tool(call("forever")),
+    )
+    .await;
+    assert!(result.is_err());
+
+    client.cancel().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_timeout_tool_error() -> anyhow::Result<()> {
+    let tool_router = Worker::tool_router()
+        .with_default_timeout(Duration::from_millis(50))
+        .with_timeout_response(TimeoutResponse::ToolError);
+    let (client, _events) = serve(tool_router).await?;
+
+    for name in ["sleep", "forever"] {
+        let result = client.call_tool(call(name)).await?;
+        assert_eq!(result.is_error, Some(true));
+        let text = result.content[0].as_text().expect("text").text.clone();
+        assert!(text.contains("timed out"), "{text}");
+    }
+
+    client.cancel().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_cancellation_drops_the_call() -> anyhow::Result<()> {
+    let (client, mut events) = serve(Worker::tool_router()).await?;
+
+    let handle = client
+        .send_cancellable_request(
+            ClientRequest::CallToolRequest(Request::new(call("wait"))),
+            PeerRequestOptions::no_options(),
+        )
+        .await?;
+    assert_eq!(events.recv().await, Some("started"));
+    handle.cancel(Some("changed my mind".into())).await?;
+    let dropped = tokio::time::timeout(Duration::from_secs(5), events.recv()).await?;
+    assert_eq!(dropped, Some("dropped"));
+
+    client.cancel().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_cancelled_call_error() -> anyhow::Result<()> {
+    let (events, _received) = mpsc::unbounded_channel();
+    let worker = Worker::new(Worker::tool_router(), events);
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    let client = tokio::spawn(async move { ().serve(client_transport).await });
+    let server = worker.clone().serve(server_transport).await?;
+    let client = client.await??;
+
+    let ct = CancellationToken::new();
+    ct.cancel();
+    let context = RequestContext {
+        ct,
+        id: NumberOrString::Number(1),
+        meta: Default::default(),
+        extensions: Default::default(),
+        peer: server.peer().clone(),
+    };
+    let error = worker
+        .tool_router
+        .call(ToolCallContext::new(&worker, call("forever"), context))
+        .await
+        .unwrap_err();
+    assert_eq!(error.code, ErrorCode::REQUEST_CANCELLED);
+    assert!(error.message.contains("cancelled"), "{}", error.message);
+
+    client.cancel().await?;
+    Ok(())
+}
@@ -0,0 +1,204 @@
+//cargo test --test test_visibility --features "client server macros"
+use std::sync::{
+    Arc,
+    atomic::{AtomicBool, Ordering},
+};
+
+use rmcp::{
+    ClientHandler, RoleServer, ServerHandler, ServiceExt,
+    handler::server::router::{prompt::PromptRouter, tool::ToolRouter},
+    model::*,
+    prompt, prompt_handler, prompt_router,
+    service::{NotificationContext, RequestContext, RoleClient, RunningService},
+    tool, tool_handler, tool_router,
+};
+use tokio::sync::mpsc;
+
+/// Whether the session is an insider, as announced by the client.
+fn is_insider(context: &RequestContext<RoleServer>) -> bool {
+    context
+        .peer
+        .peer_info()
+        .is_some_and(|info| info.client_info.name == "insider")
+}
+
+#[derive(Debug, Clone)]
+struct Lab {
+    tool_router: ToolRouter<Self>,
+    prompt_router: PromptRouter<Self>,
+    unlocked: Arc<AtomicBool>,
+}
+
+#[tool_router]
+impl Lab {
+    fn new() -> Self {
+        let unlocked = Arc::new(AtomicBool::new(false));
+        let tool_router = Self::tool_router().with_filter({
+            let unlocked = unlocked.clone();
+            move |tool, _context| tool.name != "experiment" || unlocked.load(Ordering::SeqCst)
+        });
+        let prompt_router = Self::prompt_router()
+            .with_filter(|prompt, context| prompt.name != "secret" || is_insider(context));
+        Self {
+            tool_router,
+            prompt_router,
+            unlocked,
+        }
+    }
+
+    #[tool(description = "Unlock the experiment")]
+    async fn unlock(&self, context: RequestContext<RoleServer>) -> String {
+        self.unlocked.store(true, Ordering::SeqCst);
+        let changed = self.tool_router.refresh_visibility(&context).await;
+        format!("changed: {changed}")
+    }
+
+    #[tool(description = "Run the experiment")]
+    fn experiment(&self) -> String {
+        "eureka".into()
+    }
+}
+
+#[prompt_router]
+impl Lab {
+    /// Reveal the secret
+    #[prompt]
+    fn secret(&self) -> Vec<PromptMessage> {
+        vec![PromptMessage::new_text(PromptMessageRole::User, "42")]
+    }
+
+    /// Greet
+    #[prompt]
+    fn greet(&self) -> Vec<PromptMessage> {
+        vec![PromptMessage::new_text(PromptMessageRole::User, "hello")]
+    }
+}
+
+#[tool_handler]
+#[prompt_handler]
+impl ServerHandler for Lab {
+    fn get_info(&self) -> ServerInfo {
+        ServerInfo {
+            capabilities: ServerCapabilities::builder()
+                .enable_tools()
+                .enable_tool_list_changed()
+                .enable_prompts()
+                .build(),
+            ..Default::default()
+        }
+    }
+}
+
+struct Client {
+    name: &'static str,
+    changes: mpsc::UnboundedSender<()>,
+}
+
+impl ClientHandler for Client {
+    fn get_info(&self) -> ClientInfo {
+        ClientInfo {
+            client_info: Implementation {
+                name: self.name.into(),
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    async fn on_tool_list_changed(&self, _context: NotificationContext<RoleClient>) {
+        let _ = self.changes.send(());
+    }
+}
+
+async fn connect(
+    name: &'static str,
+) -> anyhow::Result<(
+    RunningService<RoleClient, Client>,
+    mpsc::UnboundedReceiver<()>,
+)> {
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    tokio::spawn(async move {
+        Lab::new().serve(server_transport).await?.waiting().await?;
+        anyhow::Ok(())
+    });
+    let (changes, received) = mpsc::unbounded_channel();
+    Ok((
+        Client { name, changes }.serve(client_transport).await?,
+        received,
+    ))
+}
+
+async fn tool_names(client: &RunningService<RoleClient, Client>) -> anyhow::Result<Vec<String>> {
+    let mut names = client
+        .list_all_tools()
+        .await?
+        .into_iter()
+        .map(|tool| tool.name.into_owned())
+        .collect::<Vec<_>>();
+    names.sort();
+    Ok(names)
+}
+
+async fn call(
+    client: &RunningService<RoleClient, Client>,
+    name: &'static str,
+) -> anyhow::Result<String> {
+    let result = client
+        .call_tool(CallToolRequestParams {
+            meta: None,
+            name: name.into(),
+            arguments: None,
+            task: None,
+        })
+        .await?;
+    Ok(result.content[0].as_text().expect("text").text.clone())
+}
+
+#[tokio::test]
+async fn test_tool_visibility() -> anyhow::Result<()> {
+    let (client, mut changes) = connect("guest").await?;
+    assert_eq!(tool_names(&client).await?, ["unlock"]);
+    let error = call(&client, "experiment").await.unwrap_err();
+    assert!(error.to_string().contains("tool not found"), "{error}");
+
+    assert_eq!(call(&client, "unlock").await?, "changed: true");
+    changes.recv().await.expect("tools/list_changed");
+    assert_eq!(tool_names(&client).await?, ["experiment", "unlock"]);
+    assert_eq!(call(&client, "experiment").await?, "eureka");
+
+    // nothing changed since the last listing
+    assert_eq!(call(&client, "unlock").await?, "changed: false");
+
+    client.cancel().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_prompt_visibility() -> anyhow::Result<()> {
+    for (name, visible) in [("guest", false), ("insider", true)] {
+        let (client, _changes) = connect(name).await?;
+        let mut prompts = client
+            .list_all_prompts()
+            .await?
+            .into_iter()
+            .map(|prompt| prompt.name)
+            .collect::<Vec<_>>();
+        prompts.sort();
+        let expected: &[&str] = if visible {
+            &["greet", "secret"]
+        } else {
+            &["greet"]
+        };
+        assert_eq!(prompts, expected);
+        let secret = client
+            .get_prompt(GetPromptRequestParams {
+                meta: None,
+                name: "secret".into(),
+                arguments: None,
+            })
+            .await;
+        assert_eq!(secret.is_ok(), visible);
+        client.cancel().await?;
+    }
+    Ok(())
+}